@@ -0,0 +1,315 @@
+//! Download categories and tags
+//!
+//! A category is a named set of defaults from `[[categories]]` in the config.
+//! It applies when given with `--category`, or when one of its URL, extension
+//! or host matchers fits the added input; the first matching category wins.
+//! The CLI records the category and any `--tag`s in the store, and the
+//! category name counts as a tag when filtering.
+
+use anyhow::{bail, Context, Result};
+use gosh_dl::{DownloadOptions, DownloadStatus};
+use std::path::Path;
+
+use crate::config::CategoryConfig;
+use crate::input::url_parser::ParsedInput;
+use crate::store::DownloadExtras;
+use crate::util::parse_speed;
+
+impl CategoryConfig {
+    pub fn validate(&self) -> Result<()> {
+        parse_tag(&self.name).context("Invalid category name")?;
+        if let Some(ref speed) = self.max_speed {
+            parse_speed(speed).context("max_speed is not a valid speed")?;
+        }
+        if self.max_connections == Some(0) {
+            bail!("max_connections must be at least 1");
+        }
+        if self.seed_ratio.is_some_and(|r| r < 0.0) {
+            bail!("seed_ratio must not be negative");
+        }
+        Ok(())
+    }
+
+    /// Whether any matcher fits the input. A category without matchers only
+    /// applies when asked for by name.
+    pub fn matches(&self, input: &ParsedInput) -> bool {
+        let target = match input {
+            ParsedInput::Http(url) | ParsedInput::Magnet(url) => url.clone(),
+            ParsedInput::TorrentFile(path) => path.display().to_string(),
+        };
+        if self.urls.iter().any(|p| url_matches(p, &target)) {
+            return true;
+        }
+
+        if let Some(ext) = extension(input) {
+            let wanted = |e: &String| e.trim_start_matches('.').eq_ignore_ascii_case(&ext);
+            if self.extensions.iter().any(wanted) {
+                return true;
+            }
+        }
+
+        match input {
+            ParsedInput::Http(url) => {
+                let host = host(url);
+                self.hosts.iter().any(|h| host_matches(h, &host))
+            }
+            _ => false,
+        }
+    }
+
+    /// Download options the category sets; command-line flags go on top
+    pub fn options(&self) -> DownloadOptions {
+        DownloadOptions {
+            save_dir: self.dir.clone(),
+            priority: self.priority.unwrap_or_default(),
+            // Checked by CliConfig::validate
+            max_download_speed: self.max_speed.as_deref().and_then(|s| parse_speed(s).ok()),
+            max_connections: self.max_connections,
+            ..Default::default()
+        }
+    }
+}
+
+/// The category to use for an input: the one named, or the first that matches
+pub fn resolve<'a>(
+    categories: &'a [CategoryConfig],
+    name: Option<&str>,
+    input: &ParsedInput,
+) -> Result<Option<&'a CategoryConfig>> {
+    match name {
+        Some(name) => named(categories, name).map(Some),
+        None => Ok(matching(categories, input)),
+    }
+}
+
+/// The first category whose matchers fit the input
+pub fn matching<'a>(
+    categories: &'a [CategoryConfig],
+    input: &ParsedInput,
+) -> Option<&'a CategoryConfig> {
+    categories.iter().find(|c| c.matches(input))
+}
+
+pub fn named<'a>(categories: &'a [CategoryConfig], name: &str) -> Result<&'a CategoryConfig> {
+    categories
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(name.trim()))
+        .with_context(|| format!("Unknown category '{}'", name.trim()))
+}
+
+/// Normalize a tag: trimmed, lowercase, without spaces or commas
+pub fn parse_tag(s: &str) -> Result<String> {
+    let tag = s.trim().to_lowercase();
+    if tag.is_empty() {
+        bail!("Tag cannot be empty");
+    }
+    if tag.contains(|c: char| c == ',' || c.is_whitespace()) {
+        bail!(
+            "Invalid tag '{}'. Tags cannot contain spaces or commas",
+            s.trim()
+        );
+    }
+    Ok(tag)
+}
+
+pub fn parse_tags(tags: &[String]) -> Result<Vec<String>> {
+    let mut parsed: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = parse_tag(tag)?;
+        if !parsed.contains(&tag) {
+            parsed.push(tag);
+        }
+    }
+    Ok(parsed)
+}
+
+/// Start a category hook in the background. The command runs through the
+/// shell with the download described in `GOSH_*` environment variables.
+pub fn run_hook(
+    command: &str,
+    dl: &DownloadStatus,
+    extras: &DownloadExtras,
+    error: Option<&str>,
+) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    let path = dl
+        .metadata
+        .save_dir
+        .join(dl.metadata.filename.as_deref().unwrap_or(&dl.metadata.name));
+    let url = dl
+        .metadata
+        .url
+        .as_deref()
+        .or(dl.metadata.magnet_uri.as_deref())
+        .unwrap_or_default();
+    cmd.arg(command)
+        .env("GOSH_GID", dl.id.to_gid())
+        .env("GOSH_NAME", &dl.metadata.name)
+        .env("GOSH_PATH", path)
+        .env("GOSH_URL", url)
+        .env(
+            "GOSH_CATEGORY",
+            extras.category.as_deref().unwrap_or_default(),
+        )
+        .env("GOSH_TAGS", extras.tags.join(","))
+        .env("GOSH_ERROR", error.unwrap_or_default())
+        .stdin(std::process::Stdio::null());
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to run hook '{}'", command))?;
+    let command = command.to_string();
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) if !status.success() => {
+                tracing::warn!("Hook '{}' exited with {}", command, status)
+            }
+            Err(e) => tracing::warn!("Hook '{}' failed: {}", command, e),
+            Ok(_) => {}
+        }
+    });
+    Ok(())
+}
+
+/// Extension of the file an input names, lowercase
+fn extension(input: &ParsedInput) -> Option<String> {
+    let name = match input {
+        ParsedInput::Http(url) => {
+            let path = url.split(['?', '#']).next().unwrap_or(url);
+            let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+            path.split_once('/')?.1.rsplit('/').next()?.to_string()
+        }
+        _ => input.display(),
+    };
+    Path::new(&name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+}
+
+/// Host of a URL, lowercase, without credentials or port
+fn host(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host.to_lowercase()
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().trim_start_matches("*.").to_lowercase();
+    !pattern.is_empty()
+        && (host == pattern
+            || host
+                .strip_suffix(&pattern)
+                .is_some_and(|sub| sub.ends_with('.')))
+}
+
+/// `*` matches any run of characters over the whole URL; a pattern without
+/// one matches anywhere in it
+pub fn url_matches(pattern: &str, url: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let url = url.to_lowercase();
+    if pattern.is_empty() {
+        return false;
+    }
+    if !pattern.contains('*') {
+        return url.contains(&pattern);
+    }
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !url.starts_with(first) || !url.ends_with(last) || url.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &url[first.len()..url.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::url_parser::parse_input;
+
+    fn category(name: &str) -> CategoryConfig {
+        CategoryConfig {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn matchers() {
+        let iso = CategoryConfig {
+            extensions: vec![".iso".to_string()],
+            ..category("iso")
+        };
+        let work = CategoryConfig {
+            hosts: vec!["example.com".to_string()],
+            urls: vec!["https://*/builds/*.tar.gz".to_string()],
+            ..category("work")
+        };
+        let input = |s| parse_input(s).unwrap();
+
+        assert!(iso.matches(&input("https://mirror.org/debian.ISO?dl=1")));
+        assert!(!iso.matches(&input("https://mirror.org/iso/readme")));
+        assert!(iso.matches(&input("magnet:?xt=urn:btih:abc&dn=ubuntu.iso")));
+
+        assert!(work.matches(&input("https://files.example.com/a")));
+        assert!(work.matches(&input("http://user@example.com:8080/a")));
+        assert!(!work.matches(&input("https://notexample.com/a")));
+        assert!(work.matches(&input("https://ci.org/builds/app.tar.gz")));
+        assert!(!work.matches(&input("https://ci.org/builds/app.tar.gz.sig")));
+        assert!(!category("bare").matches(&input("https://example.com/a.iso")));
+
+        let categories = [iso, work];
+        let found = resolve(&categories, None, &input("https://example.com/x.iso")).unwrap();
+        assert_eq!(found.unwrap().name, "iso");
+        let found = resolve(&categories, Some("Work"), &input("https://a.org/x.iso")).unwrap();
+        assert_eq!(found.unwrap().name, "work");
+        assert!(resolve(&categories, Some("music"), &input("https://a.org/")).is_err());
+    }
+
+    #[test]
+    fn tags_are_normalized() {
+        assert_eq!(
+            parse_tags(&["ISO".to_string(), " work ".to_string(), "iso".to_string()]).unwrap(),
+            ["iso", "work"]
+        );
+        assert!(parse_tag("").is_err());
+        assert!(parse_tag("a,b").is_err());
+        assert!(parse_tag("two words").is_err());
+    }
+
+    #[test]
+    fn validation() {
+        assert!(category("iso").validate().is_ok());
+        assert!(category("").validate().is_err());
+        let fast = CategoryConfig {
+            max_speed: Some("fast".to_string()),
+            ..category("iso")
+        };
+        assert!(fast.validate().is_err());
+        let none = CategoryConfig {
+            max_connections: Some(0),
+            ..category("iso")
+        };
+        assert!(none.validate().is_err());
+    }
+}
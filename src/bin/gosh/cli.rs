@@ -1,6 +1,12 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
+use crate::filter::Filter;
+use crate::hold::AfterFailure;
+use crate::output::columns::{Column, SortKey};
+use crate::output::template::Template;
+use crate::progress::ProgressInterval;
+
 #[derive(Parser)]
 #[command(name = "gosh")]
 #[command(
@@ -9,6 +15,7 @@ use std::path::PathBuf;
     about = "Fast download manager with HTTP and BitTorrent support"
 )]
 #[command(propagate_version = true)]
+#[command(disable_help_subcommand = true)]
 #[command(
     after_help = "Run 'gosh' without arguments to start the interactive TUI.\n\
     Or pass URLs directly: gosh https://example.com/file.zip"
@@ -30,6 +37,15 @@ pub struct Cli {
     #[arg(long, value_enum, default_value = "table", global = true)]
     pub output: OutputFormat,
 
+    /// Print each result through a template, e.g. '{gid}\t{name}\t{progress:.1}%'
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        global = true,
+        conflicts_with = "output"
+    )]
+    pub format: Option<Template>,
+
     /// Output directory for direct downloads
     #[arg(short = 'd', long, global = true)]
     pub dir: Option<PathBuf>,
@@ -78,6 +94,38 @@ pub struct Cli {
     #[arg(long)]
     pub seed_ratio: Option<f64>,
 
+    /// Stop seeding after this long (for torrents, e.g. "24h")
+    #[arg(long, value_name = "DURATION")]
+    pub seed_time: Option<String>,
+
+    /// Stop seeding after this long without uploading (for torrents, e.g. "30m")
+    #[arg(long, value_name = "DURATION")]
+    pub seed_idle: Option<String>,
+
+    /// Save a .torrent once magnet metadata is fetched (next to the download, or at PATH)
+    #[arg(long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    pub save_torrent: Option<Option<PathBuf>>,
+
+    /// How direct downloads report progress [default: bar on a terminal, plain otherwise]
+    #[arg(long, value_enum)]
+    pub progress: Option<ProgressMode>,
+
+    /// How often --progress plain prints a line: a percentage such as 10% or a duration such as 30s
+    #[arg(long, value_name = "INTERVAL", default_value = "10%")]
+    pub progress_interval: ProgressInterval,
+
+    /// Write --progress json records to this file descriptor instead of stdout
+    #[arg(long, value_name = "FD")]
+    pub progress_fd: Option<i32>,
+
+    /// Write a JSON summary of the run to this file
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// Print the exit codes and what they mean (same as `gosh help exit-codes`)
+    #[arg(long)]
+    pub exit_code_map: bool,
+
     /// Disable DHT for BitTorrent
     #[arg(long)]
     pub no_dht: bool,
@@ -126,6 +174,9 @@ pub enum Commands {
     /// List all downloads
     List(ListArgs),
 
+    /// Live view of the download list (same as `list --watch`)
+    Top(ListArgs),
+
     /// Show detailed status of a download
     Status(StatusArgs),
 
@@ -135,23 +186,53 @@ pub enum Commands {
     /// Resume one or more paused downloads
     Resume(ResumeArgs),
 
+    /// Restart one or more failed downloads
+    Retry(RetryArgs),
+
     /// Cancel and optionally delete one or more downloads
     Cancel(CancelArgs),
 
+    /// Remove completed or failed downloads from the history
+    Purge(PurgeArgs),
+
+    /// Write downloads with their options as JSON, for `gosh import`
+    Export(ExportArgs),
+
+    /// Add the downloads from a `gosh export` file or an aria2 session
+    Import(ImportArgs),
+
     /// Set download priority
     Priority(PriorityArgs),
 
+    /// Move a download within the queue
+    Move(MoveArgs),
+
     /// Show global download/upload statistics
     Stats,
 
     /// Parse and show torrent file information
     Info(InfoArgs),
 
+    /// Seed existing data from a torrent file without downloading it
+    Seed(SeedArgs),
+
+    /// Fetch a magnet link's metadata and show it, without downloading
+    FetchMetadata(FetchMetadataArgs),
+
+    /// Show the bandwidth schedule
+    Schedule(ScheduleArgs),
+
+    /// Show data usage against the quota caps
+    Quota,
+
     /// Manage configuration
     Config(ConfigArgs),
 
     /// Generate shell completions
     Completions(CompletionsArgs),
+
+    /// Show help for a command, or `gosh help exit-codes`
+    Help(HelpArgs),
 }
 
 #[derive(Args)]
@@ -173,14 +254,34 @@ pub struct AddArgs {
     #[arg(short = 'o', long)]
     pub out: Option<String>,
 
-    /// Download priority
-    #[arg(short = 'p', long, value_enum, default_value = "normal")]
-    pub priority: Priority,
+    /// Download priority [default: the category's, or normal]
+    #[arg(short = 'p', long, value_enum)]
+    pub priority: Option<Priority>,
+
+    /// Tag the download (repeatable)
+    #[arg(short = 't', long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Use this category's defaults instead of the first one that matches
+    #[arg(long, value_name = "NAME")]
+    pub category: Option<String>,
 
     /// Wait for download to complete (show progress)
     #[arg(short = 'w', long)]
     pub wait: bool,
 
+    /// With --wait, write a JSON summary of the run to this file
+    #[arg(long, value_name = "FILE", requires = "wait")]
+    pub report: Option<PathBuf>,
+
+    /// How --wait reports progress: bar, plain or none [default: bar on a terminal, plain otherwise]
+    #[arg(long, value_enum, value_name = "MODE", requires = "wait")]
+    pub progress: Option<ProgressMode>,
+
+    /// How often --progress plain prints a line, e.g. 10% or 30s
+    #[arg(long, value_name = "INTERVAL", default_value = "10%")]
+    pub progress_interval: ProgressInterval,
+
     /// Custom headers (format: "Name: Value")
     #[arg(short = 'H', long = "header", value_name = "HEADER")]
     pub headers: Vec<String>,
@@ -220,6 +321,30 @@ pub struct AddArgs {
     /// Seed ratio limit (for torrents, e.g., 1.0 = upload same amount as downloaded)
     #[arg(long)]
     pub seed_ratio: Option<f64>,
+
+    /// Stop seeding after this long (for torrents, e.g. "24h")
+    #[arg(long, value_name = "DURATION")]
+    pub seed_time: Option<String>,
+
+    /// Stop seeding after this long without uploading (for torrents, e.g. "30m")
+    #[arg(long, value_name = "DURATION")]
+    pub seed_idle: Option<String>,
+
+    /// Save a .torrent once magnet metadata is fetched (next to the download, or at PATH)
+    #[arg(long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    pub save_torrent: Option<Option<PathBuf>>,
+
+    /// Keep the download paused until this time ("02:00" or "2026-10-20T01:00")
+    #[arg(long, value_name = "TIME")]
+    pub start_at: Option<String>,
+
+    /// Start only once this download has completed
+    #[arg(long, value_name = "GID")]
+    pub after: Option<String>,
+
+    /// What to do if the --after download fails
+    #[arg(long, value_enum, default_value = "fail", requires = "after")]
+    pub after_failure: AfterFailure,
 }
 
 #[derive(Args)]
@@ -228,9 +353,37 @@ pub struct ListArgs {
     #[arg(short = 's', long, value_enum)]
     pub state: Option<StateFilter>,
 
+    /// Show only downloads with this tag or category
+    #[arg(short = 't', long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Show only downloads matching this expression, e.g. 'kind=torrent and size>1G'
+    #[arg(short = 'w', long = "where", value_name = "EXPR")]
+    pub filter: Option<Filter>,
+
+    /// Columns to show, comma-separated: id, name, size, progress, down, up, eta, state, priority, peers, ratio, created, dir
+    #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<Column>,
+
+    /// Sort by a column instead of queue order, e.g. 'size' or 'speed:desc'
+    #[arg(long, value_name = "COLUMN[:desc]")]
+    pub sort: Option<SortKey>,
+
+    /// Show at most this many downloads
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
     /// Show only download IDs (useful for scripting)
-    #[arg(long)]
+    #[arg(long, conflicts_with = "watch")]
     pub ids_only: bool,
+
+    /// Keep the list on screen, refreshing it until Ctrl-C
+    #[arg(long)]
+    pub watch: bool,
+
+    /// How often --watch refreshes, e.g. 1s, 5s or 1m
+    #[arg(long, value_name = "DURATION", default_value = "1s")]
+    pub interval: String,
 }
 
 #[derive(Args)]
@@ -250,21 +403,53 @@ pub struct StatusArgs {
 #[derive(Args)]
 pub struct PauseArgs {
     /// Download IDs to pause (use 'all' to pause all active downloads)
-    #[arg(required = true)]
+    #[arg(required_unless_present_any = ["tag", "filter"])]
     pub ids: Vec<String>,
+
+    /// Pause the active downloads with this tag or category (only those of the IDs given, if any)
+    #[arg(short = 't', long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    #[command(flatten)]
+    pub select: SelectArgs,
 }
 
 #[derive(Args)]
 pub struct ResumeArgs {
     /// Download IDs to resume (use 'all' to resume all paused downloads)
-    #[arg(required = true)]
+    #[arg(required_unless_present_any = ["tag", "filter"])]
     pub ids: Vec<String>,
+
+    /// Resume the paused downloads with this tag or category (only those of the IDs given, if any)
+    #[arg(short = 't', long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    #[command(flatten)]
+    pub select: SelectArgs,
+}
+
+#[derive(Args)]
+pub struct RetryArgs {
+    /// Download IDs to retry (use 'all' to retry all failed downloads)
+    #[arg(required_unless_present = "filter")]
+    pub ids: Vec<String>,
+
+    /// Download from this URL instead, e.g. to replace an expired link (one download only)
+    #[arg(long, value_name = "URL")]
+    pub new_url: Option<String>,
+
+    /// Retry downloads whose error is not retryable without asking
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
+    #[command(flatten)]
+    pub select: SelectArgs,
 }
 
 #[derive(Args)]
 pub struct CancelArgs {
     /// Download IDs to cancel
-    #[arg(required = true)]
+    #[arg(required_unless_present = "filter")]
     pub ids: Vec<String>,
 
     /// Also delete downloaded files
@@ -274,16 +459,97 @@ pub struct CancelArgs {
     /// Skip confirmation prompt
     #[arg(short = 'y', long)]
     pub yes: bool,
+
+    #[command(flatten)]
+    pub select: SelectArgs,
+}
+
+#[derive(Args)]
+pub struct PurgeArgs {
+    /// Remove completed downloads (with neither flag, both kinds go)
+    #[arg(long)]
+    pub completed: bool,
+
+    /// Remove failed downloads
+    #[arg(long)]
+    pub failed: bool,
+
+    /// Only those that finished at least this long ago (e.g. 7d, 12h)
+    #[arg(long, value_name = "DURATION")]
+    pub older_than: Option<String>,
+
+    /// Also delete downloaded files
+    #[arg(long)]
+    pub delete_files: bool,
+
+    /// Skip confirmation prompt
+    #[arg(short = 'y', long)]
+    pub yes: bool,
 }
 
 #[derive(Args)]
+pub struct ExportArgs {
+    /// Export only downloads in these states (default: all)
+    #[arg(short = 's', long, value_enum, value_delimiter = ',')]
+    pub state: Vec<StateFilter>,
+
+    /// Export only downloads with this tag or category
+    #[arg(short = 't', long, value_name = "TAG")]
+    pub tag: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Export file to read ('-' for stdin)
+    #[arg(
+        required_unless_present = "aria2_session",
+        conflicts_with = "aria2_session"
+    )]
+    pub file: Option<PathBuf>,
+
+    /// Read an aria2c session file (from --save-session) instead
+    #[arg(long, value_name = "FILE")]
+    pub aria2_session: Option<PathBuf>,
+
+    /// Show what would be added without adding anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+#[command(allow_missing_positional = true)]
 pub struct PriorityArgs {
-    /// Download ID
-    pub id: String,
+    /// Download IDs (without IDs, --where sets it on every match)
+    #[arg(required_unless_present = "filter")]
+    pub ids: Vec<String>,
 
     /// New priority level
     #[arg(value_enum)]
     pub priority: Priority,
+
+    #[command(flatten)]
+    pub select: SelectArgs,
+}
+
+/// Selecting downloads by expression, for commands that act on several
+#[derive(Args)]
+pub struct SelectArgs {
+    /// Only downloads matching this expression, e.g. 'kind=torrent and state=seeding' (without IDs, all downloads are checked)
+    #[arg(short = 'w', long = "where", value_name = "EXPR")]
+    pub filter: Option<Filter>,
+
+    /// List the matching downloads without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct MoveArgs {
+    /// Download ID
+    pub id: String,
+
+    /// New position: top, bottom, up, down, or a number (1 = front)
+    pub position: String,
 }
 
 #[derive(Args)]
@@ -292,6 +558,50 @@ pub struct InfoArgs {
     pub file: PathBuf,
 }
 
+#[derive(Args)]
+pub struct SeedArgs {
+    /// Path to torrent file
+    pub file: PathBuf,
+
+    /// Directory holding the torrent's data
+    #[arg(long, value_name = "DIR")]
+    pub data: PathBuf,
+
+    /// Seed even if some pieces are missing or corrupt (they will be downloaded)
+    #[arg(long)]
+    pub allow_partial: bool,
+
+    /// Seed ratio limit
+    #[arg(long)]
+    pub seed_ratio: Option<f64>,
+
+    /// Stop seeding after this long (e.g. "24h")
+    #[arg(long, value_name = "DURATION")]
+    pub seed_time: Option<String>,
+
+    /// Stop seeding after this long without uploading (e.g. "30m")
+    #[arg(long, value_name = "DURATION")]
+    pub seed_idle: Option<String>,
+
+    /// Keep seeding in the foreground until a seeding limit is reached
+    #[arg(short = 'w', long)]
+    pub wait: bool,
+}
+
+#[derive(Args)]
+pub struct FetchMetadataArgs {
+    /// Magnet link
+    pub magnet: String,
+
+    /// Give up after this long (e.g. "2m")
+    #[arg(long, value_name = "DURATION", default_value = "2m")]
+    pub timeout: String,
+
+    /// Also write a .torrent file (current directory, or at PATH)
+    #[arg(long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    pub save_torrent: Option<Option<PathBuf>>,
+}
+
 #[derive(Args)]
 pub struct ConfigArgs {
     #[command(subcommand)]
@@ -318,6 +628,18 @@ pub enum ConfigAction {
     },
 }
 
+#[derive(Args)]
+pub struct ScheduleArgs {
+    #[command(subcommand)]
+    pub action: ScheduleAction,
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleAction {
+    /// Show the rules, which one is in force and when that next changes
+    Show,
+}
+
 #[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OutputFormat {
     /// Human-readable table format
@@ -326,6 +648,12 @@ pub enum OutputFormat {
     Json,
     /// Pretty-printed JSON
     JsonPretty,
+    /// JSON, one object per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
 }
 
 #[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
@@ -357,6 +685,26 @@ pub struct CompletionsArgs {
     pub shell: clap_complete::Shell,
 }
 
+#[derive(Args)]
+pub struct HelpArgs {
+    /// Command (and subcommand) to show help for, or `exit-codes`
+    #[arg(value_name = "TOPIC")]
+    pub topic: Vec<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// Progress bars on the terminal
+    Bar,
+    /// One line per start, finish and progress interval, for logs
+    Plain,
+    /// No progress output
+    None,
+    /// One JSON record per line: started, progress (bytes, total, speed,
+    /// eta), completed, failed
+    Json,
+}
+
 #[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ColorChoice {
     Auto,
@@ -374,3 +722,16 @@ impl Priority {
         }
     }
 }
+
+impl StateFilter {
+    pub fn matches(self, state: &gosh_dl::DownloadState) -> bool {
+        use gosh_dl::DownloadState;
+        match self {
+            StateFilter::Active => state.is_active(),
+            StateFilter::Waiting => matches!(state, DownloadState::Queued),
+            StateFilter::Paused => matches!(state, DownloadState::Paused),
+            StateFilter::Completed => matches!(state, DownloadState::Completed),
+            StateFilter::Error => matches!(state, DownloadState::Error { .. }),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{bail, Context, Result};
-use gosh_dl::{DownloadEvent, DownloadId, DownloadOptions};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use chrono::{Local, Utc};
+use gosh_dl::{DownloadEvent, DownloadId, DownloadOptions, DownloadState};
+use indicatif::{ProgressBar, ProgressStyle};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::io::{self, BufRead};
@@ -8,12 +9,22 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use crate::app::App;
-use crate::cli::{AddArgs, OutputFormat};
+use crate::category::{self, parse_tags};
+use crate::cli::{AddArgs, ProgressMode};
+use crate::config::CategoryConfig;
+use crate::exit_code::CategoryError;
+use crate::hold::{parse_start_at, pause_new, Hold};
 use crate::input::url_parser::{parse_input, ParsedInput};
-use crate::output::table::print_add_results;
+use crate::output::record::{Output, Record, Value};
+use crate::output::table::{print_add_results, print_report};
+use crate::progress::{multi_progress, resolve_mode, PlainProgress, ProgressInterval};
+use crate::purge;
+use crate::report::{output_path, Outcome, Report};
+use crate::store::{DownloadExtras, Store};
+use crate::supervisor::{Supervisor, SupervisorAction};
 use crate::util::{
-    parse_checksum, parse_selected_files, parse_speed, sanitize_filename, truncate_str,
-    validate_max_connections, validate_seed_ratio,
+    parse_checksum, parse_selected_files, parse_speed, resolve_download_id, sanitize_filename,
+    truncate_str, validate_max_connections, validate_seed_ratio,
 };
 
 #[derive(Serialize)]
@@ -23,10 +34,27 @@ pub struct AddResult {
     pub kind: String,
 }
 
-pub async fn execute(args: AddArgs, app: &App, output: OutputFormat) -> Result<()> {
+impl Record for AddResult {
+    const FIELDS: &'static [&'static str] = &["gid", "input", "kind"];
+
+    fn field(&self, name: &str) -> Value {
+        match name {
+            "gid" => self.id.as_str().into(),
+            "input" => self.input.as_str().into(),
+            "kind" => self.kind.as_str().into(),
+            _ => Value::Empty,
+        }
+    }
+}
+
+pub async fn execute(args: AddArgs, app: &App, output: &Output) -> Result<()> {
     // Collect all URLs from various sources
     let mut urls = args.urls.clone();
 
+    if args.progress == Some(ProgressMode::Json) {
+        bail!("--progress json is only supported for direct downloads");
+    }
+
     // Check for stdin input (indicated by '-' in urls)
     if urls.iter().any(|u| u == "-") {
         urls.retain(|u| u != "-");
@@ -42,6 +70,11 @@ pub async fn execute(args: AddArgs, app: &App, output: OutputFormat) -> Result<(
         bail!("No URLs provided. Use positional arguments, -i <file>, or pipe to stdin with '-'");
     }
 
+    // Like the TUI and direct mode, a waiting add runs for a while
+    if args.wait {
+        purge::apply_retention(app.engine(), &app.config).await?;
+    }
+
     // Validate single filename for multiple downloads
     if args.out.is_some() && urls.len() > 1 {
         bail!("Cannot use -o/--out with multiple downloads. Remove -o or add one URL at a time.");
@@ -50,10 +83,33 @@ pub async fn execute(args: AddArgs, app: &App, output: OutputFormat) -> Result<(
     // Parse and categorize inputs
     let inputs: Vec<ParsedInput> = urls.iter().map(|u| parse_input(u)).collect::<Result<_>>()?;
 
+    let seed_limits = DownloadExtras::seed_limits(
+        args.seed_ratio,
+        args.seed_time.as_deref(),
+        args.seed_idle.as_deref(),
+    )?;
+
+    // Holds are resolved up front so a bad time or GID adds nothing
+    let start_at = args
+        .start_at
+        .as_deref()
+        .map(|s| parse_start_at(s, Local::now()))
+        .transpose()?;
+    let after = args
+        .after
+        .as_deref()
+        .map(|gid| resolve_download_id(gid, app.engine()))
+        .transpose()?;
+    let tags = parse_tags(&args.tags)?;
+    if let Some(ref name) = args.category {
+        category::named(&app.config.categories, name)?;
+    }
+
     // Add each download
     let mut results = Vec::new();
     for input in inputs {
-        let options = build_options(&args, &input)?;
+        let category = category::resolve(&app.config.categories, args.category.as_deref(), &input)?;
+        let options = build_options(&args, &input, category)?;
 
         let id = match &input {
             ParsedInput::Http(url) => app.engine().add_http(url, options).await?,
@@ -66,6 +122,43 @@ pub async fn execute(args: AddArgs, app: &App, output: OutputFormat) -> Result<(
             }
         };
 
+        // The engine has no per-download seeding limits or holds; the
+        // supervisor reads them from here
+        let is_torrent = matches!(input, ParsedInput::Magnet(_) | ParsedInput::TorrentFile(_));
+        let mut extras = if is_torrent {
+            seed_limits.clone()
+        } else {
+            DownloadExtras::default()
+        };
+        if let (ParsedInput::Magnet(_), Some(target)) = (&input, &args.save_torrent) {
+            extras.save_torrent = true;
+            extras.torrent_path = target.clone();
+        }
+        if let Some(category) = category {
+            if is_torrent && extras.seed_ratio.is_none() {
+                extras.seed_ratio = category.seed_ratio;
+            }
+            extras.category = Some(category.name.clone());
+        }
+        extras.tags = tags.clone();
+        extras.start_at = start_at;
+        extras.after = after;
+        if after.is_some() {
+            extras.after_failure = args.after_failure;
+        }
+        let hold = Hold::of(&extras, Utc::now());
+        if !extras.is_empty() {
+            Store::update(&app.config.state_path(), |store| {
+                *store.entry(id) = extras;
+            })?;
+        }
+        if let Some(hold) = hold {
+            pause_new(app.engine(), id).await?;
+            if output.is_table() {
+                println!("{}: {}", id.to_gid(), hold.describe());
+            }
+        }
+
         results.push(AddResult {
             id: id.to_gid(),
             input: input.display(),
@@ -74,23 +167,37 @@ pub async fn execute(args: AddArgs, app: &App, output: OutputFormat) -> Result<(
     }
 
     // If --wait, monitor until completion
-    if args.wait {
-        wait_for_completion(app, &results).await?;
-    }
+    let report = if args.wait {
+        let mode = resolve_mode(args.progress);
+        let checksum = args.checksum.is_some();
+        Some(wait_for_completion(app, &results, checksum, mode, args.progress_interval).await?)
+    } else {
+        None
+    };
 
     // Output results
-    match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string(&results)?);
+    output.print(&results, &results, || print_add_results(&results))?;
+
+    if let Some(report) = report {
+        if output.is_table() {
+            println!();
+            print_report(report.entries());
         }
-        OutputFormat::JsonPretty => {
-            println!("{}", serde_json::to_string_pretty(&results)?);
+        if let Some(ref path) = args.report {
+            report.write(path)?;
         }
-        OutputFormat::Table => {
-            print_add_results(&results);
+        if let Some(failure) = report.failure() {
+            let failed = report
+                .entries()
+                .iter()
+                .filter(|e| e.outcome == Outcome::Failed)
+                .count();
+            bail!(CategoryError::new(
+                failure,
+                format!("{} of {} downloads failed", failed, report.entries().len())
+            ));
         }
     }
-
     Ok(())
 }
 
@@ -132,11 +239,16 @@ fn read_urls_from_file(path: &PathBuf) -> Result<Vec<String>> {
     Ok(urls)
 }
 
-fn build_options(args: &AddArgs, input: &ParsedInput) -> Result<DownloadOptions> {
-    let mut options = DownloadOptions {
-        priority: args.priority.to_engine_priority(),
-        ..Default::default()
-    };
+fn build_options(
+    args: &AddArgs,
+    input: &ParsedInput,
+    category: Option<&CategoryConfig>,
+) -> Result<DownloadOptions> {
+    let mut options = category.map(CategoryConfig::options).unwrap_or_default();
+
+    if let Some(priority) = args.priority {
+        options.priority = priority.to_engine_priority();
+    }
 
     if let Some(ref dir) = args.dir {
         options.save_dir = Some(dir.clone());
@@ -201,21 +313,36 @@ fn build_options(args: &AddArgs, input: &ParsedInput) -> Result<DownloadOptions>
     Ok(options)
 }
 
-async fn wait_for_completion(app: &App, results: &[AddResult]) -> Result<()> {
-    let ids: HashSet<DownloadId> = results
+pub async fn wait_for_completion(
+    app: &App,
+    results: &[AddResult],
+    checksum: bool,
+    mode: ProgressMode,
+    interval: ProgressInterval,
+) -> Result<Report> {
+    // A GID is only half the ID, so look the downloads up rather than
+    // rebuilding IDs from it
+    let added: Vec<(DownloadId, &AddResult)> = results
         .iter()
-        .filter_map(|r| DownloadId::from_gid(&r.id))
+        .filter_map(|r| Some((resolve_download_id(&r.id, app.engine()).ok()?, r)))
         .collect();
+    let ids: HashSet<DownloadId> = added.iter().map(|(id, _)| *id).collect();
 
+    let mut report = Report::default();
+    for (id, result) in &added {
+        let checksum = checksum && result.kind == "http";
+        report.track(*id, &result.input, checksum, app.engine());
+    }
     if ids.is_empty() {
-        return Ok(());
+        return Ok(report);
     }
 
     let mut remaining = ids.clone();
     let mut events = app.subscribe();
 
     // Setup progress bars
-    let multi = MultiProgress::new();
+    let multi = multi_progress(mode);
+    let mut plain = (mode == ProgressMode::Plain).then(|| PlainProgress::new(interval));
     let style = ProgressStyle::with_template(
         "{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}) {msg}",
     )?
@@ -232,16 +359,72 @@ async fn wait_for_completion(app: &App, results: &[AddResult]) -> Result<()> {
         .collect();
 
     // Set initial messages
-    for result in results {
-        if let Some(id) = DownloadId::from_gid(&result.id) {
-            if let Some(pb) = bars.get(&id) {
-                pb.set_message(truncate_str(&result.input, 30));
+    for (id, result) in &added {
+        if let Some(pb) = bars.get(id) {
+            pb.set_message(truncate_str(&result.input, 30));
+        }
+        if let Some(ref mut plain) = plain {
+            plain.started(*id, &result.input);
+        }
+    }
+    let path = |id: DownloadId| app.engine().status(id).map(|s| output_path(&s));
+
+    // Downloads that finished before we subscribed send no more events
+    for id in &ids {
+        match app.engine().status(*id).map(|s| s.state) {
+            Some(DownloadState::Completed) => {
+                if let Some(pb) = bars.get(id) {
+                    pb.finish_with_message("Done");
+                }
+                remaining.remove(id);
+                report.finish(*id, Outcome::Completed, None, app.engine());
+                if let Some(ref mut plain) = plain {
+                    plain.completed(*id, path(*id));
+                }
+            }
+            Some(DownloadState::Error { message, .. }) => {
+                if let Some(pb) = bars.get(id) {
+                    pb.abandon_with_message(format!("Failed: {}", truncate_str(&message, 40)));
+                }
+                remaining.remove(id);
+                report.finish(*id, Outcome::Failed, Some(&message), app.engine());
+                if let Some(ref mut plain) = plain {
+                    plain.failed(Some(*id), "", &message);
+                }
             }
+            _ => {}
         }
     }
 
+    let mut supervisor = Supervisor::new(&app.config);
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+
     while !remaining.is_empty() {
-        match events.recv().await {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = ticker.tick() => {
+                for action in supervisor.tick(app.engine()).await {
+                    let SupervisorAction::SeedingStopped { id, limit, .. } = action else {
+                        continue;
+                    };
+                    if remaining.remove(&id) {
+                        if let Some(pb) = bars.get(&id) {
+                            pb.finish_with_message(format!("Done ({limit})"));
+                        }
+                        report.finish(id, Outcome::Completed, None, app.engine());
+                        if let Some(ref mut plain) = plain {
+                            plain.completed(id, path(id));
+                        }
+                    }
+                }
+                continue;
+            }
+        };
+        if let Ok(ref event) = event {
+            supervisor.handle_event(app.engine(), event);
+        }
+
+        match event {
             Ok(DownloadEvent::Progress { id, progress }) if ids.contains(&id) => {
                 if let Some(pb) = bars.get(&id) {
                     if let Some(total) = progress.total_size {
@@ -249,18 +432,30 @@ async fn wait_for_completion(app: &App, results: &[AddResult]) -> Result<()> {
                     }
                     pb.set_position(progress.completed_size);
                 }
+                report.progress(id, &progress);
+                if let Some(ref mut plain) = plain {
+                    plain.progress(id, &progress);
+                }
             }
             Ok(DownloadEvent::Completed { id }) if ids.contains(&id) => {
                 if let Some(pb) = bars.get(&id) {
                     pb.finish_with_message("Done");
                 }
                 remaining.remove(&id);
+                report.finish(id, Outcome::Completed, None, app.engine());
+                if let Some(ref mut plain) = plain {
+                    plain.completed(id, path(id));
+                }
             }
             Ok(DownloadEvent::Failed { id, error, .. }) if ids.contains(&id) => {
                 if let Some(pb) = bars.get(&id) {
                     pb.abandon_with_message(format!("Failed: {}", truncate_str(&error, 40)));
                 }
                 remaining.remove(&id);
+                report.finish(id, Outcome::Failed, Some(&error), app.engine());
+                if let Some(ref mut plain) = plain {
+                    plain.failed(Some(id), "", &error);
+                }
             }
             Ok(DownloadEvent::Paused { id }) if ids.contains(&id) => {
                 if let Some(pb) = bars.get(&id) {
@@ -272,5 +467,6 @@ async fn wait_for_completion(app: &App, results: &[AddResult]) -> Result<()> {
         }
     }
 
-    Ok(())
+    report.interrupt_remaining(app.engine());
+    Ok(report)
 }
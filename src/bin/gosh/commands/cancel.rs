@@ -3,11 +3,25 @@ use std::io::{self, Write};
 
 use crate::app::App;
 use crate::cli::CancelArgs;
-use crate::util::resolve_download_ids;
+use crate::output::table::print_dry_run;
+use crate::store::Store;
+use crate::util::resolve_selected_ids;
 
 pub async fn execute(args: CancelArgs, app: &App) -> Result<()> {
     // For "all", cancel all downloads
-    let ids = resolve_download_ids(&args.ids, app.engine(), |_| true)?;
+    let store = Store::load(&app.config.state_path())?;
+    let ids = resolve_selected_ids(
+        &args.ids,
+        None,
+        args.select.filter.as_ref(),
+        app.engine(),
+        &store,
+        |_| true,
+    )?;
+    if args.select.dry_run {
+        print_dry_run("cancel", &ids, app.engine(), &store);
+        return Ok(());
+    }
 
     if ids.is_empty() {
         println!("No downloads to cancel");
@@ -45,6 +45,15 @@ fn get_config_value(config: &CliConfig, key: &str) -> Result<()> {
         ["general", "database_path"] => config.general.database_path.display().to_string(),
         ["general", "log_file"] => display_optional_path(config.general.log_file.as_ref()),
         ["general", "log_level"] => config.general.log_level.clone(),
+        ["general", "log_format"] => config.general.log_format.as_str().to_string(),
+        ["general", "log_rotate"] => config.general.log_rotate.clone(),
+        ["general", "log_keep"] => config.general.log_keep.to_string(),
+        ["general", "retention", "completed"] => {
+            display_optional_string(config.general.retention.completed.as_ref())
+        }
+        ["general", "retention", "failed"] => {
+            display_optional_string(config.general.retention.failed.as_ref())
+        }
         ["engine", "max_concurrent_downloads"] => {
             config.engine.max_concurrent_downloads.to_string()
         }
@@ -72,10 +81,55 @@ fn get_config_value(config: &CliConfig, key: &str) -> Result<()> {
         ["engine", "read_timeout"] => config.engine.read_timeout.to_string(),
         ["engine", "max_retries"] => config.engine.max_retries.to_string(),
         ["engine", "accept_invalid_certs"] => config.engine.accept_invalid_certs.to_string(),
+        ["engine", "auto_retry"] => on_off(config.engine.auto_retry.is_some()),
+        ["engine", "auto_retry", field] => match config.engine.auto_retry {
+            None => "off".to_string(),
+            Some(ref auto_retry) => match *field {
+                "interval" => auto_retry.interval.clone(),
+                "max_interval" => auto_retry.max_interval.clone(),
+                "max_attempts" => auto_retry.max_attempts.to_string(),
+                _ => anyhow::bail!("Unknown configuration key: {}", key),
+            },
+        },
+        ["torrent", "seeding_limit_action"] => {
+            config.torrent.seeding_limit_action.as_str().to_string()
+        }
+        ["torrent", "seed_time"] => display_optional_string(config.torrent.seed_time.as_ref()),
+        ["torrent", "seed_idle"] => display_optional_string(config.torrent.seed_idle.as_ref()),
+        ["torrent", "max_seeding"] => config
+            .torrent
+            .max_seeding
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "unlimited".to_string()),
+        ["torrent", "save_metadata"] => config.torrent.save_metadata.to_string(),
+        ["torrent", "metadata_dir"] => display_optional_path(config.torrent.metadata_dir.as_ref()),
+        ["quota", "daily_download"] => {
+            display_optional_string(config.quota.daily_download.as_ref())
+        }
+        ["quota", "daily_upload"] => display_optional_string(config.quota.daily_upload.as_ref()),
+        ["quota", "weekly_download"] => {
+            display_optional_string(config.quota.weekly_download.as_ref())
+        }
+        ["quota", "weekly_upload"] => display_optional_string(config.quota.weekly_upload.as_ref()),
+        ["quota", "monthly_download"] => {
+            display_optional_string(config.quota.monthly_download.as_ref())
+        }
+        ["quota", "monthly_upload"] => {
+            display_optional_string(config.quota.monthly_upload.as_ref())
+        }
+        ["quota", "reset_day"] => config.quota.reset_day.to_string(),
+        ["quota", "week_start"] => config.quota.week_start.clone(),
         ["tui", "refresh_rate_ms"] => config.tui.refresh_rate_ms.to_string(),
         ["tui", "theme"] => config.tui.theme.clone(),
         ["tui", "show_speed_graph"] => config.tui.show_speed_graph.to_string(),
         ["tui", "show_peers"] => config.tui.show_peers.to_string(),
+        ["list", "default_columns"] => {
+            if config.list.default_columns.is_empty() {
+                "unset".to_string()
+            } else {
+                config.list.default_columns.join(",")
+            }
+        }
         _ => anyhow::bail!("Unknown configuration key: {}", key),
     };
 
@@ -102,6 +156,21 @@ fn set_config_value(key: &str, value: &str, config_path: Option<&Path>) -> Resul
         ["general", "log_level"] => {
             config.general.log_level = value.to_string();
         }
+        ["general", "log_format"] => {
+            config.general.log_format = value.parse()?;
+        }
+        ["general", "log_rotate"] => {
+            config.general.log_rotate = value.to_string();
+        }
+        ["general", "log_keep"] => {
+            config.general.log_keep = value.parse()?;
+        }
+        ["general", "retention", "completed"] => {
+            config.general.retention.completed = parse_optional_string(value);
+        }
+        ["general", "retention", "failed"] => {
+            config.general.retention.failed = parse_optional_string(value);
+        }
         ["engine", "max_concurrent_downloads"] => {
             config.engine.max_concurrent_downloads = value.parse()?;
         }
@@ -155,6 +224,72 @@ fn set_config_value(key: &str, value: &str, config_path: Option<&Path>) -> Resul
         ["engine", "accept_invalid_certs"] => {
             config.engine.accept_invalid_certs = value.parse()?;
         }
+        ["engine", "auto_retry"] => {
+            config.engine.auto_retry = match value {
+                "on" => Some(config.engine.auto_retry.unwrap_or_default()),
+                "off" => None,
+                _ => anyhow::bail!("engine.auto_retry must be 'on' or 'off'"),
+            };
+        }
+        // Setting any of these turns auto-retry on
+        ["engine", "auto_retry", field] => {
+            let auto_retry = config
+                .engine
+                .auto_retry
+                .get_or_insert_with(Default::default);
+            match *field {
+                "interval" => auto_retry.interval = value.to_string(),
+                "max_interval" => auto_retry.max_interval = value.to_string(),
+                "max_attempts" => auto_retry.max_attempts = value.parse()?,
+                _ => anyhow::bail!("Unknown configuration key: {}", key),
+            }
+        }
+        ["torrent", "seeding_limit_action"] => {
+            config.torrent.seeding_limit_action = value.parse()?;
+        }
+        ["torrent", "seed_time"] => {
+            config.torrent.seed_time = parse_optional_string(value);
+        }
+        ["torrent", "seed_idle"] => {
+            config.torrent.seed_idle = parse_optional_string(value);
+        }
+        ["torrent", "max_seeding"] => {
+            config.torrent.max_seeding = if value == "unlimited" {
+                None
+            } else {
+                Some(value.parse()?)
+            };
+        }
+        ["torrent", "save_metadata"] => {
+            config.torrent.save_metadata = value.parse()?;
+        }
+        ["torrent", "metadata_dir"] => {
+            config.torrent.metadata_dir = parse_optional_path(value);
+        }
+        ["quota", "daily_download"] => {
+            config.quota.daily_download = parse_optional_string(value);
+        }
+        ["quota", "daily_upload"] => {
+            config.quota.daily_upload = parse_optional_string(value);
+        }
+        ["quota", "weekly_download"] => {
+            config.quota.weekly_download = parse_optional_string(value);
+        }
+        ["quota", "weekly_upload"] => {
+            config.quota.weekly_upload = parse_optional_string(value);
+        }
+        ["quota", "monthly_download"] => {
+            config.quota.monthly_download = parse_optional_string(value);
+        }
+        ["quota", "monthly_upload"] => {
+            config.quota.monthly_upload = parse_optional_string(value);
+        }
+        ["quota", "reset_day"] => {
+            config.quota.reset_day = value.parse()?;
+        }
+        ["quota", "week_start"] => {
+            config.quota.week_start = value.to_string();
+        }
         ["tui", "refresh_rate_ms"] => {
             config.tui.refresh_rate_ms = value.parse()?;
         }
@@ -167,6 +302,12 @@ fn set_config_value(key: &str, value: &str, config_path: Option<&Path>) -> Resul
         ["tui", "show_peers"] => {
             config.tui.show_peers = value.parse()?;
         }
+        ["list", "default_columns"] => {
+            config.list.default_columns = match value {
+                "unset" => Vec::new(),
+                _ => value.split(',').map(|c| c.trim().to_string()).collect(),
+            };
+        }
         _ => anyhow::bail!("Unknown configuration key: {}", key),
     }
 
@@ -180,6 +321,10 @@ fn set_config_value(key: &str, value: &str, config_path: Option<&Path>) -> Resul
     Ok(())
 }
 
+fn on_off(on: bool) -> String {
+    if on { "on" } else { "off" }.to_string()
+}
+
 fn display_optional_path(path: Option<&std::path::PathBuf>) -> String {
     path.map(|p| p.display().to_string())
         .unwrap_or_else(|| "unset".to_string())
@@ -285,6 +430,128 @@ mod tests {
         assert!(config.engine.proxy_url.is_none());
     }
 
+    #[test]
+    fn torrent_seeding_keys_are_validated() {
+        let tempdir = TempDir::new().unwrap();
+        let config_path = tempdir.path().join("config.toml");
+
+        set_config_value(
+            "torrent.seeding_limit_action",
+            "remove_with_files",
+            Some(config_path.as_path()),
+        )
+        .unwrap();
+        set_config_value("torrent.seed_time", "24h", Some(config_path.as_path())).unwrap();
+        set_config_value("torrent.max_seeding", "3", Some(config_path.as_path())).unwrap();
+        assert!(
+            set_config_value("torrent.seed_idle", "later", Some(config_path.as_path())).is_err()
+        );
+        assert!(set_config_value(
+            "torrent.seeding_limit_action",
+            "stop",
+            Some(config_path.as_path())
+        )
+        .is_err());
+
+        let config = CliConfig::load(Some(config_path.as_path())).unwrap();
+        assert_eq!(
+            config.torrent.seeding_limit_action,
+            crate::config::SeedingLimitAction::RemoveWithFiles
+        );
+        assert_eq!(config.torrent.seed_time.as_deref(), Some("24h"));
+        assert!(config.torrent.seed_idle.is_none());
+        assert_eq!(config.torrent.max_seeding, Some(3));
+    }
+
+    #[test]
+    fn retention_keys_are_validated() {
+        let tempdir = TempDir::new().unwrap();
+        let config_path = tempdir.path().join("config.toml");
+
+        set_config_value(
+            "general.retention.completed",
+            "30d",
+            Some(config_path.as_path()),
+        )
+        .unwrap();
+        assert!(set_config_value(
+            "general.retention.failed",
+            "soon",
+            Some(config_path.as_path())
+        )
+        .is_err());
+
+        let config = CliConfig::load(Some(config_path.as_path())).unwrap();
+        assert_eq!(config.general.retention.completed.as_deref(), Some("30d"));
+        assert!(config.general.retention.failed.is_none());
+    }
+
+    #[test]
+    fn list_default_columns_round_trip() {
+        let tempdir = TempDir::new().unwrap();
+        let config_path = tempdir.path().join("config.toml");
+
+        set_config_value(
+            "list.default_columns",
+            "id, name,size",
+            Some(config_path.as_path()),
+        )
+        .unwrap();
+        assert!(set_config_value(
+            "list.default_columns",
+            "id,colour",
+            Some(config_path.as_path())
+        )
+        .is_err());
+
+        let config = CliConfig::load(Some(config_path.as_path())).unwrap();
+        assert_eq!(config.list.default_columns, ["id", "name", "size"]);
+    }
+
+    #[test]
+    fn quota_keys_are_validated() {
+        let tempdir = TempDir::new().unwrap();
+        let config_path = tempdir.path().join("config.toml");
+
+        set_config_value(
+            "quota.monthly_download",
+            "500G",
+            Some(config_path.as_path()),
+        )
+        .unwrap();
+        set_config_value("quota.reset_day", "15", Some(config_path.as_path())).unwrap();
+        assert!(
+            set_config_value("quota.daily_upload", "lots", Some(config_path.as_path())).is_err()
+        );
+        assert!(set_config_value("quota.reset_day", "31", Some(config_path.as_path())).is_err());
+        assert!(
+            set_config_value("quota.week_start", "someday", Some(config_path.as_path())).is_err()
+        );
+
+        let config = CliConfig::load(Some(config_path.as_path())).unwrap();
+        assert_eq!(config.quota.monthly_download.as_deref(), Some("500G"));
+        assert_eq!(config.quota.reset_day, 15);
+        assert!(config.quota.daily_upload.is_none());
+    }
+
+    #[test]
+    fn auto_retry_keys_turn_it_on() {
+        let tempdir = TempDir::new().unwrap();
+        let config_path = tempdir.path().join("config.toml");
+        let path = Some(config_path.as_path());
+
+        set_config_value("engine.auto_retry.max_attempts", "5", path).unwrap();
+        let config = CliConfig::load(path).unwrap();
+        let auto_retry = config.engine.auto_retry.unwrap();
+        assert_eq!(auto_retry.max_attempts, 5);
+        assert_eq!(auto_retry.interval, "10m");
+
+        assert!(set_config_value("engine.auto_retry.interval", "soon", path).is_err());
+        assert!(set_config_value("engine.auto_retry.max_attempts", "0", path).is_err());
+        set_config_value("engine.auto_retry", "off", path).unwrap();
+        assert!(CliConfig::load(path).unwrap().engine.auto_retry.is_none());
+    }
+
     #[test]
     fn unknown_keys_still_error_cleanly() {
         let err = set_config_value("engine.unknown_key", "1", None).unwrap_err();
@@ -0,0 +1,59 @@
+use anyhow::{bail, Result};
+use chrono::Utc;
+use gosh_dl::DownloadKind;
+
+use crate::app::App;
+use crate::cli::{ExportArgs, OutputFormat};
+use crate::export::{self, ExportFile, EXPORT_VERSION};
+use crate::output::record::Output;
+use crate::queue::sort_by_queue;
+use crate::store::Store;
+use crate::torrent_file::MetadataReader;
+
+pub async fn execute(args: ExportArgs, app: &App, output: &Output) -> Result<()> {
+    let mut downloads = app.engine().list();
+    if !args.state.is_empty() {
+        downloads.retain(|d| args.state.iter().any(|s| s.matches(&d.state)));
+    }
+    let store = Store::load(&app.config.state_path())?;
+    if let Some(ref tag) = args.tag {
+        downloads.retain(|d| store.has_tag(d.id, tag));
+    }
+    sort_by_queue(&mut downloads, &store);
+
+    // Torrents added from a file are exported with the file the engine stored
+    let needs_files = downloads
+        .iter()
+        .any(|d| d.kind != DownloadKind::Http && d.metadata.magnet_uri.is_none());
+    let reader = if needs_files {
+        match MetadataReader::open(&app.config.general.database_path).await {
+            Ok(reader) => Some(reader),
+            Err(e) => {
+                tracing::warn!("Exporting torrents as magnet links: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut exported = Vec::with_capacity(downloads.len());
+    for dl in &downloads {
+        exported.push(export::export(dl, store.get(dl.id), reader.as_ref()).await?);
+    }
+    let file = ExportFile {
+        version: EXPORT_VERSION,
+        exported_at: Utc::now(),
+        downloads: exported,
+    };
+
+    match output.format {
+        OutputFormat::Json | OutputFormat::Ndjson => println!("{}", serde_json::to_string(&file)?),
+        OutputFormat::JsonPretty | OutputFormat::Table => {
+            println!("{}", serde_json::to_string_pretty(&file)?)
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => bail!(output.unsupported()),
+    }
+
+    Ok(())
+}
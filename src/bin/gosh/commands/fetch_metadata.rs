@@ -0,0 +1,79 @@
+use anyhow::{bail, Context, Result};
+use gosh_dl::torrent::Metainfo;
+use gosh_dl::{DownloadId, DownloadOptions, DownloadState};
+use indicatif::ProgressBar;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::app::App;
+use crate::cli::FetchMetadataArgs;
+use crate::commands::info::output_torrent_info;
+use crate::input::url_parser::{parse_input, ParsedInput};
+use crate::output::record::Output;
+use crate::torrent_file::{
+    build_torrent_file, torrent_file_path, write_torrent_file, MetadataReader,
+};
+use crate::util::parse_duration;
+
+pub async fn execute(args: FetchMetadataArgs, app: &App, output: &Output) -> Result<()> {
+    let ParsedInput::Magnet(magnet) = parse_input(&args.magnet)? else {
+        bail!("fetch-metadata expects a magnet link");
+    };
+    let timeout = Duration::from_secs(parse_duration(&args.timeout).context("Invalid --timeout")?);
+
+    // The engine starts downloading as soon as metadata arrives, so point the
+    // temporary download at a scratch directory and throw it away afterwards
+    let scratch = std::env::temp_dir().join(format!("gosh-metadata-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&scratch)?;
+    let options = DownloadOptions {
+        save_dir: Some(scratch.clone()),
+        ..Default::default()
+    };
+    let id = app.engine().add_magnet(&magnet, options).await?;
+
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_message("Fetching metadata from peers...");
+    spinner.enable_steady_tick(Duration::from_millis(100));
+
+    let result = tokio::time::timeout(timeout, wait_for_metadata(app, id)).await;
+    spinner.finish_and_clear();
+
+    let _ = app.engine().cancel(id, true).await;
+    let _ = std::fs::remove_dir_all(&scratch);
+
+    let raw = match result {
+        Ok(raw) => raw?,
+        Err(_) => bail!("Timed out after {} waiting for metadata", args.timeout),
+    };
+    let data = build_torrent_file(&raw, Some(&magnet))?;
+    let metainfo = Metainfo::parse(&data).context("Fetched metadata is not a valid torrent")?;
+
+    if let Some(target) = args.save_torrent {
+        let path = torrent_file_path(target.as_deref(), Path::new("."), &metainfo.info.name);
+        write_torrent_file(&path, &data)?;
+        eprintln!("Saved {}", path.display());
+    }
+
+    output_torrent_info(&metainfo, output)
+}
+
+async fn wait_for_metadata(app: &App, id: DownloadId) -> Result<Vec<u8>> {
+    let reader = MetadataReader::open(&app.config.general.database_path).await?;
+    let mut events = app.subscribe();
+
+    loop {
+        if let Some(raw) = reader.load(id).await? {
+            return Ok(raw);
+        }
+        if let Some(DownloadState::Error { message, .. }) = app.engine().status(id).map(|s| s.state)
+        {
+            bail!("Failed to fetch metadata: {}", message);
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => bail!("Interrupted"),
+            _ = events.recv() => {}
+            _ = tokio::time::sleep(Duration::from_millis(500)) => {}
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use gosh_dl::{DownloadId, DownloadState};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::app::App;
+use crate::cli::ImportArgs;
+use crate::export::{self, ExportedDownload};
+use crate::hold::{pause_new, Hold};
+use crate::input::aria2_session::{parse_session, Aria2Download};
+use crate::input::url_parser::ParsedInput;
+use crate::output::table::print_aria2_mapping;
+use crate::store::Store;
+
+/// An aria2 download and the gosh download it became
+pub struct Aria2Mapping {
+    pub aria2_gid: String,
+    pub id: String,
+    pub input: String,
+}
+
+pub async fn execute(args: ImportArgs, app: &App) -> Result<()> {
+    if let Some(ref session) = args.aria2_session {
+        return import_aria2_session(session, args.dry_run, app).await;
+    }
+    let path = args.file.context("No export file given")?;
+    let contents = if path.as_os_str() == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read export file: {}", path.display()))?
+    };
+    let file = export::parse(&contents)?;
+
+    // Downloads already here, by URL or info hash
+    let mut known = export::known(app.engine());
+
+    // Old ID to new (or existing) ID, for remapping `after` holds
+    let mut ids: HashMap<DownloadId, DownloadId> = HashMap::new();
+    let mut pending = Vec::new();
+    let mut skipped = 0;
+    for dl in file.downloads {
+        let key = export::identity(&dl.source, dl.info_hash.as_deref());
+        if let Some(existing) = key.as_ref().and_then(|key| known.get(key)) {
+            println!("Skipped (already added): {}", dl.name);
+            ids.insert(dl.id, *existing);
+            skipped += 1;
+            continue;
+        }
+        if let Some(key) = key {
+            // A placeholder until it is added; the file may list it twice
+            known.insert(key, dl.id);
+        }
+        pending.push(dl);
+    }
+    let in_file: HashSet<DownloadId> = pending.iter().map(|dl| dl.id).collect();
+
+    if args.dry_run {
+        for dl in &pending {
+            println!("Would import: {} ({})", dl.name, dl.source.kind());
+        }
+        println!(
+            "Would import {} download(s), skipping {} already added",
+            pending.len(),
+            skipped
+        );
+        return Ok(());
+    }
+
+    let state_path = app.config.state_path();
+    let mut imported = Vec::new();
+    let mut error_count = 0;
+    for mut dl in pending {
+        // A hold on a download that is neither here nor in the file is dropped
+        if let Some(after) = dl.extras.after {
+            let found = in_file.contains(&after)
+                || ids.contains_key(&after)
+                || app.engine().status(after).is_some();
+            if !found {
+                dl.extras.clear_hold();
+            }
+        }
+        match import(app, &state_path, &dl).await {
+            Ok(id) => {
+                println!("Imported: {} as {}", dl.name, id.to_gid());
+                ids.insert(dl.id, id);
+                imported.push(id);
+            }
+            Err(e) => {
+                eprintln!("Failed to import {}: {:#}", dl.name, e);
+                error_count += 1;
+            }
+        }
+    }
+
+    // Holds on downloads from the same file follow them to their new IDs
+    if !imported.is_empty() {
+        Store::update(&state_path, |store| {
+            for id in &imported {
+                let after = store.get(*id).and_then(|extras| extras.after);
+                if let Some(new) = after.and_then(|after| ids.get(&after)) {
+                    store.entry(*id).after = Some(*new);
+                }
+            }
+        })?;
+    }
+
+    if imported.is_empty() && skipped == 0 && error_count == 0 {
+        println!("No downloads to import");
+    } else {
+        println!(
+            "Imported {} download(s), skipped {} already added",
+            imported.len(),
+            skipped
+        );
+    }
+
+    if error_count > 0 {
+        anyhow::bail!("Failed to import {} download(s)", error_count);
+    }
+
+    Ok(())
+}
+
+/// Recreate the downloads of an aria2c session, printing which GID became which
+async fn import_aria2_session(path: &Path, dry_run: bool, app: &App) -> Result<()> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read aria2 session: {}", path.display()))?;
+    let entries = parse_session(&contents)
+        .with_context(|| format!("Invalid aria2 session: {}", path.display()))?;
+
+    let mut known = export::known(app.engine());
+    let state_path = app.config.state_path();
+    let mut mappings = Vec::new();
+    let mut ignored = BTreeSet::new();
+    let mut metalinks = Vec::new();
+    let (mut imported, mut skipped, mut error_count) = (0, 0, 0);
+
+    for entry in &entries {
+        if entry.is_metalink() {
+            metalinks.push(format!("line {} ({})", entry.line, entry.uris[0]));
+            continue;
+        }
+        let aria2_gid = entry.gid().unwrap_or("-").to_string();
+        let dl = match entry.to_download() {
+            Ok(dl) => dl,
+            Err(e) => {
+                eprintln!("Failed to import line {}: {:#}", entry.line, e);
+                error_count += 1;
+                continue;
+            }
+        };
+        ignored.extend(dl.ignored.iter().cloned());
+
+        let key = export::input_identity(&dl.input);
+        if let Some(existing) = key.as_ref().and_then(|key| known.get(key)) {
+            mappings.push(Aria2Mapping {
+                aria2_gid,
+                id: existing.to_gid(),
+                input: format!("{} (already added)", dl.input.display()),
+            });
+            skipped += 1;
+            continue;
+        }
+        if dry_run {
+            println!("Would import: {} ({})", dl.input.display(), dl.input.kind());
+            imported += 1;
+            continue;
+        }
+
+        match add_aria2(app, &state_path, &dl).await {
+            Ok(id) => {
+                if let Some(key) = key {
+                    known.insert(key, id);
+                }
+                mappings.push(Aria2Mapping {
+                    aria2_gid,
+                    id: id.to_gid(),
+                    input: dl.input.display(),
+                });
+                imported += 1;
+            }
+            Err(e) => {
+                eprintln!("Failed to import line {}: {:#}", entry.line, e);
+                error_count += 1;
+            }
+        }
+    }
+
+    print_aria2_mapping(&mappings);
+    if !ignored.is_empty() {
+        eprintln!(
+            "Ignored aria2 options: {}",
+            ignored.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    if !metalinks.is_empty() {
+        eprintln!(
+            "Skipped metalink downloads, which are not supported: {}",
+            metalinks.join(", ")
+        );
+    }
+    if dry_run {
+        println!(
+            "Would import {} download(s), skipping {} already added",
+            imported, skipped
+        );
+    } else {
+        println!(
+            "Imported {} download(s), skipped {} already added",
+            imported, skipped
+        );
+    }
+
+    if error_count > 0 {
+        anyhow::bail!("Failed to import {} download(s)", error_count);
+    }
+
+    Ok(())
+}
+
+async fn add_aria2(app: &App, state_path: &Path, dl: &Aria2Download) -> Result<DownloadId> {
+    let options = dl.options.clone();
+    let id = match &dl.input {
+        ParsedInput::Http(url) => app.engine().add_http(url, options).await?,
+        ParsedInput::Magnet(uri) => app.engine().add_magnet(uri, options).await?,
+        ParsedInput::TorrentFile(path) => {
+            let data = tokio::fs::read(path)
+                .await
+                .with_context(|| format!("Failed to read torrent file: {}", path.display()))?;
+            app.engine().add_torrent(&data, options).await?
+        }
+    };
+    if !dl.extras.is_empty() {
+        Store::update(state_path, |store| {
+            *store.entry(id) = dl.extras.clone();
+        })?;
+    }
+    if dl.paused {
+        pause_new(app.engine(), id).await?;
+    }
+    Ok(id)
+}
+
+/// Add one download with its extras, paused if it was paused or is held
+async fn import(app: &App, state_path: &Path, dl: &ExportedDownload) -> Result<DownloadId> {
+    let id = export::add(app.engine(), dl).await?;
+    if !dl.extras.is_empty() {
+        Store::update(state_path, |store| {
+            *store.entry(id) = dl.extras.clone();
+        })?;
+    }
+    if dl.state == DownloadState::Paused || Hold::of(&dl.extras, Utc::now()).is_some() {
+        pause_new(app.engine(), id).await?;
+    }
+    Ok(id)
+}
@@ -3,11 +3,12 @@ use gosh_dl::torrent::Metainfo;
 use serde::Serialize;
 use std::path::PathBuf;
 
-use crate::cli::{InfoArgs, OutputFormat};
+use crate::cli::InfoArgs;
 use crate::format::format_size;
+use crate::output::record::{Output, Record, Value};
 
 #[derive(Serialize)]
-struct TorrentInfo {
+pub struct TorrentInfo {
     name: String,
     info_hash: String,
     total_size: u64,
@@ -32,7 +33,40 @@ struct FileInfo {
     size_human: String,
 }
 
-pub async fn execute(args: InfoArgs, output: OutputFormat) -> Result<()> {
+impl Record for TorrentInfo {
+    const FIELDS: &'static [&'static str] = &[
+        "name",
+        "info_hash",
+        "size",
+        "piece_length",
+        "pieces",
+        "files",
+        "private",
+        "announce",
+        "created",
+        "created_by",
+        "comment",
+    ];
+
+    fn field(&self, name: &str) -> Value {
+        match name {
+            "name" => self.name.as_str().into(),
+            "info_hash" => self.info_hash.as_str().into(),
+            "size" => self.total_size.into(),
+            "piece_length" => self.piece_length.into(),
+            "pieces" => self.piece_count.into(),
+            "files" => self.files.len().into(),
+            "private" => self.private.into(),
+            "announce" => self.announce.clone().into(),
+            "created" => self.creation_date.clone().into(),
+            "created_by" => self.created_by.clone().into(),
+            "comment" => self.comment.clone().into(),
+            _ => Value::Empty,
+        }
+    }
+}
+
+pub async fn execute(args: InfoArgs, output: &Output) -> Result<()> {
     let data = tokio::fs::read(&args.file)
         .await
         .with_context(|| format!("Failed to read torrent file: {}", args.file.display()))?;
@@ -40,21 +74,13 @@ pub async fn execute(args: InfoArgs, output: OutputFormat) -> Result<()> {
     let metainfo = Metainfo::parse(&data)
         .with_context(|| format!("Failed to parse torrent file: {}", args.file.display()))?;
 
-    let info = build_torrent_info(&metainfo);
-
-    match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string(&info)?);
-        }
-        OutputFormat::JsonPretty => {
-            println!("{}", serde_json::to_string_pretty(&info)?);
-        }
-        OutputFormat::Table => {
-            print_torrent_info(&info);
-        }
-    }
+    output_torrent_info(&metainfo, output)
+}
 
-    Ok(())
+/// Print torrent metadata in the requested output format
+pub fn output_torrent_info(metainfo: &Metainfo, output: &Output) -> Result<()> {
+    let info = build_torrent_info(metainfo);
+    output.print(&[&info], &info, || print_torrent_info(&info))
 }
 
 fn build_torrent_info(metainfo: &Metainfo) -> TorrentInfo {
@@ -1,13 +1,61 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use chrono::{Local, Utc};
 use gosh_dl::DownloadStatus;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::app::App;
-use crate::cli::{ListArgs, OutputFormat, StateFilter};
+use crate::cli::{ListArgs, StateFilter};
 use crate::format::format_speed;
-use crate::output::table::print_download_table;
+use crate::output::columns::{print_columns, terminal_height, terminal_width, Column, RowContext};
+use crate::output::record::{download_records, Output};
+use crate::queue::sort_by_queue;
+use crate::store::Store;
+use crate::supervisor::Supervisor;
+use crate::util::parse_duration;
 
-pub async fn execute(args: ListArgs, app: &App, output: OutputFormat) -> Result<()> {
-    let downloads = match args.state {
+pub async fn execute(args: ListArgs, app: &App, output: &Output) -> Result<()> {
+    let columns = if args.columns.is_empty() {
+        app.config.list.columns()?
+    } else {
+        args.columns.clone()
+    };
+    if args.watch {
+        return watch(&args, &columns, app, output).await;
+    }
+
+    let store = Store::load(&app.config.state_path())?;
+    let uploaded = HashMap::new();
+    let row = RowContext {
+        store: &store,
+        uploaded: &uploaded,
+    };
+    let downloads = select(&args, app, &row);
+
+    if args.ids_only {
+        for dl in &downloads {
+            println!("{}", dl.id.to_gid());
+        }
+        return Ok(());
+    }
+
+    output.print(&download_records(&downloads, &store), &downloads, || {
+        print_columns(&downloads, &columns, &row, terminal_width())
+    })?;
+
+    // Print summary
+    if output.is_table() {
+        print_summary(app, downloads.len(), false);
+    }
+
+    Ok(())
+}
+
+/// The downloads to show, filtered, in order and cut to `--limit`
+fn select(args: &ListArgs, app: &App, row: &RowContext) -> Vec<DownloadStatus> {
+    let mut downloads = match args.state {
         Some(StateFilter::Active) => app.engine().active(),
         Some(StateFilter::Waiting) => app.engine().waiting(),
         Some(StateFilter::Paused) => filter_paused(&app.engine().list()),
@@ -15,44 +63,109 @@ pub async fn execute(args: ListArgs, app: &App, output: OutputFormat) -> Result<
         Some(StateFilter::Error) => filter_errors(&app.engine().stopped()),
         None => app.engine().list(),
     };
+    if let Some(ref tag) = args.tag {
+        downloads.retain(|d| row.store.has_tag(d.id, tag));
+    }
+    if let Some(ref filter) = args.filter {
+        let now = Utc::now();
+        downloads.retain(|d| filter.matches(d, row.store, now));
+    }
+    sort_by_queue(&mut downloads, row.store);
+    if let Some(sort) = args.sort {
+        sort.sort(&mut downloads, row);
+    }
+    if let Some(limit) = args.limit {
+        downloads.truncate(limit);
+    }
+    downloads
+}
 
-    if args.ids_only {
-        for dl in &downloads {
-            println!("{}", dl.id.to_gid());
-        }
-        return Ok(());
+fn print_summary(app: &App, shown_count: usize, always_speed: bool) {
+    let stats = app.engine().global_stats();
+    for line in format_summary(
+        shown_count,
+        stats.num_active,
+        stats.num_waiting,
+        stats.num_stopped,
+        stats.download_speed,
+        stats.upload_speed,
+        always_speed,
+    ) {
+        println!("{line}");
     }
+}
 
-    match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string(&downloads)?);
-        }
-        OutputFormat::JsonPretty => {
-            println!("{}", serde_json::to_string_pretty(&downloads)?);
-        }
-        OutputFormat::Table => {
-            print_download_table(&downloads);
-        }
+/// Refresh the list every `--interval` until Ctrl-C. On a terminal the table
+/// is redrawn in place; otherwise, or with a machine-readable output, each
+/// refresh is appended.
+async fn watch(args: &ListArgs, columns: &[Column], app: &App, output: &Output) -> Result<()> {
+    if args.ids_only {
+        bail!("--ids-only cannot be used with --watch");
+    }
+    let secs = parse_duration(&args.interval)?;
+    if secs == 0 {
+        bail!("--interval must be at least 1s");
     }
+    let redraw = output.is_table() && std::io::stdout().is_terminal();
 
-    // Print summary
-    if matches!(output, OutputFormat::Table) {
-        let stats = app.engine().global_stats();
-        for line in format_summary(
-            downloads.len(),
-            stats.num_active,
-            stats.num_waiting,
-            stats.num_stopped,
-            stats.download_speed,
-            stats.upload_speed,
-        ) {
-            println!("{line}");
+    // Downloads run in this process while it watches, so keep the retry,
+    // schedule, quota and seeding rules going as the TUI does
+    let mut supervisor = Supervisor::with_retries(&app.config);
+    let mut events = app.subscribe();
+    let mut ticker = tokio::time::interval(Duration::from_secs(secs));
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = ticker.tick() => {
+                supervisor.tick(app.engine()).await;
+                let row = RowContext {
+                    store: supervisor.store(),
+                    uploaded: supervisor.uploaded(),
+                };
+                let mut downloads = select(args, app, &row);
+                if !output.is_table() {
+                    output.print(&download_records(&downloads, row.store), &downloads, || {})?;
+                    continue;
+                }
+
+                if redraw {
+                    // Keep the frame within the screen: header, rule and summary
+                    // take up to 7 lines
+                    if let Some(height) = terminal_height() {
+                        downloads.truncate(height.saturating_sub(7).max(1));
+                    }
+                    print!("{}", CLEAR_SCREEN);
+                }
+                println!(
+                    "Every {}: {}",
+                    args.interval.trim(),
+                    Local::now().format("%Y-%m-%d %H:%M:%S")
+                );
+                println!();
+                print_columns(&downloads, columns, &row, terminal_width());
+                print_summary(app, downloads.len(), true);
+                if !redraw {
+                    println!();
+                }
+                std::io::stdout().flush()?;
+            }
+            event = events.recv() => {
+                match event {
+                    Ok(ref event) => supervisor.handle_event(app.engine(), event),
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => {}
+                }
+            }
         }
     }
 
-    Ok(())
+    app.shutdown().await
 }
 
+/// Move to the top-left corner and clear the screen
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[J";
+
 fn filter_paused(downloads: &[DownloadStatus]) -> Vec<DownloadStatus> {
     downloads
         .iter()
@@ -84,6 +197,7 @@ fn format_summary(
     stopped_count: usize,
     download_speed: u64,
     upload_speed: u64,
+    always_speed: bool,
 ) -> Vec<String> {
     let mut lines = vec![
         String::new(),
@@ -94,7 +208,7 @@ fn format_summary(
         ),
     ];
 
-    if download_speed > 0 || upload_speed > 0 {
+    if always_speed || download_speed > 0 || upload_speed > 0 {
         lines.push(format!(
             "Speed: {}/s down, {}/s up",
             format_speed(download_speed),
@@ -111,7 +225,7 @@ mod tests {
 
     #[test]
     fn summary_wording_separates_filtered_and_global_counts() {
-        let summary = format_summary(2, 5, 1, 9, 0, 0);
+        let summary = format_summary(2, 5, 1, 9, 0, 0, false);
 
         assert!(summary.iter().any(|line| line == "Showing 2 download(s)"));
         assert!(summary
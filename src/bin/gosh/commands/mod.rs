@@ -1,10 +1,19 @@
 pub mod add;
 pub mod cancel;
 pub mod config;
+pub mod export;
+pub mod fetch_metadata;
+pub mod import;
 pub mod info;
 pub mod list;
 pub mod pause;
 pub mod priority;
+pub mod purge;
+pub mod quota;
+pub mod reorder;
 pub mod resume;
+pub mod retry;
+pub mod schedule;
+pub mod seed;
 pub mod stats;
 pub mod status;
@@ -3,15 +3,43 @@ use gosh_dl::DownloadState;
 
 use crate::app::App;
 use crate::cli::PauseArgs;
-use crate::util::resolve_download_ids;
+use crate::output::table::print_dry_run;
+use crate::store::Store;
+use crate::util::resolve_selected_ids;
 
 pub async fn execute(args: PauseArgs, app: &App) -> Result<()> {
     // For "all", pause only active downloads (downloading/seeding)
-    let ids = resolve_download_ids(&args.ids, app.engine(), |d| {
-        matches!(
-            d.state,
-            DownloadState::Downloading | DownloadState::Seeding | DownloadState::Connecting
-        )
+    let store = Store::load(&app.config.state_path())?;
+    let ids = resolve_selected_ids(
+        &args.ids,
+        args.tag.as_deref(),
+        args.select.filter.as_ref(),
+        app.engine(),
+        &store,
+        |d| {
+            matches!(
+                d.state,
+                DownloadState::Downloading | DownloadState::Seeding | DownloadState::Connecting
+            )
+        },
+    )?;
+    if args.select.dry_run {
+        print_dry_run("pause", &ids, app.engine(), &store);
+        return Ok(());
+    }
+    if ids.is_empty() {
+        if let Some(tag) = &args.tag {
+            println!("No active downloads tagged '{}'", tag);
+        } else if args.select.filter.is_some() {
+            println!("No active downloads match");
+        }
+    }
+
+    // Paused by hand, so no longer waiting for its turn in the queue
+    Store::update(&app.config.state_path(), |store| {
+        for id in &ids {
+            store.clear_queue_hold(*id);
+        }
     })?;
 
     let mut success_count = 0;
@@ -2,15 +2,49 @@ use anyhow::Result;
 
 use crate::app::App;
 use crate::cli::PriorityArgs;
-use crate::util::resolve_download_id;
+use crate::output::table::print_dry_run;
+use crate::store::Store;
+use crate::util::resolve_selected_ids;
 
 pub async fn execute(args: PriorityArgs, app: &App) -> Result<()> {
-    let id = resolve_download_id(&args.id, app.engine())?;
+    let store = Store::load(&app.config.state_path())?;
+    let ids = resolve_selected_ids(
+        &args.ids,
+        None,
+        args.select.filter.as_ref(),
+        app.engine(),
+        &store,
+        |_| true,
+    )?;
+    if args.select.dry_run {
+        print_dry_run(
+            &format!("set priority {:?} for", args.priority),
+            &ids,
+            app.engine(),
+            &store,
+        );
+        return Ok(());
+    }
     let priority = args.priority.to_engine_priority();
 
-    app.engine().set_priority(id, priority)?;
+    let mut error_count = 0;
+    for id in &ids {
+        match app.engine().set_priority(*id, priority) {
+            Ok(_) => println!("Set priority of {} to {:?}", id.to_gid(), args.priority),
+            Err(e) => {
+                eprintln!("Failed to set priority of {}: {}", id.to_gid(), e);
+                error_count += 1;
+            }
+        }
+    }
 
-    println!("Set priority of {} to {:?}", id.to_gid(), args.priority);
+    if ids.is_empty() {
+        println!("No downloads match");
+    }
+
+    if error_count > 0 {
+        anyhow::bail!("Failed to set priority of {} download(s)", error_count);
+    }
 
     Ok(())
 }
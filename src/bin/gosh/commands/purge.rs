@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use std::io::{self, Write};
+
+use crate::app::App;
+use crate::cli::PurgeArgs;
+use crate::purge::{self, PurgeFilter};
+use crate::store::Store;
+use crate::util::parse_duration;
+
+pub async fn execute(args: PurgeArgs, app: &App) -> Result<()> {
+    let older_than = args
+        .older_than
+        .as_deref()
+        .map(parse_duration)
+        .transpose()
+        .context("Invalid --older-than")?;
+    // With neither flag, purge both kinds
+    let both = !args.completed && !args.failed;
+    let filter = PurgeFilter {
+        completed: args.completed || both,
+        failed: args.failed || both,
+        older_than,
+    };
+    let store = Store::load(&app.config.state_path())?;
+    let downloads = purge::select(app.engine().list(), &store, filter);
+
+    if downloads.is_empty() {
+        println!("No downloads to purge");
+        return Ok(());
+    }
+
+    // Confirm unless --yes is specified
+    if !args.yes {
+        let action = if args.delete_files {
+            "purge and DELETE FILES for"
+        } else {
+            "purge"
+        };
+
+        print!(
+            "Are you sure you want to {} {} download(s)? [y/N] ",
+            action,
+            downloads.len()
+        );
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    let state_path = app.config.state_path();
+    let mut success_count = 0;
+    let mut error_count = 0;
+
+    for dl in downloads {
+        let gid = dl.id.to_gid();
+        match purge::purge(app.engine(), &state_path, dl.id, args.delete_files).await {
+            Ok(()) => {
+                if args.delete_files {
+                    println!("Purged and deleted: {}", gid);
+                } else {
+                    println!("Purged: {}", gid);
+                }
+                success_count += 1;
+            }
+            Err(e) => {
+                eprintln!("Failed to purge {}: {:#}", gid, e);
+                error_count += 1;
+            }
+        }
+    }
+
+    if success_count > 0 {
+        println!("Successfully purged {} download(s)", success_count);
+    }
+
+    if error_count > 0 {
+        anyhow::bail!("Failed to purge {} download(s)", error_count);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use crate::cli::OutputFormat;
+use crate::config::CliConfig;
+use crate::format::format_size;
+use crate::output::record::Output;
+use crate::quota::{Cap, Direction, Period, Quota, PERIODS};
+use crate::store::Store;
+
+#[derive(Serialize)]
+struct QuotaOverview {
+    usage: Vec<UsageInfo>,
+    exceeded: Vec<String>,
+    exempt: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct UsageInfo {
+    period: Period,
+    direction: Direction,
+    used: u64,
+    cap: Option<u64>,
+    /// First day of the next period
+    resets: NaiveDate,
+}
+
+pub async fn execute(config: &CliConfig, output: &Output) -> Result<()> {
+    let quota = Quota::from_config(&config.quota)?;
+    let store = Store::load(&config.state_path())?;
+    let today = Local::now().date_naive();
+
+    let mut usage = Vec::new();
+    for period in PERIODS {
+        let used = quota.used(&store.usage, period, today);
+        for direction in [Direction::Download, Direction::Upload] {
+            usage.push(UsageInfo {
+                period,
+                direction,
+                used: used.get(direction),
+                cap: quota
+                    .caps
+                    .iter()
+                    .find(|c| c.period == period && c.direction == direction)
+                    .map(|c| c.limit),
+                resets: quota.next_reset(period, today),
+            });
+        }
+    }
+    let overview = QuotaOverview {
+        usage,
+        exceeded: quota
+            .exceeded(&store.usage, today)
+            .iter()
+            .map(Cap::to_string)
+            .collect(),
+        exempt: quota.exempt().to_vec(),
+    };
+
+    match output.format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string(&overview)?)
+        }
+        OutputFormat::JsonPretty => println!("{}", serde_json::to_string_pretty(&overview)?),
+        OutputFormat::Table => print_overview(&overview),
+        OutputFormat::Csv | OutputFormat::Tsv => bail!(output.unsupported()),
+    }
+
+    Ok(())
+}
+
+fn print_overview(overview: &QuotaOverview) {
+    println!(
+        "{:<8} {:<9} {:>10} {:>10} {:>5}  Resets",
+        "Period", "Direction", "Used", "Cap", "%"
+    );
+    println!("{}", "\u{2500}".repeat(58));
+    for row in &overview.usage {
+        let (cap, percent) = match row.cap {
+            Some(cap) => (
+                format_size(cap),
+                format!("{:.0}", row.used as f64 * 100.0 / cap.max(1) as f64),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:<8} {:<9} {:>10} {:>10} {:>5}  {}",
+            row.period.as_str(),
+            row.direction.as_str(),
+            format_size(row.used),
+            cap,
+            percent,
+            row.resets.format("%a %Y-%m-%d")
+        );
+    }
+    println!();
+
+    if !overview.exempt.is_empty() {
+        println!("Exempt: {}", overview.exempt.join(", "));
+    }
+    if overview.exceeded.is_empty() {
+        println!("Within all caps");
+    } else {
+        println!(
+            "Used up: {} (downloads it covers stay paused until it resets)",
+            overview.exceeded.join(", ")
+        );
+    }
+}
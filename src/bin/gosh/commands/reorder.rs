@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use crate::app::App;
+use crate::cli::MoveArgs;
+use crate::queue::{move_in_queue, QueueMove};
+use crate::store::Store;
+use crate::util::resolve_download_id;
+
+pub async fn execute(args: MoveArgs, app: &App) -> Result<()> {
+    let to: QueueMove = args.position.parse()?;
+    let id = resolve_download_id(&args.id, app.engine())?;
+
+    let downloads = app.engine().list();
+    let (position, len) = Store::update(&app.config.state_path(), |store| {
+        move_in_queue(store, &downloads, id, to)
+    })??;
+
+    println!("Moved {} to position {} of {}", id.to_gid(), position, len);
+
+    Ok(())
+}
@@ -3,12 +3,40 @@ use gosh_dl::DownloadState;
 
 use crate::app::App;
 use crate::cli::ResumeArgs;
-use crate::util::resolve_download_ids;
+use crate::output::table::print_dry_run;
+use crate::queue::sort_ids_by_queue;
+use crate::store::Store;
+use crate::util::resolve_selected_ids;
 
 pub async fn execute(args: ResumeArgs, app: &App) -> Result<()> {
     // For "all", resume only paused downloads
-    let ids = resolve_download_ids(&args.ids, app.engine(), |d| {
-        matches!(d.state, DownloadState::Paused)
+    let store = Store::load(&app.config.state_path())?;
+    let mut ids = resolve_selected_ids(
+        &args.ids,
+        args.tag.as_deref(),
+        args.select.filter.as_ref(),
+        app.engine(),
+        &store,
+        |d| matches!(d.state, DownloadState::Paused),
+    )?;
+    // Hand them to the engine front first so they queue in order
+    sort_ids_by_queue(&mut ids, &store);
+    if args.select.dry_run {
+        print_dry_run("resume", &ids, app.engine(), &store);
+        return Ok(());
+    }
+    if ids.is_empty() {
+        if let Some(tag) = &args.tag {
+            println!("No paused downloads tagged '{}'", tag);
+        } else if args.select.filter.is_some() {
+            println!("No paused downloads match");
+        }
+    }
+    // Resuming by hand overrides any --start-at/--after hold
+    Store::update(&app.config.state_path(), |store| {
+        for id in &ids {
+            store.clear_hold(*id);
+        }
     })?;
 
     let mut success_count = 0;
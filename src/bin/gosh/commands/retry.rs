@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use std::io::{self, Write};
+
+use crate::app::App;
+use crate::cli::RetryArgs;
+use crate::output::table::print_dry_run;
+use crate::queue::sort_ids_by_queue;
+use crate::retry::{self, is_failed, is_retryable};
+use crate::store::Store;
+use crate::util::resolve_selected_ids;
+
+pub async fn execute(args: RetryArgs, app: &App) -> Result<()> {
+    // For "all", retry only failed downloads
+    let state_path = app.config.state_path();
+    let store = Store::load(&state_path)?;
+    let mut ids = resolve_selected_ids(
+        &args.ids,
+        None,
+        args.select.filter.as_ref(),
+        app.engine(),
+        &store,
+        is_failed,
+    )?;
+    if args.new_url.is_some() && ids.len() != 1 {
+        bail!("--new-url can only be used with a single download");
+    }
+    sort_ids_by_queue(&mut ids, &store);
+    if args.select.dry_run {
+        print_dry_run("retry", &ids, app.engine(), &store);
+        return Ok(());
+    }
+
+    let mut error_count = 0;
+    let mut downloads = Vec::new();
+    for id in ids {
+        match app.engine().status(id) {
+            Some(dl) if is_failed(&dl) => downloads.push(dl),
+            Some(_) => {
+                eprintln!("Not failed: {}", id.to_gid());
+                error_count += 1;
+            }
+            None => {
+                eprintln!("Download not found: {}", id.to_gid());
+                error_count += 1;
+            }
+        }
+    }
+
+    if downloads.is_empty() && error_count == 0 {
+        println!("No failed downloads to retry");
+        return Ok(());
+    }
+
+    // A new URL is the fix for a permanent error, so only ask without one
+    let permanent = downloads.iter().filter(|d| !is_retryable(d)).count();
+    if permanent > 0 && args.new_url.is_none() && !args.yes {
+        print!(
+            "{} download(s) failed with an error that retrying may not fix. Retry anyway? [y/N] ",
+            permanent
+        );
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            downloads.retain(is_retryable);
+            println!("Skipped {} download(s)", permanent);
+        }
+    }
+
+    let mut success_count = 0;
+
+    for dl in downloads {
+        let gid = dl.id.to_gid();
+        match retry::retry(
+            app.engine(),
+            &state_path,
+            &app.config.general.database_path,
+            &dl,
+            args.new_url.as_deref(),
+            0,
+        )
+        .await
+        {
+            Ok(id) => {
+                println!("Retried: {} as {}", gid, id.to_gid());
+                success_count += 1;
+            }
+            Err(e) => {
+                eprintln!("Failed to retry {}: {:#}", gid, e);
+                error_count += 1;
+            }
+        }
+    }
+
+    if success_count > 0 {
+        println!("Successfully retried {} download(s)", success_count);
+    }
+
+    if error_count > 0 {
+        bail!("Failed to retry {} download(s)", error_count);
+    }
+
+    Ok(())
+}
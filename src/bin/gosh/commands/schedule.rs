@@ -0,0 +1,110 @@
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+
+use crate::cli::{OutputFormat, ScheduleAction, ScheduleArgs};
+use crate::config::CliConfig;
+use crate::format::format_duration;
+use crate::output::record::Output;
+use crate::schedule::{format_days, Schedule};
+
+#[derive(Serialize)]
+struct ScheduleOverview {
+    rules: Vec<RuleInfo>,
+    /// 1-based number of the rule in force
+    active: Option<usize>,
+    next_change: Option<NextChange>,
+}
+
+#[derive(Serialize)]
+struct RuleInfo {
+    start: String,
+    end: String,
+    days: String,
+    download_limit: Option<u64>,
+    upload_limit: Option<u64>,
+    actions: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct NextChange {
+    at: NaiveDateTime,
+    rule: Option<usize>,
+}
+
+pub async fn execute(args: ScheduleArgs, config: &CliConfig, output: &Output) -> Result<()> {
+    match args.action {
+        ScheduleAction::Show => show(config, output),
+    }
+}
+
+fn show(config: &CliConfig, output: &Output) -> Result<()> {
+    let schedule = Schedule::from_config(&config.schedule)?;
+    let now = Local::now().naive_local();
+    let active = schedule.active(now);
+    let next = schedule.next_change(now);
+
+    let overview = ScheduleOverview {
+        rules: schedule
+            .rules
+            .iter()
+            .map(|r| RuleInfo {
+                start: r.start.to_string(),
+                end: r.end.to_string(),
+                days: format_days(&r.days),
+                download_limit: r.download_limit,
+                upload_limit: r.upload_limit,
+                actions: r.actions.iter().map(|a| a.to_string()).collect(),
+            })
+            .collect(),
+        active: active.map(|i| i + 1),
+        next_change: next.map(|(at, rule)| NextChange {
+            at,
+            rule: rule.map(|i| i + 1),
+        }),
+    };
+
+    match output.format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string(&overview)?)
+        }
+        OutputFormat::JsonPretty => println!("{}", serde_json::to_string_pretty(&overview)?),
+        OutputFormat::Table => {
+            if schedule.rules.is_empty() {
+                println!("No schedule rules configured");
+                return Ok(());
+            }
+
+            println!("Schedule rules:");
+            for (i, rule) in schedule.rules.iter().enumerate() {
+                let marker = if active == Some(i) { '*' } else { ' ' };
+                println!("{} {:>2}. {}", marker, i + 1, rule);
+            }
+            println!();
+
+            match active {
+                Some(i) => println!("In force: rule {}", i + 1),
+                None => println!("In force: none (no schedule limits)"),
+            }
+            match next {
+                Some((at, rule)) => {
+                    let wait = (at - now).num_seconds().max(0) as u64;
+                    let then = match rule {
+                        Some(i) => format!("rule {}", i + 1),
+                        None => "no rule".to_string(),
+                    };
+                    println!(
+                        "Next change: {} (in {}), {}",
+                        at.format("%a %H:%M"),
+                        format_duration(wait),
+                        then
+                    );
+                }
+                None => println!("Next change: never"),
+            }
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => bail!(output.unsupported()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use anyhow::{bail, Context, Result};
+use gosh_dl::torrent::{Metainfo, PieceManager};
+use gosh_dl::DownloadOptions;
+use indicatif::ProgressBar;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::App;
+use crate::cli::{OutputFormat, SeedArgs};
+use crate::commands::add::{wait_for_completion, AddResult};
+use crate::output::record::Output;
+use crate::progress::{resolve_mode, ProgressInterval};
+use crate::store::{DownloadExtras, Store};
+
+#[derive(Serialize)]
+struct SeedResult {
+    id: String,
+    name: String,
+    data_dir: PathBuf,
+    pieces_verified: usize,
+    pieces_total: usize,
+}
+
+pub async fn execute(args: SeedArgs, app: &App, output: &Output) -> Result<()> {
+    let data = tokio::fs::read(&args.file)
+        .await
+        .with_context(|| format!("Failed to read torrent file: {}", args.file.display()))?;
+
+    let metainfo = Metainfo::parse(&data)
+        .with_context(|| format!("Failed to parse torrent file: {}", args.file.display()))?;
+
+    let seed_limits = DownloadExtras::seed_limits(
+        args.seed_ratio,
+        args.seed_time.as_deref(),
+        args.seed_idle.as_deref(),
+    )?;
+
+    let data_dir = resolve_data_dir(&args.data, &metainfo.info.name)?;
+
+    // Recheck every piece against the data on disk
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_message(format!("Verifying {}...", metainfo.info.name));
+    spinner.enable_steady_tick(Duration::from_millis(100));
+
+    let pieces = PieceManager::new(Arc::new(metainfo.clone()), data_dir.clone());
+    let verified = pieces
+        .verify_existing()
+        .await
+        .with_context(|| format!("Failed to verify data in {}", data_dir.display()))?;
+    let total = pieces.num_pieces();
+    spinner.finish_and_clear();
+
+    if verified < total && !args.allow_partial {
+        bail!(
+            "Only {}/{} pieces of '{}' verified in {}. Use --allow-partial to seed anyway \
+             (missing pieces will be downloaded).",
+            verified,
+            total,
+            metainfo.info.name,
+            data_dir.display()
+        );
+    }
+
+    // The engine verifies the data again on start and goes straight to seeding
+    let options = DownloadOptions {
+        save_dir: Some(data_dir.clone()),
+        seed_ratio: seed_limits.seed_ratio,
+        ..Default::default()
+    };
+    let id = app.engine().add_torrent(&data, options).await?;
+
+    if !seed_limits.is_empty() {
+        Store::update(&app.config.state_path(), |store| {
+            *store.entry(id) = seed_limits.clone();
+        })?;
+    }
+
+    let result = SeedResult {
+        id: id.to_gid(),
+        name: metainfo.info.name.clone(),
+        data_dir,
+        pieces_verified: verified,
+        pieces_total: total,
+    };
+
+    match output.format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string(&result)?);
+        }
+        OutputFormat::JsonPretty => {
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        OutputFormat::Table => {
+            println!(
+                "Verified {}/{} pieces in {}",
+                result.pieces_verified,
+                result.pieces_total,
+                result.data_dir.display()
+            );
+            println!("Seeding {} ({})", result.name, result.id);
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => bail!(output.unsupported()),
+    }
+
+    if args.wait {
+        let added = [AddResult {
+            id: result.id.clone(),
+            input: result.name.clone(),
+            kind: "torrent".to_string(),
+        }];
+        let mode = resolve_mode(None);
+        wait_for_completion(app, &added, false, mode, ProgressInterval::default()).await?;
+    }
+
+    Ok(())
+}
+
+/// The engine expects the directory that *contains* the torrent's top-level
+/// entry. Accept the content directory itself too, since that is what people
+/// usually point at.
+fn resolve_data_dir(dir: &Path, torrent_name: &str) -> Result<PathBuf> {
+    if !dir.is_dir() {
+        bail!("Data directory does not exist: {}", dir.display());
+    }
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve data directory: {}", dir.display()))?;
+
+    if !dir.join(torrent_name).exists() && dir.file_name() == Some(torrent_name.as_ref()) {
+        if let Some(parent) = dir.parent() {
+            return Ok(parent.to_path_buf());
+        }
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn data_dir_accepts_parent_or_content_dir() {
+        let tempdir = TempDir::new().unwrap();
+        let root = tempdir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("album")).unwrap();
+
+        assert_eq!(resolve_data_dir(&root, "album").unwrap(), root);
+        assert_eq!(
+            resolve_data_dir(&root.join("album"), "album").unwrap(),
+            root
+        );
+        assert!(resolve_data_dir(&root.join("missing"), "album").is_err());
+    }
+}
@@ -2,11 +2,11 @@ use anyhow::Result;
 use serde::Serialize;
 
 use crate::app::App;
-use crate::cli::OutputFormat;
 use crate::format::format_speed;
+use crate::output::record::{Output, Record, Value};
 
 #[derive(Serialize)]
-struct GlobalStats {
+pub struct GlobalStats {
     num_active: usize,
     num_waiting: usize,
     num_stopped: usize,
@@ -16,7 +16,24 @@ struct GlobalStats {
     upload_speed_formatted: String,
 }
 
-pub async fn execute(app: &App, output: OutputFormat) -> Result<()> {
+impl Record for GlobalStats {
+    const FIELDS: &'static [&'static str] =
+        &["active", "waiting", "stopped", "total", "down", "up"];
+
+    fn field(&self, name: &str) -> Value {
+        match name {
+            "active" => self.num_active.into(),
+            "waiting" => self.num_waiting.into(),
+            "stopped" => self.num_stopped.into(),
+            "total" => (self.num_active + self.num_waiting + self.num_stopped).into(),
+            "down" => self.download_speed.into(),
+            "up" => self.upload_speed.into(),
+            _ => Value::Empty,
+        }
+    }
+}
+
+pub async fn execute(app: &App, output: &Output) -> Result<()> {
     let stats = app.engine().global_stats();
 
     let formatted = GlobalStats {
@@ -29,31 +46,21 @@ pub async fn execute(app: &App, output: OutputFormat) -> Result<()> {
         upload_speed_formatted: format!("{}/s", format_speed(stats.upload_speed)),
     };
 
-    match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string(&formatted)?);
-        }
-        OutputFormat::JsonPretty => {
-            println!("{}", serde_json::to_string_pretty(&formatted)?);
-        }
-        OutputFormat::Table => {
-            println!("Global Statistics");
-            println!("=================");
-            println!();
-            println!("Downloads:");
-            println!("  Active:   {}", stats.num_active);
-            println!("  Waiting:  {}", stats.num_waiting);
-            println!("  Stopped:  {}", stats.num_stopped);
-            println!(
-                "  Total:    {}",
-                stats.num_active + stats.num_waiting + stats.num_stopped
-            );
-            println!();
-            println!("Speed:");
-            println!("  Download: {}/s", format_speed(stats.download_speed));
-            println!("  Upload:   {}/s", format_speed(stats.upload_speed));
-        }
-    }
-
-    Ok(())
+    output.print(&[&formatted], &formatted, || {
+        println!("Global Statistics");
+        println!("=================");
+        println!();
+        println!("Downloads:");
+        println!("  Active:   {}", stats.num_active);
+        println!("  Waiting:  {}", stats.num_waiting);
+        println!("  Stopped:  {}", stats.num_stopped);
+        println!(
+            "  Total:    {}",
+            stats.num_active + stats.num_waiting + stats.num_stopped
+        );
+        println!();
+        println!("Speed:");
+        println!("  Download: {}/s", format_speed(stats.download_speed));
+        println!("  Upload:   {}/s", format_speed(stats.upload_speed));
+    })
 }
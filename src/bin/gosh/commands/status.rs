@@ -1,39 +1,65 @@
 use anyhow::Result;
-use gosh_dl::{DownloadState, DownloadStatus};
+use chrono::{DateTime, Local, Utc};
+use gosh_dl::{DownloadState, DownloadStatus, EngineError};
 
 use crate::app::App;
-use crate::cli::{OutputFormat, StatusArgs};
+use crate::cli::StatusArgs;
 use crate::format::{format_duration, format_size};
+use crate::hold::{held, Hold};
+use crate::output::record::{DownloadRecord, Output};
+use crate::retry::is_failed;
+use crate::store::Store;
 use crate::util::{resolve_download_id, truncate_str};
 
-pub async fn execute(args: StatusArgs, app: &App, output: OutputFormat) -> Result<()> {
+pub async fn execute(args: StatusArgs, app: &App, output: &Output) -> Result<()> {
     let id = resolve_download_id(&args.id, app.engine())?;
 
     let status = app
         .engine()
         .status(id)
-        .ok_or_else(|| anyhow::anyhow!("Download not found: {}", args.id))?;
-
-    match output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string(&status)?);
-        }
-        OutputFormat::JsonPretty => {
-            println!("{}", serde_json::to_string_pretty(&status)?);
-        }
-        OutputFormat::Table => {
-            print_detailed_status(&status, args.peers, args.files);
-        }
-    }
-
-    Ok(())
+        .ok_or_else(|| EngineError::NotFound(args.id.clone()))?;
+
+    let store = Store::load(&app.config.state_path())?;
+    let record = DownloadRecord {
+        dl: &status,
+        store: &store,
+    };
+    output.print(&[record], &status, || {
+        let next_retry = store
+            .get(id)
+            .filter(|_| is_failed(&status))
+            .and_then(|e| Some((e.next_retry?, e.retry_attempts + 1)));
+        print_detailed_status(
+            &status,
+            held(&status, &store),
+            next_retry,
+            args.peers,
+            args.files,
+        );
+    })
 }
 
-fn print_detailed_status(status: &DownloadStatus, show_peers: bool, show_files: bool) {
+fn print_detailed_status(
+    status: &DownloadStatus,
+    hold: Option<Hold>,
+    next_retry: Option<(DateTime<Utc>, u32)>,
+    show_peers: bool,
+    show_files: bool,
+) {
     println!("Download: {}", status.id.to_gid());
     println!("Name: {}", status.metadata.name);
     println!("Type: {:?}", status.kind);
     println!("State: {}", format_state(&status.state));
+    if let Some(hold) = hold {
+        println!("Hold: {}", hold.describe());
+    }
+    if let Some((at, attempt)) = next_retry {
+        println!(
+            "Next retry: {} (attempt {})",
+            at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            attempt
+        );
+    }
     println!("Priority: {:?}", status.priority);
     println!();
 
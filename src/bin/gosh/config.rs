@@ -1,17 +1,57 @@
 use anyhow::{Context, Result};
-use chrono::Weekday;
+use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use tracing_subscriber::EnvFilter;
 
-use crate::util::parse_speed;
+use crate::logging::Rotation;
+use crate::output::columns::{parse_columns, Column, DEFAULT_COLUMNS};
+use crate::quota::Quota;
+use crate::retry::AutoRetry;
+use crate::schedule::{ClockTime, Schedule};
+use crate::util::parse_duration;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct CliConfig {
     pub general: GeneralConfig,
     pub engine: EngineSettings,
+    pub torrent: TorrentSettings,
     pub tui: TuiConfig,
+    #[serde(skip_serializing_if = "ListConfig::is_empty")]
+    pub list: ListConfig,
     pub schedule: ScheduleConfig,
+    pub quota: QuotaConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<CategoryConfig>,
+}
+
+/// Data caps; sizes take K/M/G suffixes, None = no cap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_download: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_upload: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_download: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_upload: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_download: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_upload: Option<String>,
+
+    /// Day of the month the monthly caps reset on (1-28)
+    pub reset_day: u32,
+
+    /// Day the weekly caps reset on (mon-sun)
+    pub week_start: String,
+
+    /// Categories or tags whose traffic does not count
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exempt: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -20,13 +60,89 @@ pub struct ScheduleConfig {
     pub rules: Vec<ScheduleRuleConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ScheduleRuleConfig {
-    pub start_hour: u8,
-    pub end_hour: u8,
+    /// Start time ("HH:MM"); whole-hour `start_hour` is still accepted
+    #[serde(alias = "start_hour", deserialize_with = "de_start_time")]
+    pub start: String,
+    /// End time ("HH:MM", exclusive); before `start` runs past midnight.
+    /// The older `end_hour` includes the whole hour.
+    #[serde(alias = "end_hour", deserialize_with = "de_end_time")]
+    pub end: String,
+    #[serde(default)]
     pub days: String,
     pub download_limit: Option<String>,
     pub upload_limit: Option<String>,
+    /// `pause_all`, `resume_all`, `max_concurrent_downloads=N`, `seeding=off`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+}
+
+/// A named set of download defaults, applied by `--category` or when a
+/// matcher fits the added URL
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CategoryConfig {
+    pub name: String,
+
+    /// Save directory (None = general.download_dir)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<gosh_dl::DownloadPriority>,
+
+    /// Speed limit per download (e.g. "2M")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_speed: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_ratio: Option<f64>,
+
+    /// Shell command run when a download in the category completes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_complete: Option<String>,
+
+    /// Shell command run when a download in the category fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_error: Option<String>,
+
+    /// URL patterns; `*` matches anything, otherwise a substring match
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+
+    /// File extensions, without the dot
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+
+    /// Hosts, including their subdomains
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+}
+
+/// A rule time, as "HH:MM" or a whole hour from older configs
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TimeOrHour {
+    Time(String),
+    Hour(u8),
+}
+
+fn de_start_time<'de, D: serde::Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    Ok(match TimeOrHour::deserialize(d)? {
+        TimeOrHour::Time(s) => s,
+        TimeOrHour::Hour(h) => ClockTime::from_hour(h).to_string(),
+    })
+}
+
+fn de_end_time<'de, D: serde::Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    Ok(match TimeOrHour::deserialize(d)? {
+        TimeOrHour::Time(s) => s,
+        TimeOrHour::Hour(h) => ClockTime::from_hour(h.saturating_add(1)).to_string(),
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,8 +157,42 @@ pub struct GeneralConfig {
     /// Log file path (None = stderr only)
     pub log_file: Option<PathBuf>,
 
-    /// Log level (trace, debug, info, warn, error)
+    /// Log level for the log file (trace, debug, info, warn, error), with
+    /// optional per-module filters such as "info,gosh_dl::http=debug"
     pub log_level: String,
+
+    /// Log file format
+    pub log_format: LogFormat,
+
+    /// When to start a new log file: "daily", a size such as "10M", or "never"
+    pub log_rotate: String,
+
+    /// Rotated log files to keep
+    pub log_keep: usize,
+
+    /// How long finished downloads stay in the history
+    #[serde(skip_serializing_if = "RetentionConfig::is_empty")]
+    pub retention: RetentionConfig,
+}
+
+/// Age after which finished downloads are removed at startup (e.g. "30d",
+/// None = kept); their files stay on disk
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Completed downloads, by when they completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<String>,
+
+    /// Failed downloads, by when they failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<String>,
+}
+
+impl RetentionConfig {
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_none() && self.failed.is_none()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +245,116 @@ pub struct EngineSettings {
 
     /// Accept invalid TLS certificates (insecure)
     pub accept_invalid_certs: bool,
+
+    /// Re-add downloads that failed with a retryable error (None = off)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_retry: Option<AutoRetryConfig>,
+}
+
+/// Backoff for retrying failed downloads once the engine's own retries are
+/// used up; durations take s/m/h/d suffixes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoRetryConfig {
+    /// Wait before the first attempt; doubles with each one after it
+    pub interval: String,
+
+    /// Longest wait between two attempts
+    pub max_interval: String,
+
+    /// Attempts before giving up on a download
+    pub max_attempts: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TorrentSettings {
+    /// What to do with a torrent once a seeding limit is reached
+    pub seeding_limit_action: SeedingLimitAction,
+
+    /// Default seeding time limit (e.g. "24h", None = unlimited)
+    pub seed_time: Option<String>,
+
+    /// Stop seeding after this long without uploading (e.g. "30m", None = never)
+    pub seed_idle: Option<String>,
+
+    /// Maximum torrents seeding at the same time (None = unlimited)
+    pub max_seeding: Option<usize>,
+
+    /// Write a .torrent file once a magnet's metadata has been fetched
+    pub save_metadata: bool,
+
+    /// Directory for saved .torrent files (None = next to the download)
+    pub metadata_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedingLimitAction {
+    /// Pause the torrent, keeping it in the list
+    #[default]
+    Pause,
+    /// Remove the torrent from the list, keeping its files
+    Remove,
+    /// Remove the torrent and delete its files
+    RemoveWithFiles,
+}
+
+impl SeedingLimitAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::Remove => "remove",
+            Self::RemoveWithFiles => "remove_with_files",
+        }
+    }
+}
+
+impl std::str::FromStr for SeedingLimitAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "pause" => Ok(Self::Pause),
+            "remove" => Ok(Self::Remove),
+            "remove_with_files" => Ok(Self::RemoveWithFiles),
+            _ => anyhow::bail!(
+                "Invalid seeding limit action '{}'. Use pause, remove or remove_with_files",
+                s
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// One line of text per event
+    #[default]
+    Text,
+    /// One JSON object per event
+    Json,
+}
+
+impl LogFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => anyhow::bail!("Invalid log format '{}'. Use text or json", s),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,6 +373,28 @@ pub struct TuiConfig {
     pub show_peers: bool,
 }
 
+/// `gosh list` table defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListConfig {
+    /// Columns shown without --columns (empty = the built-in set)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub default_columns: Vec<String>,
+}
+
+impl ListConfig {
+    pub fn is_empty(&self) -> bool {
+        self.default_columns.is_empty()
+    }
+
+    pub fn columns(&self) -> Result<Vec<Column>> {
+        if self.default_columns.is_empty() {
+            return Ok(DEFAULT_COLUMNS.to_vec());
+        }
+        parse_columns(&self.default_columns.join(","))
+    }
+}
+
 impl Default for GeneralConfig {
     fn default() -> Self {
         let download_dir = dirs::download_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -126,6 +408,10 @@ impl Default for GeneralConfig {
             database_path: data_dir.join("gosh.db"),
             log_file: None,
             log_level: "info".to_string(),
+            log_format: LogFormat::Text,
+            log_rotate: "daily".to_string(),
+            log_keep: 5,
+            retention: RetentionConfig::default(),
         }
     }
 }
@@ -149,6 +435,33 @@ impl Default for EngineSettings {
             read_timeout: 60,
             max_retries: 3,
             accept_invalid_certs: false,
+            auto_retry: None,
+        }
+    }
+}
+
+impl Default for AutoRetryConfig {
+    fn default() -> Self {
+        Self {
+            interval: "10m".to_string(),
+            max_interval: "1h".to_string(),
+            max_attempts: 12,
+        }
+    }
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            daily_download: None,
+            daily_upload: None,
+            weekly_download: None,
+            weekly_upload: None,
+            monthly_download: None,
+            monthly_upload: None,
+            reset_day: 1,
+            week_start: "mon".to_string(),
+            exempt: Vec::new(),
         }
     }
 }
@@ -188,6 +501,13 @@ impl CliConfig {
             .unwrap_or_else(|| PathBuf::from("gosh-config.toml"))
     }
 
+    /// Path of the CLI state file, kept next to the engine database
+    pub fn state_path(&self) -> PathBuf {
+        self.general
+            .database_path
+            .with_file_name("gosh-cli-state.json")
+    }
+
     /// Apply environment variable overrides (proxy)
     pub fn apply_env_overrides(&mut self) {
         if self.engine.proxy_url.is_none() {
@@ -221,15 +541,45 @@ impl CliConfig {
         if self.tui.refresh_rate_ms == 0 {
             anyhow::bail!("tui.refresh_rate_ms must be at least 1");
         }
+        self.list.columns().context("list.default_columns")?;
         if self.engine.seed_ratio < 0.0 {
             anyhow::bail!("engine.seed_ratio must not be negative");
         }
-        for (i, rule) in self.schedule.rules.iter().enumerate() {
-            if rule.start_hour > 23 {
-                anyhow::bail!("schedule.rules[{}].start_hour must be 0-23", i);
-            }
-            if rule.end_hour > 23 {
-                anyhow::bail!("schedule.rules[{}].end_hour must be 0-23", i);
+        if let Some(ref s) = self.torrent.seed_time {
+            parse_duration(s).context("torrent.seed_time is not a valid duration")?;
+        }
+        if let Some(ref s) = self.torrent.seed_idle {
+            parse_duration(s).context("torrent.seed_idle is not a valid duration")?;
+        }
+        EnvFilter::try_new(&self.general.log_level)
+            .context("general.log_level is not a valid filter")?;
+        self.general
+            .log_rotate
+            .parse::<Rotation>()
+            .context("general.log_rotate")?;
+        if let Some(ref s) = self.general.retention.completed {
+            parse_duration(s).context("general.retention.completed is not a valid duration")?;
+        }
+        if let Some(ref s) = self.general.retention.failed {
+            parse_duration(s).context("general.retention.failed is not a valid duration")?;
+        }
+        if let Some(ref auto_retry) = self.engine.auto_retry {
+            AutoRetry::from_config(auto_retry)?;
+        }
+        if self.torrent.max_seeding == Some(0) {
+            anyhow::bail!("torrent.max_seeding must be at least 1");
+        }
+        Schedule::from_config(&self.schedule)?;
+        Quota::from_config(&self.quota)?;
+        for (i, category) in self.categories.iter().enumerate() {
+            category
+                .validate()
+                .with_context(|| format!("categories[{}]", i))?;
+            if self.categories[..i]
+                .iter()
+                .any(|c| c.name.eq_ignore_ascii_case(&category.name))
+            {
+                anyhow::bail!("Duplicate category '{}'", category.name);
             }
         }
         Ok(())
@@ -244,22 +594,17 @@ impl CliConfig {
             min_segment_size: self.engine.min_segment_size,
             global_download_limit: self.engine.global_download_limit,
             global_upload_limit: self.engine.global_upload_limit,
-            schedule_rules: self
-                .schedule
-                .rules
-                .iter()
-                .map(|r| {
-                    let days = parse_schedule_days(&r.days);
-                    let dl = r.download_limit.as_ref().and_then(|s| parse_speed(s).ok());
-                    let ul = r.upload_limit.as_ref().and_then(|s| parse_speed(s).ok());
-                    gosh_dl::ScheduleRule::new(r.start_hour, r.end_hour, days, dl, ul)
-                })
-                .collect(),
+            // The supervisor keeps these up to date as rules come and go
+            schedule_rules: Schedule::from_config(&self.schedule)
+                .map(|s| s.engine_rules(Local::now().naive_local()))
+                .unwrap_or_default(),
             user_agent: self.engine.user_agent.clone(),
             enable_dht: self.engine.enable_dht,
             enable_pex: self.engine.enable_pex,
             enable_lpd: self.engine.enable_lpd,
             max_peers: self.engine.max_peers,
+            // The engine stops torrents at the global ratio by its own byte
+            // counters; the supervisor enforces per-download ratios
             seed_ratio: self.engine.seed_ratio,
             database_path: Some(self.general.database_path.clone()),
             http: gosh_dl::config::HttpConfig {
@@ -295,36 +640,3 @@ impl CliConfig {
         Ok(())
     }
 }
-
-fn parse_schedule_days(s: &str) -> Vec<Weekday> {
-    match s.to_lowercase().as_str() {
-        "all" | "" => Vec::new(),
-        "weekdays" => vec![
-            Weekday::Mon,
-            Weekday::Tue,
-            Weekday::Wed,
-            Weekday::Thu,
-            Weekday::Fri,
-        ],
-        "weekends" => vec![Weekday::Sat, Weekday::Sun],
-        other => other
-            .split(',')
-            .filter_map(|d| {
-                let day = d.trim().to_lowercase();
-                match day.as_str() {
-                    "mon" => Some(Weekday::Mon),
-                    "tue" => Some(Weekday::Tue),
-                    "wed" => Some(Weekday::Wed),
-                    "thu" => Some(Weekday::Thu),
-                    "fri" => Some(Weekday::Fri),
-                    "sat" => Some(Weekday::Sat),
-                    "sun" => Some(Weekday::Sun),
-                    _ => {
-                        tracing::warn!("Unrecognized schedule day '{}', ignoring", d.trim());
-                        None
-                    }
-                }
-            })
-            .collect(),
-    }
-}
@@ -5,15 +5,24 @@
 
 use anyhow::{bail, Result};
 use gosh_dl::{DownloadEvent, DownloadId, DownloadOptions, DownloadState};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use crate::app::App;
-use crate::config::CliConfig;
+use crate::category;
+use crate::cli::ProgressMode;
+use crate::config::{CategoryConfig, CliConfig};
+use crate::exit_code::{Category, SUCCESS};
 use crate::format::{print_error, print_warning};
 use crate::input::url_parser::{parse_input, ParsedInput};
+use crate::output::table::print_report;
+use crate::progress::{error_kind, multi_progress, resolve_mode, ProgressInterval, ProgressLines};
+use crate::purge;
+use crate::report::{output_path, Outcome, Report};
+use crate::store::{DownloadExtras, Store};
+use crate::supervisor::{Supervisor, SupervisorAction};
 use crate::util::{
     parse_checksum, parse_selected_files, parse_speed, sanitize_filename, truncate_str,
     validate_max_connections, validate_seed_ratio,
@@ -34,14 +43,13 @@ pub struct DirectOptions {
     pub sequential: bool,
     pub select_files: Option<String>,
     pub seed_ratio: Option<f64>,
-}
-
-/// Exit codes for direct download mode
-pub mod exit_codes {
-    pub const SUCCESS: i32 = 0;
-    pub const PARTIAL_FAILURE: i32 = 1;
-    pub const TOTAL_FAILURE: i32 = 2;
-    pub const INTERRUPTED: i32 = 130;
+    pub seed_time: Option<String>,
+    pub seed_idle: Option<String>,
+    pub save_torrent: Option<Option<PathBuf>>,
+    pub progress: Option<ProgressMode>,
+    pub progress_fd: Option<i32>,
+    pub progress_interval: ProgressInterval,
+    pub report: Option<PathBuf>,
 }
 
 /// State tracking for each download
@@ -70,11 +78,23 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
         .map(|u| parse_input(u))
         .collect::<Result<_>>()?;
 
+    let seed_limits = DownloadExtras::seed_limits(
+        opts.seed_ratio,
+        opts.seed_time.as_deref(),
+        opts.seed_idle.as_deref(),
+    )?;
+
+    let mode = resolve_mode(opts.progress);
+    let mut lines = ProgressLines::open(mode, opts.progress_fd, opts.progress_interval)?;
+    // The summary table goes to stdout, so not when JSON progress does
+    let summary = mode != ProgressMode::Json || opts.progress_fd.is_some();
+
     // Initialize the download engine
     let app = App::new(config).await?;
+    purge::apply_retention(app.engine(), &app.config).await?;
 
-    // Setup multi-progress bar
-    let multi = MultiProgress::new();
+    // Setup multi-progress bar, hidden unless progress is shown as bars
+    let multi = multi_progress(mode);
 
     let bar_style = ProgressStyle::with_template(
         "{spinner:.green} {msg:<40} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}) ETA: {eta}",
@@ -86,7 +106,7 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
 
     // Add downloads and create progress bars
     let mut downloads: HashMap<DownloadId, DownloadInfo> = HashMap::new();
-    let mut failed_to_add = 0;
+    let mut report = Report::default();
 
     for input in &inputs {
         let pb = multi.add(ProgressBar::new(0));
@@ -94,7 +114,8 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
         pb.set_message(truncate_str(&input.display(), 40));
         pb.enable_steady_tick(Duration::from_millis(100));
 
-        let options = build_options(&opts, input)?;
+        let category = category::matching(&app.config.categories, input);
+        let options = build_options(&opts, input, category)?;
 
         let result = match input {
             ParsedInput::Http(url) => app.engine().add_http(url, options).await,
@@ -107,6 +128,33 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
 
         match result {
             Ok(id) => {
+                let is_torrent =
+                    matches!(input, ParsedInput::Magnet(_) | ParsedInput::TorrentFile(_));
+                let mut extras = if is_torrent {
+                    seed_limits.clone()
+                } else {
+                    DownloadExtras::default()
+                };
+                if let (ParsedInput::Magnet(_), Some(target)) = (input, &opts.save_torrent) {
+                    extras.save_torrent = true;
+                    extras.torrent_path = target.clone();
+                }
+                if let Some(category) = category {
+                    if is_torrent && extras.seed_ratio.is_none() {
+                        extras.seed_ratio = category.seed_ratio;
+                    }
+                    extras.category = Some(category.name.clone());
+                }
+                if !extras.is_empty() {
+                    Store::update(&app.config.state_path(), |store| {
+                        *store.entry(id) = extras;
+                    })?;
+                }
+                if let Some(ref mut lines) = lines {
+                    lines.started(id, &input.display())?;
+                }
+                let checksum = opts.checksum.is_some() && matches!(input, ParsedInput::Http(_));
+                report.track(id, &input.display(), checksum, app.engine());
                 downloads.insert(
                     id,
                     DownloadInfo {
@@ -119,20 +167,27 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
             }
             Err(e) => {
                 pb.abandon_with_message(format!("Failed: {}", truncate_str(&e.to_string(), 35)));
-                failed_to_add += 1;
+                if let Some(ref mut lines) = lines {
+                    lines.failed(None, &input.display(), "add", &e.to_string(), false)?;
+                }
+                let category = Category::of_engine_error(&e);
+                report.failed_to_add(&input.display(), &e.to_string(), category);
             }
         }
     }
 
     if downloads.is_empty() {
+        finish_report(&report, opts.report.as_deref(), summary)?;
         app.shutdown().await?;
         print_error("All downloads failed to start");
-        return Ok(exit_codes::TOTAL_FAILURE);
+        return Ok(report.failure().map_or(SUCCESS, Category::code));
     }
 
     // Subscribe to events and monitor progress
     let mut events = app.subscribe();
     let download_ids: HashSet<DownloadId> = downloads.keys().copied().collect();
+    let mut supervisor = Supervisor::new(&app.config);
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
 
     loop {
         // Check if all downloads are done
@@ -143,19 +198,46 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
         // Process events with timeout
         tokio::select! {
             _ = tokio::signal::ctrl_c() => {
+                report.interrupt_remaining(app.engine());
                 // Cancel all active downloads
                 for id in &download_ids {
                     let _ = app.engine().cancel(*id, false).await;
                 }
-                for info in downloads.values() {
+                for (id, info) in &downloads {
                     if !info.completed && !info.failed {
                         info.progress_bar.abandon_with_message("Interrupted");
+                        if let Some(ref mut lines) = lines {
+                            lines.failed(Some(*id), &info.name, "interrupted", "Interrupted", true)?;
+                        }
                     }
                 }
+                finish_report(&report, opts.report.as_deref(), summary)?;
                 app.shutdown().await?;
-                return Ok(exit_codes::INTERRUPTED);
+                return Ok(Category::Interrupted.code());
+            }
+            _ = ticker.tick() => {
+                for action in supervisor.tick(app.engine()).await {
+                    let SupervisorAction::SeedingStopped { id, limit, .. } = action else {
+                        continue;
+                    };
+                    if let Some(info) = downloads.get_mut(&id) {
+                        info.completed = true;
+                        info.progress_bar.finish_with_message(format!(
+                            "{} - Done ({})",
+                            truncate_str(&info.name, 33),
+                            limit
+                        ));
+                        if let Some(ref mut lines) = lines {
+                            lines.completed(id, app.engine().status(id).map(|s| output_path(&s)))?;
+                        }
+                        report.finish(id, Outcome::Completed, None, app.engine());
+                    }
+                }
             }
             event = events.recv() => {
+                if let Ok(ref event) = event {
+                    supervisor.handle_event(app.engine(), event);
+                }
                 match event {
                     Ok(DownloadEvent::Progress { id, progress }) if download_ids.contains(&id) => {
                         if let Some(info) = downloads.get_mut(&id) {
@@ -167,6 +249,10 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
                             }
                             info.progress_bar.set_position(progress.completed_size);
                         }
+                        if let Some(ref mut lines) = lines {
+                            lines.progress(id, &progress)?;
+                        }
+                        report.progress(id, &progress);
                     }
                     Ok(DownloadEvent::Completed { id }) if download_ids.contains(&id) => {
                         if let Some(info) = downloads.get_mut(&id) {
@@ -174,13 +260,25 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
                             info.progress_bar
                                 .finish_with_message(format!("{} - Done", truncate_str(&info.name, 33)));
                         }
+                        if let Some(ref mut lines) = lines {
+                            lines.completed(id, app.engine().status(id).map(|s| output_path(&s)))?;
+                        }
+                        report.finish(id, Outcome::Completed, None, app.engine());
                     }
-                    Ok(DownloadEvent::Failed { id, error, .. }) if download_ids.contains(&id) => {
+                    Ok(DownloadEvent::Failed { id, error, retryable }) if download_ids.contains(&id) => {
                         if let Some(info) = downloads.get_mut(&id) {
                             info.failed = true;
                             info.progress_bar
                                 .abandon_with_message(format!("Failed: {}", truncate_str(&error, 32)));
+                            if let Some(ref mut lines) = lines {
+                                let kind = match app.engine().status(id).map(|s| s.state) {
+                                    Some(DownloadState::Error { kind, .. }) => error_kind(&kind),
+                                    _ => error_kind(""),
+                                };
+                                lines.failed(Some(id), &info.name, &kind, &error, retryable)?;
+                            }
                         }
+                        report.finish(id, Outcome::Failed, Some(&error), app.engine());
                     }
                     Ok(DownloadEvent::StateChanged { id, new_state, .. })
                         if download_ids.contains(&id) =>
@@ -212,31 +310,55 @@ pub async fn execute(opts: DirectOptions, config: CliConfig) -> Result<i32> {
         }
     }
 
+    report.interrupt_remaining(app.engine());
+    finish_report(&report, opts.report.as_deref(), summary)?;
+
     // Shutdown engine gracefully
     app.shutdown().await?;
 
     // Determine exit code
     let completed_count = downloads.values().filter(|d| d.completed).count();
-    let failed_count = downloads.values().filter(|d| d.failed).count() + failed_to_add;
+    let failed_count = report
+        .entries()
+        .iter()
+        .filter(|e| e.outcome == Outcome::Failed)
+        .count();
     let total = inputs.len();
 
-    if failed_count == 0 {
-        Ok(exit_codes::SUCCESS)
-    } else if completed_count > 0 {
+    let Some(failure) = report.failure() else {
+        return Ok(SUCCESS);
+    };
+    if completed_count > 0 {
         print_warning(&format!(
             "{}/{} downloads completed, {} failed",
             completed_count, total, failed_count
         ));
-        Ok(exit_codes::PARTIAL_FAILURE)
     } else {
         print_error(&format!("All {} downloads failed", total));
-        Ok(exit_codes::TOTAL_FAILURE)
+    }
+    Ok(failure.code())
+}
+
+/// Print the summary table, unless stdout carries JSON progress, and write
+/// the `--report` file
+fn finish_report(report: &Report, path: Option<&Path>, table: bool) -> Result<()> {
+    if table {
+        println!();
+        print_report(report.entries());
+    }
+    match path {
+        Some(path) => report.write(path),
+        None => Ok(()),
     }
 }
 
 /// Build download options from direct mode CLI options
-fn build_options(opts: &DirectOptions, input: &ParsedInput) -> Result<DownloadOptions> {
-    let mut options = DownloadOptions::default();
+fn build_options(
+    opts: &DirectOptions,
+    input: &ParsedInput,
+    category: Option<&CategoryConfig>,
+) -> Result<DownloadOptions> {
+    let mut options = category.map(CategoryConfig::options).unwrap_or_default();
 
     if let Some(ref dir) = opts.dir {
         options.save_dir = Some(dir.clone());
@@ -0,0 +1,413 @@
+//! Exit codes
+//!
+//! Failures are sorted into categories with stable exit codes, so scripts
+//! can tell a dropped connection from a full disk. Engine errors are
+//! classified by their variant and kind; [`Category::of_kind`] applies the
+//! same mapping to the `kind` a failed download's state records, which is
+//! only the engine error's debug form.
+
+use gosh_dl::{EngineError, NetworkErrorKind, ProtocolErrorKind, StorageErrorKind};
+use serde::Serialize;
+
+/// Why gosh failed, as an exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Anything not covered below, or downloads failing for different reasons
+    Error,
+    /// Unknown flag or bad argument, reported by the argument parser
+    Usage,
+    /// DNS failure, refused or dropped connection, timeout
+    Network,
+    /// The server answered with an error status
+    Http,
+    /// TLS handshake or certificate failure
+    Tls,
+    /// The downloaded data did not match its checksum
+    Checksum,
+    DiskFull,
+    PermissionDenied,
+    /// Bad URL, magnet link, torrent file or option
+    InvalidInput,
+    /// No such download or file
+    NotFound,
+    /// Stopped with Ctrl-C
+    Interrupted,
+}
+
+pub const SUCCESS: i32 = 0;
+
+impl Category {
+    pub const ALL: &'static [Category] = &[
+        Category::Error,
+        Category::Usage,
+        Category::Network,
+        Category::Http,
+        Category::Tls,
+        Category::Checksum,
+        Category::DiskFull,
+        Category::PermissionDenied,
+        Category::InvalidInput,
+        Category::NotFound,
+        Category::Interrupted,
+    ];
+
+    pub fn code(self) -> i32 {
+        match self {
+            Category::Error => 1,
+            Category::Usage => 2,
+            Category::Network => 3,
+            Category::Http => 4,
+            Category::Tls => 5,
+            Category::Checksum => 6,
+            Category::DiskFull => 7,
+            Category::PermissionDenied => 8,
+            Category::InvalidInput => 9,
+            Category::NotFound => 10,
+            Category::Interrupted => 130,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Error => "error",
+            Category::Usage => "usage",
+            Category::Network => "network",
+            Category::Http => "http",
+            Category::Tls => "tls",
+            Category::Checksum => "checksum",
+            Category::DiskFull => "disk_full",
+            Category::PermissionDenied => "permission_denied",
+            Category::InvalidInput => "invalid_input",
+            Category::NotFound => "not_found",
+            Category::Interrupted => "interrupted",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Category::Error => "Any other error, or downloads that failed for different reasons",
+            Category::Usage => "Unknown flag or bad argument",
+            Category::Network => "DNS failure, refused or dropped connection, timeout",
+            Category::Http => "The server answered with an error status",
+            Category::Tls => "TLS handshake or certificate failure",
+            Category::Checksum => "Downloaded data did not match its checksum",
+            Category::DiskFull => "No space left on the device",
+            Category::PermissionDenied => "A file or directory could not be written",
+            Category::InvalidInput => "Bad URL, magnet link, torrent file or option",
+            Category::NotFound => "No such download or file",
+            Category::Interrupted => "Stopped with Ctrl-C",
+        }
+    }
+
+    /// Classify a download's error `kind`, e.g.
+    /// `Network { kind: Timeout, message: "…", retryable: true }`
+    pub fn of_kind(kind: &str) -> Self {
+        let word = |s: &str| -> String {
+            s.trim_start()
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .collect()
+        };
+        let variant = word(kind);
+        let inner = kind
+            .split_once("kind: ")
+            .map(|(_, rest)| word(rest))
+            .unwrap_or_default();
+        match (variant.as_str(), inner.as_str()) {
+            ("Network", "Tls") => Category::Tls,
+            ("Network", "HttpStatus") => Category::Http,
+            ("Network", _) => Category::Network,
+            ("Storage", "DiskFull") => Category::DiskFull,
+            ("Storage", "PermissionDenied") => Category::PermissionDenied,
+            ("Storage", "NotFound") => Category::NotFound,
+            ("Storage", "PathTraversal" | "InvalidPath") => Category::InvalidInput,
+            ("Protocol", "HashMismatch") => Category::Checksum,
+            ("Protocol", "InvalidUrl" | "InvalidTorrent" | "InvalidMagnet") => {
+                Category::InvalidInput
+            }
+            ("InvalidInput", _) => Category::InvalidInput,
+            ("NotFound", _) => Category::NotFound,
+            _ => Category::Error,
+        }
+    }
+
+    pub fn of_engine_error(error: &EngineError) -> Self {
+        match error {
+            EngineError::Network { kind, .. } => match kind {
+                NetworkErrorKind::Tls => Category::Tls,
+                NetworkErrorKind::HttpStatus(_) => Category::Http,
+                NetworkErrorKind::DnsResolution
+                | NetworkErrorKind::ConnectionRefused
+                | NetworkErrorKind::ConnectionReset
+                | NetworkErrorKind::Timeout
+                | NetworkErrorKind::Unreachable
+                | NetworkErrorKind::TooManyRedirects
+                | NetworkErrorKind::Other => Category::Network,
+            },
+            EngineError::Storage { kind, .. } => match kind {
+                StorageErrorKind::DiskFull => Category::DiskFull,
+                StorageErrorKind::PermissionDenied => Category::PermissionDenied,
+                StorageErrorKind::NotFound => Category::NotFound,
+                StorageErrorKind::PathTraversal | StorageErrorKind::InvalidPath => {
+                    Category::InvalidInput
+                }
+                StorageErrorKind::AlreadyExists | StorageErrorKind::Io => Category::Error,
+            },
+            EngineError::Protocol { kind, .. } => match kind {
+                ProtocolErrorKind::HashMismatch => Category::Checksum,
+                ProtocolErrorKind::InvalidUrl
+                | ProtocolErrorKind::InvalidTorrent
+                | ProtocolErrorKind::InvalidMagnet => Category::InvalidInput,
+                ProtocolErrorKind::RangeNotSupported
+                | ProtocolErrorKind::InvalidResponse
+                | ProtocolErrorKind::TrackerError
+                | ProtocolErrorKind::PeerProtocol
+                | ProtocolErrorKind::BencodeParse
+                | ProtocolErrorKind::PexError
+                | ProtocolErrorKind::DhtError
+                | ProtocolErrorKind::LpdError
+                | ProtocolErrorKind::MetadataError => Category::Error,
+            },
+            EngineError::InvalidInput { .. } => Category::InvalidInput,
+            EngineError::NotFound(_) => Category::NotFound,
+            EngineError::ResourceLimit { .. }
+            | EngineError::AlreadyExists(_)
+            | EngineError::InvalidState { .. }
+            | EngineError::Shutdown
+            | EngineError::Database(_)
+            | EngineError::Internal(_) => Category::Error,
+        }
+    }
+
+    /// Classify an error by the first cause in its chain that has a category
+    pub fn of_error(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(e) = cause.downcast_ref::<CategoryError>() {
+                return e.category;
+            }
+            if let Some(e) = cause.downcast_ref::<EngineError>() {
+                return Self::of_engine_error(e);
+            }
+            if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+                match e.kind() {
+                    std::io::ErrorKind::NotFound => return Category::NotFound,
+                    std::io::ErrorKind::PermissionDenied => return Category::PermissionDenied,
+                    std::io::ErrorKind::StorageFull => return Category::DiskFull,
+                    _ => {}
+                }
+            }
+        }
+        Category::Error
+    }
+
+    /// The category for a run whose downloads failed for these reasons:
+    /// theirs when they agree, [`Category::Error`] when they do not
+    pub fn of_failures(failures: &[Category]) -> Option<Self> {
+        let (first, rest) = failures.split_first()?;
+        if rest.iter().all(|c| c == first) {
+            Some(*first)
+        } else {
+            Some(Category::Error)
+        }
+    }
+}
+
+/// An error gosh raises itself, with the category to exit with
+#[derive(Debug)]
+pub struct CategoryError {
+    pub category: Category,
+    pub message: String,
+}
+
+impl CategoryError {
+    pub fn new(category: Category, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for CategoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CategoryError {}
+
+/// Print the table of exit codes, for `gosh help exit-codes`
+pub fn print_table() {
+    println!("{:>4}  {:<18} Meaning", "Code", "Category");
+    println!("{:>4}  {:<18} Success", SUCCESS, "");
+    for category in Category::ALL {
+        println!(
+            "{:>4}  {:<18} {}",
+            category.code(),
+            category.name(),
+            category.description()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The engine error's own category, and the one read back from its debug
+    /// form, as a failed download records it
+    fn categories(error: EngineError) -> (Category, Category) {
+        (
+            Category::of_engine_error(&error),
+            Category::of_kind(&format!("{:?}", error)),
+        )
+    }
+
+    fn both(category: Category) -> (Category, Category) {
+        (category, category)
+    }
+
+    #[test]
+    fn network_errors_map_by_kind() {
+        let network = |kind| EngineError::Network {
+            kind,
+            message: String::new(),
+            retryable: true,
+        };
+        for kind in [
+            NetworkErrorKind::DnsResolution,
+            NetworkErrorKind::ConnectionRefused,
+            NetworkErrorKind::ConnectionReset,
+            NetworkErrorKind::Timeout,
+            NetworkErrorKind::Unreachable,
+            NetworkErrorKind::TooManyRedirects,
+            NetworkErrorKind::Other,
+        ] {
+            assert_eq!(categories(network(kind)), both(Category::Network));
+        }
+        assert_eq!(
+            categories(network(NetworkErrorKind::HttpStatus(404))),
+            both(Category::Http)
+        );
+        assert_eq!(
+            categories(network(NetworkErrorKind::Tls)),
+            both(Category::Tls)
+        );
+    }
+
+    #[test]
+    fn storage_errors_map_by_kind() {
+        let storage = |kind| EngineError::Storage {
+            kind,
+            path: "/tmp/a".into(),
+            message: "kind: PermissionDenied".to_string(),
+        };
+        for (kind, category) in [
+            (StorageErrorKind::NotFound, Category::NotFound),
+            (
+                StorageErrorKind::PermissionDenied,
+                Category::PermissionDenied,
+            ),
+            (StorageErrorKind::DiskFull, Category::DiskFull),
+            (StorageErrorKind::PathTraversal, Category::InvalidInput),
+            (StorageErrorKind::AlreadyExists, Category::Error),
+            (StorageErrorKind::InvalidPath, Category::InvalidInput),
+            (StorageErrorKind::Io, Category::Error),
+        ] {
+            assert_eq!(categories(storage(kind)), both(category), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn protocol_errors_map_by_kind() {
+        let protocol = |kind| EngineError::Protocol {
+            kind,
+            message: String::new(),
+        };
+        for (kind, category) in [
+            (ProtocolErrorKind::InvalidUrl, Category::InvalidInput),
+            (ProtocolErrorKind::RangeNotSupported, Category::Error),
+            (ProtocolErrorKind::InvalidResponse, Category::Error),
+            (ProtocolErrorKind::InvalidTorrent, Category::InvalidInput),
+            (ProtocolErrorKind::InvalidMagnet, Category::InvalidInput),
+            (ProtocolErrorKind::HashMismatch, Category::Checksum),
+            (ProtocolErrorKind::TrackerError, Category::Error),
+            (ProtocolErrorKind::PeerProtocol, Category::Error),
+            (ProtocolErrorKind::BencodeParse, Category::Error),
+            (ProtocolErrorKind::PexError, Category::Error),
+            (ProtocolErrorKind::DhtError, Category::Error),
+            (ProtocolErrorKind::LpdError, Category::Error),
+            (ProtocolErrorKind::MetadataError, Category::Error),
+        ] {
+            assert_eq!(categories(protocol(kind)), both(category), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn invalid_input_errors_are_invalid_input() {
+        let error = EngineError::InvalidInput {
+            field: "url",
+            message: "bad".to_string(),
+        };
+        assert_eq!(categories(error), both(Category::InvalidInput));
+    }
+
+    #[test]
+    fn not_found_errors_are_not_found() {
+        let error = EngineError::NotFound("abc".to_string());
+        assert_eq!(categories(error), both(Category::NotFound));
+    }
+
+    #[test]
+    fn other_engine_errors_are_plain_errors() {
+        for error in [
+            EngineError::ResourceLimit {
+                resource: "downloads",
+                limit: 5,
+            },
+            EngineError::AlreadyExists("abc".to_string()),
+            EngineError::InvalidState {
+                action: "pause",
+                current_state: "completed".to_string(),
+            },
+            EngineError::Shutdown,
+            EngineError::Database("locked".to_string()),
+            EngineError::Internal("bug".to_string()),
+        ] {
+            assert_eq!(categories(error), both(Category::Error));
+        }
+    }
+
+    #[test]
+    fn errors_are_classified_through_their_context() {
+        let err = anyhow::Error::new(EngineError::InvalidInput {
+            field: "url",
+            message: "bad".to_string(),
+        })
+        .context("Failed to add");
+        assert_eq!(Category::of_error(&err), Category::InvalidInput);
+
+        let io = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let err = anyhow::Error::new(io).context("Failed to write");
+        assert_eq!(Category::of_error(&err), Category::PermissionDenied);
+
+        assert_eq!(
+            Category::of_error(&anyhow::anyhow!("oops")),
+            Category::Error
+        );
+    }
+
+    #[test]
+    fn failures_agree_or_fall_back_to_error() {
+        assert_eq!(Category::of_failures(&[]), None);
+        assert_eq!(
+            Category::of_failures(&[Category::Network, Category::Network]),
+            Some(Category::Network)
+        );
+        assert_eq!(
+            Category::of_failures(&[Category::Network, Category::Checksum]),
+            Some(Category::Error)
+        );
+    }
+}
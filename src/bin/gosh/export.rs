@@ -0,0 +1,247 @@
+//! Queue export files
+//!
+//! `gosh export` writes each download's source, options and CLI extras as
+//! JSON so `gosh import` can add them again on another machine. Torrents
+//! added from a file carry the `.torrent` itself (hex encoded, as stored by
+//! the engine); magnets carry their link. Downloads are matched against what
+//! the engine already has by URL or info hash, so importing twice adds
+//! nothing new; the same goes for aria2 session imports.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use gosh_dl::torrent::{MagnetUri, Metainfo};
+use gosh_dl::{
+    DownloadEngine, DownloadId, DownloadKind, DownloadOptions, DownloadState, DownloadStatus,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::input::url_parser::ParsedInput;
+use crate::retry;
+use crate::store::DownloadExtras;
+use crate::torrent_file::MetadataReader;
+
+/// Version written by this build; newer files are refused
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportFile {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub downloads: Vec<ExportedDownload>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedDownload {
+    /// ID on the exporting machine, so `after` holds can be remapped
+    pub id: DownloadId,
+    pub name: String,
+    pub source: Source,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info_hash: Option<String>,
+    pub options: DownloadOptions,
+    pub state: DownloadState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_size: Option<u64>,
+    #[serde(default)]
+    pub completed_size: u64,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "DownloadExtras::is_empty")]
+    pub extras: DownloadExtras,
+}
+
+/// Where a download comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Url(String),
+    Magnet(String),
+    /// Hex-encoded `.torrent` file
+    Torrent(String),
+}
+
+impl Source {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Source::Url(_) => "http",
+            Source::Magnet(_) => "magnet",
+            Source::Torrent(_) => "torrent",
+        }
+    }
+}
+
+/// Describe a download for export
+pub async fn export(
+    dl: &DownloadStatus,
+    extras: Option<&DownloadExtras>,
+    reader: Option<&MetadataReader>,
+) -> Result<ExportedDownload> {
+    let meta = &dl.metadata;
+    let mut source = None;
+    if dl.kind != DownloadKind::Http && meta.magnet_uri.is_none() {
+        if let Some(reader) = reader {
+            source = reader
+                .load(dl.id)
+                .await?
+                .map(|data| Source::Torrent(hex::encode(data)));
+        }
+    }
+    let source = match source {
+        Some(source) => source,
+        // Without the stored file a torrent falls back to its info hash
+        None => match retry::source(dl, None)? {
+            ParsedInput::Http(url) => Source::Url(url),
+            ParsedInput::Magnet(uri) => Source::Magnet(uri),
+            ParsedInput::TorrentFile(_) => unreachable!("retry::source gives no torrent files"),
+        },
+    };
+
+    Ok(ExportedDownload {
+        id: dl.id,
+        name: meta.name.clone(),
+        source,
+        info_hash: meta.info_hash.clone(),
+        options: retry::options(dl),
+        state: dl.state.clone(),
+        total_size: dl.progress.total_size,
+        completed_size: dl.progress.completed_size,
+        created_at: dl.created_at,
+        completed_at: dl.completed_at,
+        extras: extras.map(portable).unwrap_or_default(),
+    })
+}
+
+/// Extras worth carrying to another machine: pauses by this machine's
+/// schedule, quota or queue and its retry bookkeeping are left behind
+fn portable(extras: &DownloadExtras) -> DownloadExtras {
+    DownloadExtras {
+        schedule_paused: false,
+        quota_paused: false,
+        queue_held: false,
+        retry_attempts: 0,
+        next_retry: None,
+        ..extras.clone()
+    }
+}
+
+/// What identifies a download across machines: its URL, or its info hash
+pub fn identity(source: &Source, info_hash: Option<&str>) -> Option<String> {
+    match source {
+        Source::Url(url) => Some(url.clone()),
+        _ => info_hash.map(str::to_lowercase).or_else(|| match source {
+            Source::Magnet(uri) => MagnetUri::parse(uri)
+                .ok()
+                .map(|magnet| hex::encode(magnet.info_hash)),
+            _ => None,
+        }),
+    }
+}
+
+/// Identity of something about to be added with `gosh add`
+pub fn input_identity(input: &ParsedInput) -> Option<String> {
+    match input {
+        ParsedInput::Http(url) => Some(url.clone()),
+        ParsedInput::Magnet(uri) => identity(&Source::Magnet(uri.clone()), None),
+        ParsedInput::TorrentFile(path) => std::fs::read(path)
+            .ok()
+            .and_then(|data| Metainfo::parse(&data).ok())
+            .map(|metainfo| hex::encode(metainfo.info_hash)),
+    }
+}
+
+/// Identity of a download the engine already has
+pub fn status_identity(dl: &DownloadStatus) -> Option<String> {
+    let meta = &dl.metadata;
+    match (&meta.url, &meta.info_hash) {
+        (Some(url), _) if dl.kind == DownloadKind::Http => Some(url.clone()),
+        (_, Some(hash)) => Some(hash.to_lowercase()),
+        _ => None,
+    }
+}
+
+/// Downloads the engine already has, by identity
+pub fn known(engine: &DownloadEngine) -> HashMap<String, DownloadId> {
+    engine
+        .list()
+        .iter()
+        .filter_map(|d| status_identity(d).map(|key| (key, d.id)))
+        .collect()
+}
+
+pub fn parse(contents: &str) -> Result<ExportFile> {
+    let file: ExportFile = serde_json::from_str(contents).context("Invalid export file")?;
+    if file.version > EXPORT_VERSION {
+        bail!(
+            "Export file version {} is newer than this gosh supports ({})",
+            file.version,
+            EXPORT_VERSION
+        );
+    }
+    Ok(file)
+}
+
+/// Add an exported download to the engine
+pub async fn add(engine: &DownloadEngine, dl: &ExportedDownload) -> Result<DownloadId> {
+    let options = dl.options.clone();
+    Ok(match &dl.source {
+        Source::Url(url) => engine.add_http(url, options).await?,
+        Source::Magnet(uri) => engine.add_magnet(uri, options).await?,
+        Source::Torrent(data) => {
+            let data = hex::decode(data).context("Invalid torrent data in export file")?;
+            engine.add_torrent(&data, options).await?
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+
+    #[tokio::test]
+    async fn round_trips_and_matches_by_identity() {
+        let mut http = status(DownloadKind::Http, DownloadState::Paused, 100, 40);
+        http.metadata.url = Some("https://example.com/a.iso".to_string());
+        let extras = DownloadExtras {
+            tags: vec!["linux".to_string()],
+            quota_paused: true,
+            retry_attempts: 3,
+            ..Default::default()
+        };
+
+        let exported = export(&http, Some(&extras), None).await.unwrap();
+        assert_eq!(
+            exported.source,
+            Source::Url("https://example.com/a.iso".to_string())
+        );
+        assert_eq!(exported.completed_size, 40);
+        assert_eq!(exported.extras.tags, ["linux"]);
+        assert!(!exported.extras.quota_paused && exported.extras.retry_attempts == 0);
+
+        let file = ExportFile {
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            downloads: vec![exported],
+        };
+        let parsed = parse(&serde_json::to_string(&file).unwrap()).unwrap();
+        let dl = &parsed.downloads[0];
+        assert_eq!(dl.state, DownloadState::Paused);
+        assert_eq!(
+            identity(&dl.source, dl.info_hash.as_deref()),
+            status_identity(&http)
+        );
+
+        let hash = "c12fe1c06bba254a9dc9f519b335aa7c1367a88a";
+        let magnet = Source::Magnet(format!("magnet:?xt=urn:btih:{}", hash.to_uppercase()));
+        assert_eq!(identity(&magnet, None).as_deref(), Some(hash));
+
+        let newer = serde_json::json!({
+            "version": EXPORT_VERSION + 1,
+            "exported_at": Utc::now(),
+            "downloads": [],
+        });
+        assert!(parse(&newer.to_string()).is_err());
+    }
+}
@@ -0,0 +1,501 @@
+//! `--where` filter expressions
+//!
+//! Conditions are `field op value`, joined with `and`, `or` and `not` and
+//! grouped with parentheses, e.g. `kind=torrent and (state=seeding or
+//! size>4G)`. Text values may be quoted. `name` and `url` take `*`
+//! wildcards, as category URL patterns do; without one they match anywhere.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use gosh_dl::{DownloadKind, DownloadPriority, DownloadState, DownloadStatus};
+
+use crate::category::url_matches;
+use crate::store::Store;
+use crate::util::{parse_duration, parse_speed};
+
+const FIELDS: &str = "state, name, url, kind, size, progress, speed, age, priority, tag";
+
+/// A parsed `--where` expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter(Expr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Test(Field, Op, Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    State,
+    Name,
+    Url,
+    Kind,
+    Size,
+    Progress,
+    Speed,
+    Age,
+    Priority,
+    Tag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Text(String),
+    State(StateValue),
+    Kind(KindValue),
+    Priority(DownloadPriority),
+    /// Bytes, bytes/sec, percent or seconds, depending on the field
+    Number(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StateValue {
+    Queued,
+    Connecting,
+    Downloading,
+    Seeding,
+    Paused,
+    Completed,
+    Error,
+    /// Connecting, downloading or seeding
+    Active,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KindValue {
+    Http,
+    /// From a file or a magnet link
+    Torrent,
+    Magnet,
+}
+
+impl std::str::FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!("unexpected '{}'", token);
+        }
+        Ok(Self(expr))
+    }
+}
+
+impl Filter {
+    /// Whether a download matches; `store` supplies tags and categories
+    pub fn matches(&self, dl: &DownloadStatus, store: &Store, now: DateTime<Utc>) -> bool {
+        self.0.eval(dl, store, now)
+    }
+}
+
+impl Expr {
+    fn eval(&self, dl: &DownloadStatus, store: &Store, now: DateTime<Utc>) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(dl, store, now) && b.eval(dl, store, now),
+            Expr::Or(a, b) => a.eval(dl, store, now) || b.eval(dl, store, now),
+            Expr::Not(a) => !a.eval(dl, store, now),
+            Expr::Test(field, op, value) => {
+                let hit = match (field, value) {
+                    (Field::State, Value::State(state)) => state.matches(&dl.state),
+                    (Field::Kind, Value::Kind(kind)) => kind.matches(dl.kind),
+                    (Field::Name, Value::Text(pattern)) => url_matches(pattern, &dl.metadata.name),
+                    (Field::Url, Value::Text(pattern)) => dl
+                        .metadata
+                        .url
+                        .as_deref()
+                        .or(dl.metadata.magnet_uri.as_deref())
+                        .is_some_and(|url| url_matches(pattern, url)),
+                    (Field::Tag, Value::Text(tag)) => store.has_tag(dl.id, tag),
+                    (Field::Priority, Value::Priority(p)) => return op.compare(dl.priority, *p),
+                    (_, Value::Number(n)) => {
+                        return number(*field, dl, now).is_some_and(|v| op.compare(v, *n));
+                    }
+                    _ => false,
+                };
+                // Only = and != reach here
+                hit == (*op == Op::Eq)
+            }
+        }
+    }
+}
+
+/// A numeric field's value, if known
+fn number(field: Field, dl: &DownloadStatus, now: DateTime<Utc>) -> Option<f64> {
+    let progress = &dl.progress;
+    match field {
+        Field::Size => progress.total_size.map(|size| size as f64),
+        Field::Progress => progress
+            .total_size
+            .filter(|total| *total > 0)
+            .map(|total| progress.completed_size as f64 * 100.0 / total as f64),
+        Field::Speed => Some(progress.download_speed as f64),
+        Field::Age => Some((now - dl.created_at).num_seconds().max(0) as f64),
+        _ => None,
+    }
+}
+
+impl Op {
+    fn compare<T: PartialOrd>(self, a: T, b: T) -> bool {
+        match self {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+        }
+    }
+}
+
+impl StateValue {
+    fn parse(s: &str) -> Result<Self> {
+        Ok(match s {
+            "queued" | "waiting" => Self::Queued,
+            "connecting" => Self::Connecting,
+            "downloading" => Self::Downloading,
+            "seeding" => Self::Seeding,
+            "paused" => Self::Paused,
+            "completed" | "complete" => Self::Completed,
+            "error" | "failed" => Self::Error,
+            "active" => Self::Active,
+            _ => bail!(
+                "unknown state '{}'. Use queued, connecting, downloading, seeding, paused, \
+                 completed, error or active",
+                s
+            ),
+        })
+    }
+
+    fn matches(self, state: &DownloadState) -> bool {
+        match self {
+            Self::Queued => matches!(state, DownloadState::Queued),
+            Self::Connecting => matches!(state, DownloadState::Connecting),
+            Self::Downloading => matches!(state, DownloadState::Downloading),
+            Self::Seeding => matches!(state, DownloadState::Seeding),
+            Self::Paused => matches!(state, DownloadState::Paused),
+            Self::Completed => matches!(state, DownloadState::Completed),
+            Self::Error => matches!(state, DownloadState::Error { .. }),
+            Self::Active => state.is_active(),
+        }
+    }
+}
+
+impl KindValue {
+    fn matches(self, kind: DownloadKind) -> bool {
+        match self {
+            Self::Http => kind == DownloadKind::Http,
+            Self::Torrent => kind != DownloadKind::Http,
+            Self::Magnet => kind == DownloadKind::Magnet,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// A quoted value, never a keyword
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(w) | Token::Quoted(w) => write!(f, "{}", w),
+            Token::Op(op) => write!(f, "{}", op.symbol()),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => value.push(ch),
+                        None => bail!("unterminated quote"),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let eq = chars.next_if_eq(&'=').is_some();
+                tokens.push(Token::Op(match (c, eq) {
+                    ('=', _) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    _ => bail!("expected '!='"),
+                }));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || "()=!<>'\"".contains(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if self.tokens.get(self.pos) == Some(&Token::Open) {
+            self.pos += 1;
+            let expr = self.or()?;
+            if self.next() != Some(Token::Close) {
+                bail!("missing ')'");
+            }
+            return Ok(expr);
+        }
+        self.test()
+    }
+
+    fn test(&mut self) -> Result<Expr> {
+        let field = match self.next() {
+            Some(Token::Word(w)) => w.to_lowercase(),
+            Some(token) => bail!("expected a field, found '{}'", token),
+            None => bail!("expected a field. Fields: {}", FIELDS),
+        };
+        let field = match field.as_str() {
+            "state" => Field::State,
+            "name" => Field::Name,
+            "url" => Field::Url,
+            "kind" => Field::Kind,
+            "size" => Field::Size,
+            "progress" => Field::Progress,
+            "speed" => Field::Speed,
+            "age" => Field::Age,
+            "priority" => Field::Priority,
+            "tag" => Field::Tag,
+            other => bail!("unknown field '{}'. Fields: {}", other, FIELDS),
+        };
+        let Some(Token::Op(op)) = self.next() else {
+            bail!("expected an operator after '{:?}'", field);
+        };
+        let raw = match self.next() {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => w,
+            _ => bail!("expected a value after '{}'", op.symbol()),
+        };
+
+        let ordered = matches!(
+            field,
+            Field::Size | Field::Progress | Field::Speed | Field::Age | Field::Priority
+        );
+        if !ordered && !matches!(op, Op::Eq | Op::Ne) {
+            bail!("'{}' only takes = and !=", field.name());
+        }
+        let lower = raw.to_lowercase();
+        let value = match field {
+            Field::State => Value::State(StateValue::parse(&lower)?),
+            Field::Kind => Value::Kind(match lower.as_str() {
+                "http" => KindValue::Http,
+                "torrent" => KindValue::Torrent,
+                "magnet" => KindValue::Magnet,
+                _ => bail!("unknown kind '{}'. Use http, torrent or magnet", raw),
+            }),
+            Field::Name | Field::Url | Field::Tag => Value::Text(raw),
+            Field::Priority => Value::Priority(
+                lower
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("unknown priority '{}'", raw))?,
+            ),
+            Field::Size | Field::Speed => Value::Number(
+                parse_speed(&raw).with_context(|| format!("invalid {} '{}'", field.name(), raw))?
+                    as f64,
+            ),
+            Field::Progress => Value::Number(
+                raw.trim_end_matches('%')
+                    .parse()
+                    .with_context(|| format!("invalid progress '{}'", raw))?,
+            ),
+            Field::Age => Value::Number(
+                parse_duration(&raw).with_context(|| format!("invalid age '{}'", raw))? as f64,
+            ),
+        };
+        Ok(Expr::Test(field, op, value))
+    }
+}
+
+impl Field {
+    fn name(self) -> &'static str {
+        match self {
+            Field::State => "state",
+            Field::Name => "name",
+            Field::Url => "url",
+            Field::Kind => "kind",
+            Field::Size => "size",
+            Field::Progress => "progress",
+            Field::Speed => "speed",
+            Field::Age => "age",
+            Field::Priority => "priority",
+            Field::Tag => "tag",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+    use chrono::TimeDelta;
+
+    fn filter(s: &str) -> Filter {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn evaluates_fields() {
+        let now = Utc::now();
+        let store = Store::default();
+        let mut dl = status(
+            DownloadKind::Magnet,
+            DownloadState::Seeding,
+            4 << 30,
+            1 << 30,
+        );
+        dl.metadata.name = "Ubuntu 24.04.iso".to_string();
+        dl.priority = DownloadPriority::High;
+        dl.created_at = now - TimeDelta::days(3);
+
+        for yes in [
+            "kind=torrent and state=seeding",
+            "state=active",
+            "kind = magnet",
+            "name=ubuntu*.iso",
+            "name='24.04'",
+            "size>=4G and size<5G",
+            "progress=25%",
+            "age>2d and not age>4d",
+            "priority>normal",
+            "state=paused or (kind!=http and priority=high)",
+        ] {
+            assert!(filter(yes).matches(&dl, &store, now), "{}", yes);
+        }
+        for no in [
+            "kind=http",
+            "state=downloading",
+            "name=debian",
+            "size<1G",
+            "speed>0",
+            "tag=linux",
+            "NOT state=active",
+        ] {
+            assert!(!filter(no).matches(&dl, &store, now), "{}", no);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_expressions() {
+        for bad in [
+            "",
+            "colour=red",
+            "state>paused",
+            "state=sleeping",
+            "size>lots",
+            "kind=http and",
+            "(kind=http",
+            "kind=http)",
+            "name='unterminated",
+            "state ! paused",
+        ] {
+            assert!(bad.parse::<Filter>().is_err(), "{}", bad);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::atomic::{AtomicU8, Ordering};
 
+use chrono::{DateTime, Local, Utc};
 use gosh_dl::DownloadState;
 
 use crate::util::truncate_str;
@@ -91,6 +92,16 @@ pub fn format_duration(seconds: u64) -> String {
     }
 }
 
+/// Format a time in local time, with the date only if it is not today
+pub fn format_local_time(at: DateTime<Utc>) -> String {
+    let at = at.with_timezone(&Local);
+    if at.date_naive() == Local::now().date_naive() {
+        at.format("%H:%M").to_string()
+    } else {
+        at.format("%m-%d %H:%M").to_string()
+    }
+}
+
 /// Format a download state as a short label. Shows "Error: {kind}" for errors.
 pub fn format_state(state: &DownloadState) -> String {
     match state {
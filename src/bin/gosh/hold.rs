@@ -0,0 +1,283 @@
+//! Downloads held back until a start time or another download
+//!
+//! The engine starts a download as soon as it is added, so a held download is
+//! paused right after it is added. The supervisor resumes it once its
+//! `--start-at` time has passed and its `--after` download has completed.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use gosh_dl::{DownloadEngine, DownloadId, DownloadState, DownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::format::format_local_time;
+use crate::store::{DownloadExtras, Store};
+
+/// What to do with a download whose `--after` download failed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AfterFailure {
+    /// Mark it failed too and keep it paused
+    #[default]
+    Fail,
+    /// Start it anyway
+    Start,
+    /// Cancel it
+    Cancel,
+}
+
+impl AfterFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fail => "fail",
+            Self::Start => "start",
+            Self::Cancel => "cancel",
+        }
+    }
+}
+
+/// Why a download is being held back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hold {
+    /// Waiting for its start time
+    Scheduled(DateTime<Utc>),
+    /// Waiting for another download to complete
+    Blocked(DownloadId),
+    /// The download it was waiting for failed
+    DependencyFailed(DownloadId),
+}
+
+impl Hold {
+    /// The hold recorded for a download, if any
+    pub fn of(extras: &DownloadExtras, now: DateTime<Utc>) -> Option<Self> {
+        match (extras.start_at, extras.after) {
+            (_, Some(after)) if extras.dependency_failed => Some(Self::DependencyFailed(after)),
+            (Some(at), _) if at > now => Some(Self::Scheduled(at)),
+            (_, Some(after)) => Some(Self::Blocked(after)),
+            _ => None,
+        }
+    }
+
+    /// Short state label for lists
+    pub fn label(&self) -> String {
+        match self {
+            Self::Scheduled(at) => format!("Scheduled {}", format_local_time(*at)),
+            Self::Blocked(_) => "Blocked".to_string(),
+            Self::DependencyFailed(_) => "Dep. failed".to_string(),
+        }
+    }
+
+    /// Longer description for detailed views
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Scheduled(at) => format!(
+                "Scheduled to start at {}",
+                at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            ),
+            Self::Blocked(after) => format!("Waiting for {} to complete", after.to_gid()),
+            Self::DependencyFailed(after) => format!("Not started: {} failed", after.to_gid()),
+        }
+    }
+}
+
+/// State label for a download, accounting for holds
+pub fn state_label(dl: &DownloadStatus, store: &Store) -> String {
+    match held(dl, store) {
+        Some(hold) => hold.label(),
+        // Waiting for its turn in the queue
+        None if dl.state == DownloadState::Paused
+            && store.get(dl.id).is_some_and(|e| e.queue_held) =>
+        {
+            crate::format::format_state(&DownloadState::Queued)
+        }
+        None => crate::format::format_state(&dl.state),
+    }
+}
+
+/// The hold on a download, while it is actually paused by it
+pub fn held(dl: &DownloadStatus, store: &Store) -> Option<Hold> {
+    if dl.state.is_finished() {
+        return None;
+    }
+    store
+        .get(dl.id)
+        .and_then(|extras| Hold::of(extras, Utc::now()))
+}
+
+/// Outcome of checking a held download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Wait,
+    Release,
+    DependencyFailed,
+}
+
+/// Decide whether a held download may start
+pub fn evaluate(
+    extras: &DownloadExtras,
+    downloads: &[DownloadStatus],
+    store: &Store,
+    now: DateTime<Utc>,
+) -> Option<Verdict> {
+    if extras.dependency_failed || (extras.start_at.is_none() && extras.after.is_none()) {
+        return None;
+    }
+
+    if let Some(after) = extras.after {
+        // A dependency that was removed can no longer complete
+        let failed = match downloads.iter().find(|d| d.id == after) {
+            None => true,
+            Some(dep) => {
+                matches!(dep.state, DownloadState::Error { .. })
+                    || store.get(after).is_some_and(|e| e.dependency_failed)
+            }
+        };
+        if failed {
+            return Some(Verdict::DependencyFailed);
+        }
+        let done = downloads
+            .iter()
+            .any(|d| d.id == after && d.state == DownloadState::Completed);
+        if !done {
+            return Some(Verdict::Wait);
+        }
+    }
+
+    match extras.start_at {
+        Some(at) if at > now => Some(Verdict::Wait),
+        _ => Some(Verdict::Release),
+    }
+}
+
+/// Parse `--start-at`: a time of day (next occurrence) or a local date and time
+pub fn parse_start_at(s: &str, now: DateTime<Local>) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+
+    for format in ["%H:%M", "%H:%M:%S"] {
+        if let Ok(time) = NaiveTime::parse_from_str(s, format) {
+            let mut date = now.date_naive();
+            loop {
+                if let Some(at) = local(date.and_time(time)) {
+                    if at > now {
+                        return Ok(at.with_timezone(&Utc));
+                    }
+                }
+                date = date.succ_opt().expect("date in range");
+            }
+        }
+    }
+
+    if let Ok(at) = DateTime::parse_from_rfc3339(s) {
+        return future(at.with_timezone(&Utc), s, now);
+    }
+    for format in ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, format) {
+            let Some(at) = local(naive) else {
+                bail!("Start time '{}' does not exist in the local time zone", s);
+            };
+            return future(at.with_timezone(&Utc), s, now);
+        }
+    }
+
+    bail!("Invalid start time '{}'. Use HH:MM or YYYY-MM-DDTHH:MM", s)
+}
+
+fn local(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+fn future(at: DateTime<Utc>, s: &str, now: DateTime<Local>) -> Result<DateTime<Utc>> {
+    if at <= now {
+        bail!("Start time '{}' is in the past", s);
+    }
+    Ok(at)
+}
+
+/// Pause a just-added download as soon as the engine allows it.
+///
+/// Only started downloads can be paused. One still waiting for a slot after a
+/// few seconds is left queued; the supervisor pauses it when it starts.
+pub async fn pause_new(engine: &DownloadEngine, id: DownloadId) -> Result<()> {
+    for _ in 0..150 {
+        match engine.status(id).map(|s| s.state) {
+            Some(DownloadState::Queued) => tokio::time::sleep(Duration::from_millis(20)).await,
+            Some(state) if state.is_active() => {
+                engine.pause(id).await?;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+    use chrono::{Datelike, Timelike};
+    use gosh_dl::DownloadKind;
+
+    #[test]
+    fn start_at_time_of_day_is_next_occurrence() {
+        let now = Local.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+
+        let later = parse_start_at("13:30", now).unwrap().with_timezone(&Local);
+        assert_eq!((later.day(), later.hour(), later.minute()), (18, 13, 30));
+
+        let tomorrow = parse_start_at("02:00", now).unwrap().with_timezone(&Local);
+        assert_eq!((tomorrow.day(), tomorrow.hour()), (19, 2));
+
+        let exact = parse_start_at("2026-10-20T01:00", now)
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!((exact.day(), exact.hour()), (20, 1));
+
+        assert!(parse_start_at("2026-10-01T01:00", now).is_err());
+        assert!(parse_start_at("tonight", now).is_err());
+    }
+
+    #[test]
+    fn dependency_outcomes() {
+        let now = Utc::now();
+        let mut dep = status(DownloadKind::Http, DownloadState::Downloading, 100, 10);
+        let extras = DownloadExtras {
+            after: Some(dep.id),
+            ..Default::default()
+        };
+        let store = Store::default();
+
+        let verdict =
+            |dep: &DownloadStatus| evaluate(&extras, std::slice::from_ref(dep), &store, now);
+        assert_eq!(verdict(&dep), Some(Verdict::Wait));
+
+        dep.state = DownloadState::Completed;
+        assert_eq!(verdict(&dep), Some(Verdict::Release));
+
+        dep.state = DownloadState::Error {
+            kind: "network".to_string(),
+            message: "reset".to_string(),
+            retryable: true,
+        };
+        assert_eq!(verdict(&dep), Some(Verdict::DependencyFailed));
+        assert_eq!(
+            evaluate(&extras, &[], &store, now),
+            Some(Verdict::DependencyFailed)
+        );
+
+        // Completed dependency, but the start time is still ahead
+        dep.state = DownloadState::Completed;
+        let scheduled = DownloadExtras {
+            start_at: Some(now + chrono::Duration::hours(1)),
+            ..extras.clone()
+        };
+        assert_eq!(
+            evaluate(&scheduled, &[dep], &store, now),
+            Some(Verdict::Wait)
+        );
+        assert_eq!(
+            Hold::of(&scheduled, now),
+            Some(Hold::Scheduled(scheduled.start_at.unwrap()))
+        );
+    }
+}
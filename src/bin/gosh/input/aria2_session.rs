@@ -0,0 +1,248 @@
+//! aria2 session files
+//!
+//! aria2c saves unfinished downloads with `--save-session`: one line of
+//! tab-separated URIs per download (mirrors of the same file, or a magnet or
+//! `.torrent` path), followed by indented `name=value` option lines.
+
+use anyhow::{bail, Context, Result};
+use gosh_dl::DownloadOptions;
+use std::path::PathBuf;
+
+use super::url_parser::{parse_input, ParsedInput};
+use crate::store::DownloadExtras;
+use crate::util::{parse_checksum, parse_speed, sanitize_filename, validate_seed_ratio};
+
+/// One download as written in the session file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionEntry {
+    /// Line of the URIs, for error messages
+    pub line: usize,
+    pub uris: Vec<String>,
+    pub options: Vec<(String, String)>,
+}
+
+/// A session entry translated to what `gosh add` would do
+#[derive(Debug)]
+pub struct Aria2Download {
+    pub input: ParsedInput,
+    pub options: DownloadOptions,
+    pub extras: DownloadExtras,
+    pub paused: bool,
+    /// aria2 options with no counterpart here
+    pub ignored: Vec<String>,
+}
+
+pub fn parse_session(contents: &str) -> Result<Vec<SessionEntry>> {
+    let mut entries: Vec<SessionEntry> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line_no = i + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with([' ', '\t']) {
+            let Some(entry) = entries.last_mut() else {
+                bail!("Line {}: option before any URI", line_no);
+            };
+            let (name, value) = line
+                .trim()
+                .split_once('=')
+                .with_context(|| format!("Line {}: expected name=value", line_no))?;
+            entry.options.push((name.to_string(), value.to_string()));
+        } else {
+            entries.push(SessionEntry {
+                line: line_no,
+                uris: line
+                    .split('\t')
+                    .map(str::trim)
+                    .filter(|uri| !uri.is_empty())
+                    .map(String::from)
+                    .collect(),
+                options: Vec::new(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+impl SessionEntry {
+    pub fn gid(&self) -> Option<&str> {
+        self.option("gid")
+    }
+
+    /// Metalink downloads, which gosh cannot add; the import skips them
+    pub fn is_metalink(&self) -> bool {
+        self.uris.first().is_some_and(|uri| {
+            let uri = uri.to_lowercase();
+            uri.ends_with(".metalink") || uri.ends_with(".meta4")
+        })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Translate the entry's URIs and options
+    pub fn to_download(&self) -> Result<Aria2Download> {
+        let first = self.uris.first().context("No URI")?;
+        if self.is_metalink() {
+            bail!("Metalink downloads are not supported: {}", first);
+        }
+        let input = parse_input(first)?;
+        let is_http = matches!(input, ParsedInput::Http(_));
+
+        let mut options = DownloadOptions::default();
+        if is_http {
+            options.mirrors = self.uris[1..]
+                .iter()
+                .filter(|uri| matches!(parse_input(uri), Ok(ParsedInput::Http(_))))
+                .cloned()
+                .collect();
+        }
+        let mut extras = DownloadExtras::default();
+        let mut paused = false;
+        let mut ignored = Vec::new();
+
+        for (name, value) in &self.options {
+            let invalid = || format!("Invalid aria2 option {}={}", name, value);
+            match name.as_str() {
+                "gid" => {}
+                "dir" => options.save_dir = Some(PathBuf::from(value)),
+                "out" if is_http => options.filename = Some(sanitize_filename(value)?),
+                "pause" => paused = value == "true",
+                "select-file" if !is_http => {
+                    options.selected_files = Some(parse_select_file(value).with_context(invalid)?)
+                }
+                "header" => {
+                    let (name, value) = value.split_once(':').with_context(invalid)?;
+                    options
+                        .headers
+                        .push((name.trim().to_string(), value.trim().to_string()));
+                }
+                "user-agent" => options.user_agent = Some(value.clone()),
+                "referer" => options.referer = Some(value.clone()),
+                "checksum" if is_http => {
+                    // aria2 writes e.g. sha-256=HASH
+                    let (algo, hash) = value.split_once('=').with_context(invalid)?;
+                    let algo = algo.replace('-', "").to_lowercase();
+                    options.checksum =
+                        Some(parse_checksum(&format!("{}:{}", algo, hash)).with_context(invalid)?);
+                }
+                "max-download-limit" => {
+                    let limit = parse_speed(value).with_context(invalid)?;
+                    options.max_download_speed = (limit > 0).then_some(limit);
+                }
+                "max-upload-limit" => {
+                    let limit = parse_speed(value).with_context(invalid)?;
+                    options.max_upload_speed = (limit > 0).then_some(limit);
+                }
+                "split" => {
+                    let split: usize = value.parse().with_context(invalid)?;
+                    options.max_connections = (split > 0).then_some(split);
+                }
+                "seed-ratio" if !is_http => {
+                    let ratio = value.parse().with_context(invalid)?;
+                    extras.seed_ratio = validate_seed_ratio(Some(ratio))?;
+                }
+                "seed-time" if !is_http => {
+                    // In minutes, possibly fractional
+                    let minutes: f64 = value.parse().with_context(invalid)?;
+                    extras.seed_time = Some((minutes.max(0.0) * 60.0).round() as u64);
+                }
+                _ => ignored.push(name.clone()),
+            }
+        }
+
+        Ok(Aria2Download {
+            input,
+            options,
+            extras,
+            paused,
+            ignored,
+        })
+    }
+}
+
+/// aria2's 1-based file list with ranges ("1,3-5"), as 0-based indices
+fn parse_select_file(value: &str) -> Result<Vec<usize>> {
+    let mut indices = Vec::new();
+    for token in value.split(',') {
+        let (start, end) = match token.trim().split_once('-') {
+            Some((start, end)) => (start.trim().parse()?, end.trim().parse()?),
+            None => {
+                let index = token.trim().parse()?;
+                (index, index)
+            }
+        };
+        if start == 0 || end < start {
+            bail!("Invalid file range '{}'", token);
+        }
+        indices.extend((start..=end).map(|i: usize| i - 1));
+    }
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "\
+# saved by aria2c
+https://example.com/a.iso\thttps://mirror.example.org/a.iso
+ gid=2089b05ecca3d829
+ dir=/data/iso
+ out=a.iso
+ pause=true
+ header=Authorization: Bearer abc
+ checksum=sha-256=deadbeef
+ max-download-limit=1M
+ continue=true
+
+magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a
+ gid=d5e6f7a8b9c0d1e2
+ select-file=1,3-4
+ seed-time=90
+";
+
+    #[test]
+    fn parses_entries_and_options() {
+        let entries = parse_session(SESSION).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].line, 2);
+        assert_eq!(entries[0].gid(), Some("2089b05ecca3d829"));
+
+        let http = entries[0].to_download().unwrap();
+        assert!(
+            matches!(http.input, ParsedInput::Http(ref url) if url == "https://example.com/a.iso")
+        );
+        assert_eq!(http.options.mirrors, ["https://mirror.example.org/a.iso"]);
+        assert_eq!(http.options.save_dir, Some(PathBuf::from("/data/iso")));
+        assert_eq!(http.options.filename.as_deref(), Some("a.iso"));
+        assert_eq!(
+            http.options.headers,
+            [("Authorization".to_string(), "Bearer abc".to_string())]
+        );
+        assert!(http.options.checksum.is_some());
+        assert_eq!(http.options.max_download_speed, Some(1024 * 1024));
+        assert!(http.paused);
+        assert_eq!(http.ignored, ["continue"]);
+
+        let magnet = entries[1].to_download().unwrap();
+        assert!(matches!(magnet.input, ParsedInput::Magnet(_)));
+        assert_eq!(magnet.options.selected_files, Some(vec![0, 2, 3]));
+        assert_eq!(magnet.extras.seed_time, Some(90 * 60));
+        assert!(!magnet.paused);
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_import() {
+        assert!(parse_session(" gid=1\n").is_err());
+        let entries = parse_session("/tmp/files.meta4\nhttps://example.com/a.METALINK\n").unwrap();
+        assert!(entries[0].is_metalink() && entries[1].is_metalink());
+        assert!(entries[0].to_download().is_err());
+        assert!(parse_select_file("0").is_err());
+        assert!(parse_select_file("3-1").is_err());
+    }
+}
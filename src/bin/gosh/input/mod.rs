@@ -1 +1,2 @@
+pub mod aria2_session;
 pub mod url_parser;
@@ -1,6 +1,8 @@
 use anyhow::{bail, Result};
 use std::path::PathBuf;
 
+use crate::exit_code::{Category, CategoryError};
+
 /// Parsed input type
 #[derive(Debug)]
 pub enum ParsedInput {
@@ -62,7 +64,7 @@ pub fn parse_input(input: &str) -> Result<ParsedInput> {
     let input = input.trim();
 
     if input.is_empty() {
-        bail!("Empty input");
+        bail!(invalid("Empty input"));
     }
 
     // Check for magnet links
@@ -81,26 +83,33 @@ pub fn parse_input(input: &str) -> Result<ParsedInput> {
         if input.ends_with(".torrent") || is_torrent_file(&path) {
             return Ok(ParsedInput::TorrentFile(path));
         }
-        bail!(
+        bail!(invalid(format!(
             "Existing file is not a torrent file: {}. Use 'gosh add -i <file>' to read a URL list.",
             path.display()
-        );
+        )));
     }
 
     // If it looks like a path but doesn't exist
     if input.ends_with(".torrent") {
-        bail!("Torrent file not found: {}", input);
+        bail!(CategoryError::new(
+            Category::NotFound,
+            format!("Torrent file not found: {}", input)
+        ));
     }
 
     if looks_like_implicit_url(input) {
         return Ok(ParsedInput::Http(format!("https://{}", input)));
     }
 
-    bail!(
+    bail!(invalid(format!(
         "Cannot determine input type for: {}. \
          Use http(s)://... for URLs, magnet:... for magnet links, or a path to a .torrent file.",
         input
-    )
+    )))
+}
+
+fn invalid(message: impl Into<String>) -> CategoryError {
+    CategoryError::new(Category::InvalidInput, message)
 }
 
 fn looks_like_implicit_url(input: &str) -> bool {
@@ -0,0 +1,226 @@
+//! Logging to stderr and to `general.log_file`
+//!
+//! Stderr gets the level `-v`/`-q` sets; the log file gets
+//! `general.log_level`, which may carry per-module filters. The TUI logs
+//! only to the file, as anything on stderr would draw over the screen.
+//! `RUST_LOG` overrides both filters.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+use crate::config::{GeneralConfig, LogFormat};
+use crate::util::parse_speed;
+
+/// When the log file is rotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Never,
+    /// On the first event of a new local day
+    Daily,
+    /// Before the file would grow past this many bytes
+    Size(u64),
+}
+
+impl std::str::FromStr for Rotation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "never" => Ok(Self::Never),
+            "daily" => Ok(Self::Daily),
+            size => {
+                let size = size.strip_suffix('b').unwrap_or(size);
+                match parse_speed(size) {
+                    Ok(bytes) if bytes > 0 => Ok(Self::Size(bytes)),
+                    _ => anyhow::bail!(
+                        "Invalid log rotation '{}'. Use daily, never or a size such as 10M",
+                        s
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Set up logging; `stderr_level` is the default filter for stderr, which
+/// is left out when `stderr` is false
+pub fn init(stderr_level: &str, stderr: bool, config: &GeneralConfig) -> Result<()> {
+    let filter = |default: &str| {
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default))
+    };
+
+    let file_layer = match config.log_file {
+        Some(ref path) => {
+            let rotation = config.log_rotate.parse()?;
+            let file = RollingFile::open(path, rotation, config.log_keep)
+                .with_context(|| format!("Failed to open log file {}", path.display()))?;
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || file.clone());
+            let filter = filter(&config.log_level);
+            Some(match config.log_format {
+                LogFormat::Text => layer.with_filter(filter).boxed(),
+                LogFormat::Json => layer.json().with_filter(filter).boxed(),
+            })
+        }
+        None => None,
+    };
+    let stderr_layer = stderr.then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_target(false)
+            .with_writer(io::stderr)
+            .with_filter(filter(stderr_level))
+    });
+
+    tracing_subscriber::registry()
+        .with(file_layer as Option<Box<dyn Layer<Registry> + Send + Sync>>)
+        .with(stderr_layer)
+        .init();
+    Ok(())
+}
+
+/// A log file that moves itself aside to `<name>.1` when rotated, shifting
+/// older ones up and deleting those past `keep`
+#[derive(Clone)]
+pub struct RollingFile {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// Local day of the last write
+    day: NaiveDate,
+    rotation: Rotation,
+    keep: usize,
+}
+
+impl RollingFile {
+    pub fn open(path: &Path, rotation: Rotation, keep: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = append(path)?;
+        let metadata = file.metadata()?;
+        // A file last written yesterday rotates on the first event today
+        let day = metadata
+            .modified()
+            .map(|t| DateTime::<Local>::from(t).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Inner {
+                path: path.to_path_buf(),
+                file,
+                size: metadata.len(),
+                day,
+                rotation,
+                keep,
+            })),
+        })
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl Inner {
+    fn due(&self, today: NaiveDate, len: usize) -> bool {
+        match self.rotation {
+            Rotation::Never => false,
+            Rotation::Daily => today != self.day,
+            Rotation::Size(max) => self.size > 0 && self.size + len as u64 > max,
+        }
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let _ = fs::rename(self.rotated(n), self.rotated(n + 1));
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+        }
+        self.file = append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let today = Local::now().date_naive();
+        if inner.due(today, buf.len()) {
+            inner.rotate()?;
+        }
+        inner.day = today;
+        let n = inner.file.write(buf)?;
+        inner.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rotation() {
+        assert_eq!("daily".parse::<Rotation>().unwrap(), Rotation::Daily);
+        assert_eq!("Never".parse::<Rotation>().unwrap(), Rotation::Never);
+        assert_eq!(
+            "10M".parse::<Rotation>().unwrap(),
+            Rotation::Size(10 * 1024 * 1024)
+        );
+        assert_eq!(
+            "512kb".parse::<Rotation>().unwrap(),
+            Rotation::Size(512 * 1024)
+        );
+        assert!("0".parse::<Rotation>().is_err());
+        assert!("weekly".parse::<Rotation>().is_err());
+    }
+
+    #[test]
+    fn rotates_by_size_and_day_keeping_the_newest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("logs").join("gosh.log");
+        let mut file = RollingFile::open(&path, Rotation::Size(10), 2).unwrap();
+        for line in ["one\n", "two\n", "three\n", "four\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        let read = |name: &str| fs::read_to_string(dir.path().join("logs").join(name)).unwrap();
+        assert_eq!(read("gosh.log"), "four\n");
+        assert_eq!(read("gosh.log.1"), "three\n");
+        assert_eq!(read("gosh.log.2"), "one\ntwo\n");
+        assert!(!dir.path().join("logs").join("gosh.log.3").exists());
+
+        let mut file = RollingFile::open(&path, Rotation::Daily, 2).unwrap();
+        file.inner.lock().unwrap().day = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        file.write_all(b"today\n").unwrap();
+        assert_eq!(read("gosh.log"), "today\n");
+        assert_eq!(read("gosh.log.1"), "four\n");
+        assert_eq!(read("gosh.log.2"), "three\n");
+    }
+}
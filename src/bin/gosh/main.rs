@@ -1,15 +1,30 @@
 use anyhow::Result;
 use clap::{CommandFactory, Parser};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 mod app;
+mod category;
 mod cli;
 mod commands;
 mod config;
 mod direct;
+mod exit_code;
+mod export;
+mod filter;
 mod format;
+mod hold;
 mod input;
+mod logging;
 mod output;
+mod progress;
+mod purge;
+mod queue;
+mod quota;
+mod report;
+mod retry;
+mod schedule;
+mod store;
+mod supervisor;
+mod torrent_file;
 #[cfg(feature = "tui")]
 mod tui;
 mod util;
@@ -22,7 +37,7 @@ async fn main() {
         Ok(code) => code,
         Err(e) => {
             format::print_error(&format!("{e:#}"));
-            1
+            exit_code::Category::of_error(&e).code()
         }
     };
     std::process::exit(code);
@@ -49,12 +64,21 @@ async fn run() -> Result<i32> {
         );
         return Ok(0);
     }
-
-    // Setup logging based on verbosity
-    setup_logging(cli.verbose, cli.quiet)?;
+    if let Some(Commands::Help(ref args)) = cli.command {
+        print_help(&args.topic)?;
+        return Ok(0);
+    }
+    if cli.exit_code_map {
+        exit_code::print_table();
+        return Ok(0);
+    }
 
     let config = load_runtime_config(&cli)?;
 
+    // Setup logging based on verbosity and the config's log file
+    let tui = cfg!(feature = "tui") && cli.command.is_none() && cli.urls.is_empty();
+    setup_logging(cli.verbose, cli.quiet, tui, &config.general)?;
+
     if cli.insecure {
         format::print_warning("TLS certificate verification disabled");
     }
@@ -62,7 +86,12 @@ async fn run() -> Result<i32> {
     // Route to appropriate handler
     if let Some(cmd) = cli.command {
         // Subcommand provided - run it
-        run_command(cmd, config, cli.output, cli.config.clone()).await?;
+        let output = output::record::Output {
+            format: cli.output,
+            template: cli.format.clone(),
+        };
+        check_output(&cmd, &output)?;
+        run_command(cmd, config, &output, cli.config.clone()).await?;
         Ok(0)
     } else if !cli.urls.is_empty() {
         // URLs provided without subcommand - direct download mode
@@ -80,6 +109,13 @@ async fn run() -> Result<i32> {
             sequential: cli.sequential,
             select_files: cli.select_files,
             seed_ratio: cli.seed_ratio,
+            seed_time: cli.seed_time,
+            seed_idle: cli.seed_idle,
+            save_torrent: cli.save_torrent,
+            progress: cli.progress,
+            progress_fd: cli.progress_fd,
+            progress_interval: cli.progress_interval,
+            report: cli.report,
         };
         direct::execute(opts, config).await
     } else {
@@ -97,6 +133,27 @@ async fn run() -> Result<i32> {
     }
 }
 
+/// `gosh help [COMMAND]...`, or a topic such as `exit-codes`
+fn print_help(topic: &[String]) -> Result<()> {
+    if topic == ["exit-codes"] {
+        exit_code::print_table();
+        return Ok(());
+    }
+    let mut cmd = Cli::command();
+    cmd.build();
+    let mut current = &mut cmd;
+    for name in topic {
+        current = current.find_subcommand_mut(name).ok_or_else(|| {
+            exit_code::CategoryError::new(
+                exit_code::Category::Usage,
+                format!("unknown help topic '{}'", name),
+            )
+        })?;
+    }
+    current.print_long_help()?;
+    Ok(())
+}
+
 fn load_runtime_config(cli: &Cli) -> Result<config::CliConfig> {
     let mut config = config::CliConfig::load(cli.config.as_deref())?;
     config.apply_env_overrides();
@@ -129,7 +186,12 @@ fn apply_cli_overrides(config: &mut config::CliConfig, cli: &Cli) {
     }
 }
 
-fn setup_logging(verbose: u8, quiet: bool) -> Result<()> {
+fn setup_logging(
+    verbose: u8,
+    quiet: bool,
+    tui: bool,
+    general: &config::GeneralConfig,
+) -> Result<()> {
     let level = if quiet {
         "error"
     } else {
@@ -141,39 +203,75 @@ fn setup_logging(verbose: u8, quiet: bool) -> Result<()> {
         }
     };
 
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    // The TUI owns the terminal, so it only logs to the log file
+    logging::init(level, !tui, general)
+}
 
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(tracing_subscriber::fmt::layer().with_target(false))
-        .init();
+/// Reject `--format` fields and `--output` formats a command cannot print,
+/// before anything starts
+fn check_output(cmd: &Commands, output: &output::record::Output) -> Result<()> {
+    use commands::{add::AddResult, info::TorrentInfo, stats::GlobalStats};
+    use output::record::{DownloadRecord, Record};
 
-    Ok(())
+    let fields = match cmd {
+        Commands::Add(_) => Some(AddResult::FIELDS),
+        Commands::List(_) | Commands::Top(_) | Commands::Status(_) => Some(DownloadRecord::FIELDS),
+        Commands::Stats => Some(GlobalStats::FIELDS),
+        Commands::Info(_) | Commands::FetchMetadata(_) => Some(TorrentInfo::FIELDS),
+        _ => None,
+    };
+    match (fields, &output.template) {
+        (Some(fields), Some(template)) => template.check(fields),
+        (Some(_), None) => Ok(()),
+        (None, Some(_)) => anyhow::bail!(exit_code::CategoryError::new(
+            exit_code::Category::Usage,
+            "--format is not supported by this command"
+        )),
+        (None, None) => match output.format {
+            cli::OutputFormat::Csv | cli::OutputFormat::Tsv => anyhow::bail!(output.unsupported()),
+            _ => Ok(()),
+        },
+    }
 }
 
 async fn run_command(
     cmd: Commands,
     config: config::CliConfig,
-    output_format: cli::OutputFormat,
+    output: &output::record::Output,
     config_path: Option<std::path::PathBuf>,
 ) -> Result<()> {
     // Initialize the application (engine)
     let app = app::App::new(config).await?;
 
     match cmd {
-        Commands::Add(args) => commands::add::execute(*args, &app, output_format).await,
-        Commands::List(args) => commands::list::execute(args, &app, output_format).await,
-        Commands::Status(args) => commands::status::execute(args, &app, output_format).await,
+        Commands::Add(args) => commands::add::execute(*args, &app, output).await,
+        Commands::List(args) => commands::list::execute(args, &app, output).await,
+        Commands::Top(mut args) => {
+            args.watch = true;
+            commands::list::execute(args, &app, output).await
+        }
+        Commands::Status(args) => commands::status::execute(args, &app, output).await,
         Commands::Pause(args) => commands::pause::execute(args, &app).await,
         Commands::Resume(args) => commands::resume::execute(args, &app).await,
+        Commands::Retry(args) => commands::retry::execute(args, &app).await,
         Commands::Cancel(args) => commands::cancel::execute(args, &app).await,
+        Commands::Purge(args) => commands::purge::execute(args, &app).await,
+        Commands::Export(args) => commands::export::execute(args, &app, output).await,
+        Commands::Import(args) => commands::import::execute(args, &app).await,
         Commands::Priority(args) => commands::priority::execute(args, &app).await,
-        Commands::Stats => commands::stats::execute(&app, output_format).await,
-        Commands::Info(args) => commands::info::execute(args, output_format).await,
+        Commands::Move(args) => commands::reorder::execute(args, &app).await,
+        Commands::Stats => commands::stats::execute(&app, output).await,
+        Commands::Info(args) => commands::info::execute(args, output).await,
+        Commands::Seed(args) => commands::seed::execute(args, &app, output).await,
+        Commands::FetchMetadata(args) => {
+            commands::fetch_metadata::execute(args, &app, output).await
+        }
+        Commands::Schedule(args) => commands::schedule::execute(args, &app.config, output).await,
+        Commands::Quota => commands::quota::execute(&app.config, output).await,
         Commands::Config(args) => {
             commands::config::execute(args, &app.config, config_path.as_deref()).await
         }
-        Commands::Completions(_) => Ok(()), // handled before engine init
+        Commands::Completions(_) | Commands::Help(_) => Ok(()), // handled before engine init
     }
 }
 
@@ -0,0 +1,469 @@
+//! Columns of the `gosh list` table
+//!
+//! `--columns` picks and orders them, `--sort` orders the rows by one. `name`
+//! and `dir` share whatever the terminal width leaves after the fixed-width
+//! columns; when stdout is not a terminal they are printed in full.
+
+use anyhow::{bail, Result};
+use gosh_dl::{DownloadId, DownloadKind, DownloadStatus};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::format::{format_duration, format_local_time, format_size, format_speed};
+use crate::hold::state_label;
+use crate::store::Store;
+use crate::util::truncate_str;
+
+/// Columns shown when neither `--columns` nor `[list] default_columns` is set
+pub const DEFAULT_COLUMNS: [Column; 6] = [
+    Column::Id,
+    Column::Name,
+    Column::Progress,
+    Column::Down,
+    Column::Eta,
+    Column::State,
+];
+
+const NAMES: &str =
+    "id, name, size, progress, down, up, eta, state, priority, peers, ratio, created, dir";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Id,
+    Name,
+    Size,
+    Progress,
+    Down,
+    Up,
+    Eta,
+    State,
+    Priority,
+    Peers,
+    Ratio,
+    Created,
+    Dir,
+}
+
+impl std::str::FromStr for Column {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "id" | "gid" => Column::Id,
+            "name" => Column::Name,
+            "size" => Column::Size,
+            "progress" => Column::Progress,
+            "down" | "speed" => Column::Down,
+            "up" => Column::Up,
+            "eta" => Column::Eta,
+            "state" => Column::State,
+            "priority" => Column::Priority,
+            "peers" => Column::Peers,
+            "ratio" => Column::Ratio,
+            "created" => Column::Created,
+            "dir" => Column::Dir,
+            _ => bail!("unknown column '{}'. Columns: {}", s, NAMES),
+        })
+    }
+}
+
+impl Column {
+    fn header(self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::Name => "Name",
+            Column::Size => "Size",
+            Column::Progress => "Progress",
+            Column::Down => "Down",
+            Column::Up => "Up",
+            Column::Eta => "ETA",
+            Column::State => "State",
+            Column::Priority => "Priority",
+            Column::Peers => "Peers",
+            Column::Ratio => "Ratio",
+            Column::Created => "Created",
+            Column::Dir => "Dir",
+        }
+    }
+
+    /// Fixed width, or None for the columns that share the rest
+    fn width(self) -> Option<usize> {
+        match self {
+            Column::Id => Some(16),
+            Column::Name | Column::Dir => None,
+            Column::Size => Some(10),
+            Column::Progress => Some(8),
+            Column::Down | Column::Up => Some(12),
+            Column::Eta => Some(9),
+            Column::State => Some(17),
+            Column::Priority => Some(8),
+            Column::Peers => Some(5),
+            Column::Ratio => Some(5),
+            Column::Created => Some(11),
+        }
+    }
+
+    fn right_aligned(self) -> bool {
+        matches!(
+            self,
+            Column::Size
+                | Column::Progress
+                | Column::Down
+                | Column::Up
+                | Column::Eta
+                | Column::Peers
+                | Column::Ratio
+        )
+    }
+
+    /// The cell's text, untruncated
+    pub fn cell(self, dl: &DownloadStatus, row: &RowContext) -> String {
+        let progress = &dl.progress;
+        match self {
+            Column::Id => dl.id.to_gid(),
+            Column::Name => dl.metadata.name.clone(),
+            Column::Size => progress
+                .total_size
+                .map(format_size)
+                .unwrap_or_else(|| "--".to_string()),
+            Column::Progress => format!("{:.1}%", progress.percentage()),
+            Column::Down => format!("{}/s", format_speed(progress.download_speed)),
+            Column::Up => format!("{}/s", format_speed(progress.upload_speed)),
+            Column::Eta => progress
+                .eta_seconds
+                .map(format_duration)
+                .unwrap_or_else(|| "--".to_string()),
+            Column::State => state_label(dl, row.store),
+            Column::Priority => dl.priority.to_string(),
+            Column::Peers if dl.kind == DownloadKind::Http => "--".to_string(),
+            Column::Peers => progress.peers.to_string(),
+            Column::Ratio => row
+                .ratio(dl)
+                .map(|ratio| format!("{:.2}", ratio))
+                .unwrap_or_else(|| "--".to_string()),
+            Column::Created => format_local_time(dl.created_at),
+            Column::Dir => dl.metadata.save_dir.display().to_string(),
+        }
+    }
+
+    /// Sort key; unknown values sort last either way
+    fn key(self, dl: &DownloadStatus, row: &RowContext) -> SortValue {
+        let progress = &dl.progress;
+        let number = |n: Option<f64>| SortValue::Number(n);
+        match self {
+            Column::Id | Column::Name | Column::State | Column::Dir => {
+                SortValue::Text(self.cell(dl, row).to_lowercase())
+            }
+            Column::Size => number(progress.total_size.map(|s| s as f64)),
+            Column::Progress => number(Some(progress.percentage())),
+            Column::Down => number(Some(progress.download_speed as f64)),
+            Column::Up => number(Some(progress.upload_speed as f64)),
+            Column::Eta => number(progress.eta_seconds.map(|s| s as f64)),
+            Column::Priority => number(Some(dl.priority as i8 as f64)),
+            Column::Peers => {
+                number((dl.kind != DownloadKind::Http).then_some(progress.peers as f64))
+            }
+            Column::Ratio => number(row.ratio(dl)),
+            Column::Created => number(Some(dl.created_at.timestamp_millis() as f64)),
+        }
+    }
+}
+
+/// Parse a comma-separated column list
+pub fn parse_columns(s: &str) -> Result<Vec<Column>> {
+    let columns = s
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<Column>>>()?;
+    if columns.is_empty() {
+        bail!("no columns given");
+    }
+    Ok(columns)
+}
+
+/// What cells need besides the download itself
+pub struct RowContext<'a> {
+    pub store: &'a Store,
+    /// Bytes uploaded as a running supervisor counts them, ahead of the
+    /// totals it last saved in the store
+    pub uploaded: &'a HashMap<DownloadId, f64>,
+}
+
+impl RowContext<'_> {
+    fn ratio(&self, dl: &DownloadStatus) -> Option<f64> {
+        if dl.kind == DownloadKind::Http {
+            return None;
+        }
+        let uploaded = match self.uploaded.get(&dl.id) {
+            Some(bytes) => *bytes,
+            None => self.store.get(dl.id).map_or(0, |e| e.uploaded) as f64,
+        };
+        let downloaded = dl.progress.total_size.unwrap_or(dl.progress.completed_size);
+        // Until the engine knows the size, only "nothing uploaded yet" is certain
+        match downloaded {
+            0 => (uploaded == 0.0).then_some(0.0),
+            _ => Some(uploaded / downloaded as f64),
+        }
+    }
+}
+
+/// `--sort COLUMN[:asc|desc]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (column, order) = s.split_once(':').unwrap_or((s, "asc"));
+        let descending = match order.trim().to_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            _ => bail!("unknown order '{}'. Use asc or desc", order),
+        };
+        Ok(SortKey {
+            column: column.parse()?,
+            descending,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+enum SortValue {
+    Text(String),
+    Number(Option<f64>),
+}
+
+impl SortKey {
+    /// Sort downloads by the key; ties keep their order
+    pub fn sort(self, downloads: &mut [DownloadStatus], row: &RowContext) {
+        downloads.sort_by_cached_key(|dl| OrderedValue(self.column.key(dl, row), self.descending));
+    }
+}
+
+/// A sort value with its direction, so unknowns can stay last
+struct OrderedValue(SortValue, bool);
+
+impl Ord for OrderedValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let descending = self.1;
+        let order = match (&self.0, &other.0) {
+            (SortValue::Number(None), SortValue::Number(None)) => return Ordering::Equal,
+            (SortValue::Number(None), _) => return Ordering::Greater,
+            (_, SortValue::Number(None)) => return Ordering::Less,
+            (a, b) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        };
+        if descending {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+}
+
+impl PartialOrd for OrderedValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for OrderedValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedValue {}
+
+/// Width of the terminal stdout goes to, if it is one
+pub fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(width, _)| width.0 as usize)
+}
+
+/// Height of the terminal stdout goes to, if it is one
+pub fn terminal_height() -> Option<usize> {
+    terminal_size::terminal_size().map(|(_, height)| height.0 as usize)
+}
+
+/// Widths for each column: fixed ones as they are, `name` and `dir` as wide
+/// as their longest cell but together no wider than what `total` leaves
+fn widths(columns: &[Column], cells: &[Vec<String>], total: Option<usize>) -> Vec<usize> {
+    let natural = |i: usize, column: Column| {
+        cells
+            .iter()
+            .map(|row| row[i].chars().count())
+            .chain([column.header().len()])
+            .max()
+            .unwrap_or(0)
+    };
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c.width().unwrap_or_else(|| natural(i, *c)))
+        .collect();
+    let Some(total) = total else {
+        return widths;
+    };
+
+    let fixed: usize = columns.iter().filter_map(|c| c.width()).sum();
+    let mut left = total.saturating_sub(fixed + columns.len().saturating_sub(1));
+    let mut flexible: Vec<usize> = (0..columns.len())
+        .filter(|i| columns[*i].width().is_none())
+        .collect();
+    // Narrowest first, so a short column gives its unused share to the others
+    flexible.sort_by_key(|i| widths[*i]);
+    let mut count = flexible.len();
+    for i in flexible {
+        let share = (left / count).max(MIN_FLEXIBLE_WIDTH);
+        widths[i] = widths[i].min(share);
+        left = left.saturating_sub(widths[i]);
+        count -= 1;
+    }
+    widths
+}
+
+const MIN_FLEXIBLE_WIDTH: usize = 10;
+
+/// Print downloads as a table of the given columns
+pub fn print_columns(
+    downloads: &[DownloadStatus],
+    columns: &[Column],
+    row: &RowContext,
+    total_width: Option<usize>,
+) {
+    if downloads.is_empty() {
+        println!("No downloads");
+        return;
+    }
+
+    let cells: Vec<Vec<String>> = downloads
+        .iter()
+        .map(|dl| columns.iter().map(|c| c.cell(dl, row)).collect())
+        .collect();
+    let widths = widths(columns, &cells, total_width);
+
+    let line = |texts: Vec<String>| {
+        let padded: Vec<String> = columns
+            .iter()
+            .zip(texts)
+            .zip(&widths)
+            .map(|((column, text), &width)| {
+                let text = truncate_str(&text, width);
+                if column.right_aligned() {
+                    format!("{:>width$}", text)
+                } else {
+                    format!("{:<width$}", text)
+                }
+            })
+            .collect();
+        println!("{}", padded.join(" ").trim_end());
+    };
+
+    line(columns.iter().map(|c| c.header().to_string()).collect());
+    println!(
+        "{}",
+        "─".repeat(widths.iter().sum::<usize>() + widths.len().saturating_sub(1))
+    );
+    for texts in cells {
+        line(texts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+    use gosh_dl::DownloadState;
+
+    #[test]
+    fn parses_columns_and_sort_keys() {
+        assert_eq!(
+            parse_columns("id, name,speed").unwrap(),
+            [Column::Id, Column::Name, Column::Down]
+        );
+        assert!(parse_columns("id,colour").is_err());
+        assert!(parse_columns("").is_err());
+
+        let key: SortKey = "speed:desc".parse().unwrap();
+        assert_eq!(key.column, Column::Down);
+        assert!(key.descending);
+        assert!(!"size".parse::<SortKey>().unwrap().descending);
+        assert!("size:sideways".parse::<SortKey>().is_err());
+    }
+
+    #[test]
+    fn ratio_comes_from_the_saved_upload_total() {
+        let torrent = status(DownloadKind::Torrent, DownloadState::Seeding, 100, 100);
+        let http = status(DownloadKind::Http, DownloadState::Completed, 100, 100);
+        let mut store = Store::default();
+        store.entry(torrent.id).uploaded = 150;
+        let mut uploaded = HashMap::new();
+        let row = RowContext {
+            store: &store,
+            uploaded: &uploaded,
+        };
+        assert_eq!(Column::Ratio.cell(&torrent, &row), "1.50");
+        assert_eq!(Column::Ratio.cell(&http, &row), "--");
+        let mut magnet = status(DownloadKind::Magnet, DownloadState::Queued, 0, 0);
+        magnet.progress.total_size = None;
+        assert_eq!(Column::Ratio.cell(&magnet, &row), "0.00");
+
+        uploaded.insert(torrent.id, 200.0);
+        let row = RowContext {
+            store: &store,
+            uploaded: &uploaded,
+        };
+        assert_eq!(Column::Ratio.cell(&torrent, &row), "2.00");
+    }
+
+    #[test]
+    fn sorts_with_unknown_values_last() {
+        let mut small = status(DownloadKind::Http, DownloadState::Paused, 10, 0);
+        small.metadata.name = "small".to_string();
+        let mut big = status(DownloadKind::Http, DownloadState::Paused, 1000, 0);
+        big.metadata.name = "big".to_string();
+        let mut unknown = status(DownloadKind::Http, DownloadState::Queued, 0, 0);
+        unknown.progress.total_size = None;
+        unknown.metadata.name = "unknown".to_string();
+
+        let store = Store::default();
+        let uploaded = HashMap::new();
+        let row = RowContext {
+            store: &store,
+            uploaded: &uploaded,
+        };
+        let names = |downloads: &[DownloadStatus]| -> Vec<String> {
+            downloads.iter().map(|d| d.metadata.name.clone()).collect()
+        };
+
+        let mut downloads = vec![unknown, small, big];
+        SortKey {
+            column: Column::Size,
+            descending: true,
+        }
+        .sort(&mut downloads, &row);
+        assert_eq!(names(&downloads), ["big", "small", "unknown"]);
+        SortKey {
+            column: Column::Size,
+            descending: false,
+        }
+        .sort(&mut downloads, &row);
+        assert_eq!(names(&downloads), ["small", "big", "unknown"]);
+    }
+
+    #[test]
+    fn flexible_columns_share_the_terminal_width() {
+        let columns = [Column::Id, Column::Name, Column::Dir];
+        let cells = vec![vec!["x".repeat(16), "n".repeat(80), "/data".to_string()]];
+        // Unbounded: as wide as the content
+        assert_eq!(widths(&columns, &cells, None), [16, 80, 5]);
+        // The short dir keeps its width and the name gets the rest
+        assert_eq!(widths(&columns, &cells, Some(60)), [16, 60 - 16 - 2 - 5, 5]);
+        // Never narrower than the minimum
+        assert_eq!(widths(&columns, &cells, Some(20)), [16, 10, 5]);
+    }
+}
@@ -1 +1,4 @@
+pub mod columns;
+pub mod record;
 pub mod table;
+pub mod template;
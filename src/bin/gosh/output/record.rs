@@ -0,0 +1,334 @@
+//! Records for `--output csv|tsv|ndjson` and `--format`
+//!
+//! Commands that print downloads, stats or torrent info describe each item as
+//! a record of named fields with plain values (bytes, bytes per second,
+//! seconds, percent), so scripts get numbers rather than "1.5 MB". NDJSON
+//! writes the same objects as `--output json`, one per line.
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use gosh_dl::{DownloadState, DownloadStatus};
+use serde::Serialize;
+
+use super::template::Template;
+use crate::cli::OutputFormat;
+use crate::exit_code::{Category, CategoryError};
+use crate::store::Store;
+
+/// A field value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Int(i64),
+    /// Printed with two decimals unless a template gives a precision
+    Float(f64),
+    Bool(bool),
+    Empty,
+}
+
+impl Value {
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Int(_) | Value::Float(_))
+    }
+
+    pub fn time(at: DateTime<Utc>) -> Self {
+        Value::Text(at.to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{:.2}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Empty => Ok(()),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Value::Int(n.min(i64::MAX as u64) as i64)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::from(n as u64)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Self {
+        Value::Int(n as i64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Empty, Into::into)
+    }
+}
+
+/// Something printable as a CSV row or through a template
+pub trait Record: Serialize {
+    /// Field names, in column order
+    const FIELDS: &'static [&'static str];
+
+    fn field(&self, name: &str) -> Value;
+}
+
+impl<R: Record> Record for &R {
+    const FIELDS: &'static [&'static str] = R::FIELDS;
+
+    fn field(&self, name: &str) -> Value {
+        (*self).field(name)
+    }
+}
+
+/// How to print command results
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub format: OutputFormat,
+    /// From `--format`; takes over from `format`
+    pub template: Option<Template>,
+}
+
+impl Output {
+    /// Whether results go out as the human-readable form
+    pub fn is_table(&self) -> bool {
+        self.template.is_none() && self.format == OutputFormat::Table
+    }
+
+    /// The error for a command that cannot print in the `--output` format
+    pub fn unsupported(&self) -> CategoryError {
+        CategoryError::new(
+            Category::Usage,
+            format!(
+                "--output {} is not supported by this command",
+                self.format.to_possible_value().unwrap().get_name()
+            ),
+        )
+    }
+
+    /// Print records as asked: `json` is what `--output json` writes and
+    /// `table` prints the human-readable form
+    pub fn print<R: Record>(
+        &self,
+        records: &[R],
+        json: &impl Serialize,
+        table: impl FnOnce(),
+    ) -> Result<()> {
+        if let Some(ref template) = self.template {
+            for record in records {
+                println!("{}", template.render(record));
+            }
+            return Ok(());
+        }
+        match self.format {
+            OutputFormat::Table => table(),
+            OutputFormat::Json => println!("{}", serde_json::to_string(json)?),
+            OutputFormat::JsonPretty => println!("{}", serde_json::to_string_pretty(json)?),
+            OutputFormat::Ndjson => {
+                for record in records {
+                    println!("{}", serde_json::to_string(record)?);
+                }
+            }
+            OutputFormat::Csv => print_delimited(records, ','),
+            OutputFormat::Tsv => print_delimited(records, '\t'),
+        }
+        Ok(())
+    }
+}
+
+fn print_delimited<R: Record>(records: &[R], delimiter: char) {
+    let line = |cells: Vec<String>| {
+        let cells: Vec<String> = cells
+            .into_iter()
+            .map(|cell| escape(&cell, delimiter))
+            .collect();
+        println!("{}", cells.join(&delimiter.to_string()));
+    };
+    line(R::FIELDS.iter().map(|f| f.to_string()).collect());
+    for record in records {
+        line(
+            R::FIELDS
+                .iter()
+                .map(|f| record.field(f).to_string())
+                .collect(),
+        );
+    }
+}
+
+/// Quote a CSV cell when needed; TSV cannot quote, so tabs and line breaks
+/// become spaces
+fn escape(cell: &str, delimiter: char) -> String {
+    if delimiter == '\t' {
+        return cell.replace(['\t', '\n', '\r'], " ");
+    }
+    if cell.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// A download, with the CLI state for its tags and category
+pub struct DownloadRecord<'a> {
+    pub dl: &'a DownloadStatus,
+    pub store: &'a Store,
+}
+
+impl Serialize for DownloadRecord<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.dl.serialize(serializer)
+    }
+}
+
+impl Record for DownloadRecord<'_> {
+    const FIELDS: &'static [&'static str] = &[
+        "gid",
+        "id",
+        "name",
+        "kind",
+        "state",
+        "error",
+        "priority",
+        "size",
+        "completed",
+        "progress",
+        "down",
+        "up",
+        "eta",
+        "connections",
+        "peers",
+        "seeders",
+        "dir",
+        "filename",
+        "url",
+        "info_hash",
+        "category",
+        "tags",
+        "created",
+        "completed_at",
+    ];
+
+    fn field(&self, name: &str) -> Value {
+        let dl = self.dl;
+        let progress = &dl.progress;
+        let meta = &dl.metadata;
+        let extras = self.store.get(dl.id);
+        match name {
+            "gid" => dl.id.to_gid().into(),
+            "id" => dl.id.as_uuid().to_string().into(),
+            "name" => meta.name.clone().into(),
+            "kind" => format!("{:?}", dl.kind).to_lowercase().into(),
+            "state" => state_name(&dl.state).into(),
+            "error" => match &dl.state {
+                DownloadState::Error { message, .. } => message.clone().into(),
+                _ => Value::Empty,
+            },
+            "priority" => dl.priority.to_string().into(),
+            "size" => progress.total_size.into(),
+            "completed" => progress.completed_size.into(),
+            "progress" => Value::Float(progress.percentage()),
+            "down" => progress.download_speed.into(),
+            "up" => progress.upload_speed.into(),
+            "eta" => progress.eta_seconds.into(),
+            "connections" => progress.connections.into(),
+            "peers" => progress.peers.into(),
+            "seeders" => progress.seeders.into(),
+            "dir" => meta.save_dir.display().to_string().into(),
+            "filename" => meta.filename.clone().into(),
+            "url" => meta.url.clone().or_else(|| meta.magnet_uri.clone()).into(),
+            "info_hash" => meta.info_hash.clone().into(),
+            "category" => extras.and_then(|e| e.category.clone()).into(),
+            "tags" => extras.map(|e| e.tags.join(",")).into(),
+            "created" => Value::time(dl.created_at),
+            "completed_at" => dl.completed_at.map_or(Value::Empty, Value::time),
+            _ => Value::Empty,
+        }
+    }
+}
+
+/// Lowercase state name, as `--where state=` takes it
+fn state_name(state: &DownloadState) -> &'static str {
+    match state {
+        DownloadState::Queued => "queued",
+        DownloadState::Connecting => "connecting",
+        DownloadState::Downloading => "downloading",
+        DownloadState::Seeding => "seeding",
+        DownloadState::Paused => "paused",
+        DownloadState::Completed => "completed",
+        DownloadState::Error { .. } => "error",
+    }
+}
+
+/// Records for a list of downloads
+pub fn download_records<'a>(
+    downloads: &'a [DownloadStatus],
+    store: &'a Store,
+) -> Vec<DownloadRecord<'a>> {
+    downloads
+        .iter()
+        .map(|dl| DownloadRecord { dl, store })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::DownloadExtras;
+    use crate::util::testing::status;
+    use gosh_dl::DownloadKind;
+
+    #[test]
+    fn download_fields_and_escaping() {
+        let mut dl = status(DownloadKind::Magnet, DownloadState::Downloading, 200, 50);
+        dl.metadata.name = "a, \"b\"".to_string();
+        let mut store = Store::default();
+        *store.entry(dl.id) = DownloadExtras {
+            tags: vec!["linux".to_string(), "iso".to_string()],
+            ..Default::default()
+        };
+        let record = DownloadRecord {
+            dl: &dl,
+            store: &store,
+        };
+
+        assert_eq!(record.field("kind"), Value::Text("magnet".to_string()));
+        assert_eq!(
+            record.field("state"),
+            Value::Text("downloading".to_string())
+        );
+        assert_eq!(record.field("size"), Value::Int(200));
+        assert_eq!(record.field("progress").to_string(), "25.00");
+        assert_eq!(record.field("eta"), Value::Empty);
+        assert_eq!(record.field("tags"), Value::Text("linux,iso".to_string()));
+
+        assert_eq!(escape(&dl.metadata.name, ','), "\"a, \"\"b\"\"\"");
+        assert_eq!(escape("a\tb\nc", '\t'), "a b c");
+        assert_eq!(escape("plain", ','), "plain");
+    }
+}
@@ -1,44 +1,51 @@
-use gosh_dl::DownloadStatus;
+use gosh_dl::{DownloadEngine, DownloadId, DownloadStatus};
+use std::collections::HashMap;
 
+use super::columns::{print_columns, terminal_width, RowContext, DEFAULT_COLUMNS};
 use crate::commands::add::AddResult;
-use crate::format::{format_duration, format_speed, format_state};
+use crate::commands::import::Aria2Mapping;
+use crate::format::{format_duration, format_size, format_speed};
+use crate::report::{ChecksumResult, Outcome, ReportEntry};
+use crate::store::Store;
 use crate::util::truncate_str;
 
-pub fn print_download_table(downloads: &[DownloadStatus]) {
-    if downloads.is_empty() {
-        println!("No downloads");
+pub fn print_download_table(downloads: &[DownloadStatus], store: &Store) {
+    let uploaded = HashMap::new();
+    let row = RowContext {
+        store,
+        uploaded: &uploaded,
+    };
+    print_columns(downloads, &DEFAULT_COLUMNS, &row, terminal_width());
+}
+
+/// What a `--dry-run` would act on
+pub fn print_dry_run(verb: &str, ids: &[DownloadId], engine: &DownloadEngine, store: &Store) {
+    let downloads: Vec<DownloadStatus> = ids.iter().filter_map(|id| engine.status(*id)).collect();
+    if !downloads.is_empty() {
+        print_download_table(&downloads, store);
+        println!();
+    }
+    println!("Would {} {} download(s)", verb, downloads.len());
+}
+
+pub fn print_aria2_mapping(mappings: &[Aria2Mapping]) {
+    if mappings.is_empty() {
         return;
     }
 
-    // Header
-    println!(
-        "{:<16} {:<35} {:>8} {:>12} {:>10} {:<12}",
-        "ID", "Name", "Progress", "Speed", "ETA", "State"
-    );
-    println!("{}", "─".repeat(95));
-
-    // Rows
-    for dl in downloads {
-        let progress = dl.progress.percentage();
-        let speed = format_speed(dl.progress.download_speed);
-        let eta = dl
-            .progress
-            .eta_seconds
-            .map(format_duration)
-            .unwrap_or_else(|| "--".to_string());
-        let state = format_state(&dl.state);
-        let name = truncate_str(&dl.metadata.name, 35);
+    println!("{:<16} {:<16} Input", "aria2 GID", "gosh GID");
+    println!("{}", "─".repeat(70));
 
+    for mapping in mappings {
         println!(
-            "{:<16} {:<35} {:>7.1}% {:>10}/s {:>10} {:<12}",
-            dl.id.to_gid(),
-            name,
-            progress,
-            speed,
-            eta,
-            state
+            "{:<16} {:<16} {}",
+            mapping.aria2_gid,
+            mapping.id,
+            truncate_str(&mapping.input, 50)
         );
     }
+
+    println!();
 }
 
 pub fn print_add_results(results: &[AddResult]) {
@@ -61,3 +68,47 @@ pub fn print_add_results(results: &[AddResult]) {
     println!();
     println!("Added {} download(s)", results.len());
 }
+
+/// The summary table printed at the end of a run
+pub fn print_report(entries: &[ReportEntry]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    println!(
+        "{:<40} {:>10} {:>8} {:>12} {:>5} {:<11} Result",
+        "Path", "Size", "Time", "Avg speed", "Conns", "Checksum"
+    );
+    println!("{}", "─".repeat(100));
+
+    for entry in entries {
+        let path = entry
+            .path
+            .as_ref()
+            .map_or_else(|| entry.input.clone(), |p| p.display().to_string());
+        let result = match (entry.outcome, &entry.error) {
+            (Outcome::Completed, _) => "Done".to_string(),
+            (Outcome::Failed, Some(error)) => format!("Failed: {}", truncate_str(error, 40)),
+            (Outcome::Failed, None) => "Failed".to_string(),
+            (Outcome::Interrupted, _) => "Interrupted".to_string(),
+        };
+        let checksum = match entry.checksum {
+            ChecksumResult::None => "-",
+            ChecksumResult::Verified => "ok",
+            ChecksumResult::Mismatch => "mismatch",
+            ChecksumResult::NotChecked => "not checked",
+        };
+        println!(
+            "{:<40} {:>10} {:>8} {:>12} {:>5} {:<11} {}",
+            truncate_str(&path, 40),
+            entry.size.map_or_else(|| "-".to_string(), format_size),
+            format_duration(entry.elapsed.round() as u64),
+            entry
+                .average_speed
+                .map_or_else(|| "-".to_string(), |s| format!("{}/s", format_speed(s))),
+            entry.connections,
+            checksum,
+            result
+        );
+    }
+}
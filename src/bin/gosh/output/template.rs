@@ -0,0 +1,218 @@
+//! `--format` templates
+//!
+//! Text with `{field}` placeholders, e.g. `{gid}\t{name}\t{progress:.1}%`.
+//! A placeholder may take a spec after a colon: `<` or `>` to align, a width,
+//! and `.N` digits after the point for numbers. `{{` and `}}` are literal
+//! braces; `\t`, `\n` and `\\` are escapes, so templates work in single quotes.
+
+use anyhow::{bail, Result};
+
+use super::record::{Record, Value};
+use crate::exit_code::{Category, CategoryError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Field { name: String, spec: Spec },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Spec {
+    /// Left-aligned if true, right if false; numbers go right by default
+    left: Option<bool>,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+impl std::str::FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => {
+                        literal.push('\\');
+                        literal.push(other);
+                    }
+                    None => literal.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => bail!("unmatched '}}' (use '}}}}' for a literal brace)"),
+                '{' => {
+                    let mut inner = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => inner.push(c),
+                            None => bail!("unclosed '{{' (use '{{{{' for a literal brace)"),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(parse_field(&inner)?);
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+}
+
+fn parse_field(inner: &str) -> Result<Part> {
+    let (name, spec) = inner.split_once(':').unwrap_or((inner, ""));
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("empty placeholder '{{{}}}'", inner);
+    }
+    let invalid = || anyhow::anyhow!("invalid spec ':{}' for field '{}'", spec, name);
+
+    let mut rest = spec;
+    let mut parsed = Spec::default();
+    if let Some(r) = rest.strip_prefix('<') {
+        parsed.left = Some(true);
+        rest = r;
+    } else if let Some(r) = rest.strip_prefix('>') {
+        parsed.left = Some(false);
+        rest = r;
+    }
+    let (width, precision) = match rest.split_once('.') {
+        Some((width, precision)) => (width, Some(precision)),
+        None => (rest, None),
+    };
+    if !width.is_empty() {
+        parsed.width = Some(width.parse().map_err(|_| invalid())?);
+    }
+    if let Some(precision) = precision {
+        parsed.precision = Some(precision.parse().map_err(|_| invalid())?);
+    }
+    Ok(Part::Field {
+        name: name.to_string(),
+        spec: parsed,
+    })
+}
+
+impl Template {
+    /// Fail on fields the records do not have, naming all of them
+    pub fn check(&self, fields: &[&str]) -> Result<()> {
+        let mut unknown: Vec<&str> = Vec::new();
+        for part in &self.parts {
+            if let Part::Field { name, .. } = part {
+                if !fields.contains(&name.as_str()) && !unknown.contains(&name.as_str()) {
+                    unknown.push(name);
+                }
+            }
+        }
+        let message = match unknown.as_slice() {
+            [] => return Ok(()),
+            [name] => format!("unknown field '{}'. Fields: {}", name, fields.join(", ")),
+            _ => format!(
+                "unknown fields '{}'. Fields: {}",
+                unknown.join("', '"),
+                fields.join(", ")
+            ),
+        };
+        // A bad argument, like an unknown `--columns` name the parser rejects
+        bail!(CategoryError::new(Category::Usage, message))
+    }
+
+    pub fn render<R: Record>(&self, record: &R) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Field { name, spec } => {
+                    let value = record.field(name);
+                    let text = match (&value, spec.precision) {
+                        (Value::Float(f), Some(precision)) => format!("{:.*}", precision, f),
+                        _ => value.to_string(),
+                    };
+                    let width = spec.width.unwrap_or(0);
+                    let left = spec.left.unwrap_or(!value.is_number());
+                    if left {
+                        out.push_str(&format!("{:<width$}", text));
+                    } else {
+                        out.push_str(&format!("{:>width$}", text));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Row;
+
+    impl Record for Row {
+        const FIELDS: &'static [&'static str] = &["gid", "name", "progress", "size"];
+
+        fn field(&self, name: &str) -> Value {
+            match name {
+                "gid" => Value::Text("abc".to_string()),
+                "name" => Value::Text("a.iso".to_string()),
+                "progress" => Value::Float(42.345),
+                "size" => Value::Int(1024),
+                _ => Value::Empty,
+            }
+        }
+    }
+
+    #[test]
+    fn renders_fields_specs_and_escapes() {
+        let template: Template = r"{gid}\t{name:<6}|{progress:.1}%|{size:>6}|{{x}}"
+            .parse()
+            .unwrap();
+        template.check(Row::FIELDS).unwrap();
+        assert_eq!(template.render(&Row), "abc\ta.iso |42.3%|  1024|{x}");
+        assert_eq!(
+            "{size:6}".parse::<Template>().unwrap().render(&Row),
+            "  1024"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_templates_and_names_unknown_fields() {
+        assert!("{gid".parse::<Template>().is_err());
+        assert!("gid}".parse::<Template>().is_err());
+        assert!("{}".parse::<Template>().is_err());
+        assert!("{progress:.x}".parse::<Template>().is_err());
+
+        let template: Template = "{gid} {colour} {speeed} {colour}".parse().unwrap();
+        let err = template.check(Row::FIELDS).unwrap_err();
+        assert_eq!(Category::of_error(&err), Category::Usage);
+        let err = err.to_string();
+        assert!(
+            err.starts_with("unknown fields 'colour', 'speeed'."),
+            "{}",
+            err
+        );
+    }
+}
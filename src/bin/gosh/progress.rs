@@ -0,0 +1,500 @@
+//! Progress as lines instead of bars: `--progress json` and `--progress plain`
+//!
+//! JSON progress writes one object per line, tagged by `event`: `started`
+//! when a download is added, `progress` at most once a second per download,
+//! then `completed` or `failed`. Records go to stdout, or to an inherited file
+//! descriptor with `--progress-fd` so stdout stays free.
+//!
+//! Plain progress writes the same events as short lines on stderr, with a
+//! `progress` line every `--progress-interval` (10% or 30s, say), for CI logs
+//! where bars would be hidden or turn into control codes.
+
+use anyhow::{bail, Context, Result};
+use gosh_dl::{DownloadId, DownloadProgress};
+use indicatif::{MultiProgress, ProgressDrawTarget};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::cli::ProgressMode;
+use crate::format::{format_duration, format_size, format_speed};
+use crate::util::parse_duration;
+
+/// Least time between two `progress` records for one download
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Record<'a> {
+    Started {
+        gid: String,
+        input: &'a str,
+    },
+    Progress {
+        gid: String,
+        bytes: u64,
+        total: Option<u64>,
+        speed: u64,
+        upload_speed: u64,
+        eta: Option<u64>,
+    },
+    Completed {
+        gid: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+    },
+    Failed {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gid: Option<String>,
+        input: &'a str,
+        kind: &'a str,
+        error: &'a str,
+        retryable: bool,
+    },
+}
+
+/// Writer for progress records
+pub struct JsonProgress {
+    out: Box<dyn Write + Send>,
+    last: HashMap<DownloadId, Instant>,
+}
+
+impl JsonProgress {
+    /// Write to stdout, or to the given file descriptor
+    pub fn open(fd: Option<i32>) -> Result<Self> {
+        let out: Box<dyn Write + Send> = match fd {
+            None => Box::new(std::io::stdout()),
+            Some(fd) => Box::new(open_fd(fd)?),
+        };
+        Ok(Self {
+            out,
+            last: HashMap::new(),
+        })
+    }
+
+    pub fn started(&mut self, id: DownloadId, input: &str) -> Result<()> {
+        self.write(&Record::Started {
+            gid: id.to_gid(),
+            input,
+        })
+    }
+
+    /// Write a `progress` record unless one went out for this download less
+    /// than a second ago
+    pub fn progress(&mut self, id: DownloadId, progress: &DownloadProgress) -> Result<()> {
+        let now = Instant::now();
+        if let Some(last) = self.last.get(&id) {
+            if now.duration_since(*last) < PROGRESS_INTERVAL {
+                return Ok(());
+            }
+        }
+        self.last.insert(id, now);
+        self.write(&Record::Progress {
+            gid: id.to_gid(),
+            bytes: progress.completed_size,
+            total: progress.total_size,
+            speed: progress.download_speed,
+            upload_speed: progress.upload_speed,
+            eta: progress.eta_seconds,
+        })
+    }
+
+    pub fn completed(&mut self, id: DownloadId, path: Option<PathBuf>) -> Result<()> {
+        self.write(&Record::Completed {
+            gid: id.to_gid(),
+            path,
+        })
+    }
+
+    /// `id` is `None` for an input that could not be added
+    pub fn failed(
+        &mut self,
+        id: Option<DownloadId>,
+        input: &str,
+        kind: &str,
+        error: &str,
+        retryable: bool,
+    ) -> Result<()> {
+        self.write(&Record::Failed {
+            gid: id.map(|id| id.to_gid()),
+            input,
+            kind,
+            error,
+            retryable,
+        })
+    }
+
+    fn write(&mut self, record: &Record) -> Result<()> {
+        let line = serde_json::to_string(record)?;
+        writeln!(self.out, "{}", line).context("Failed to write progress")?;
+        self.out.flush().context("Failed to write progress")
+    }
+}
+
+/// The mode to use: as given, or bars on a terminal and plain lines otherwise
+pub fn resolve_mode(mode: Option<ProgressMode>) -> ProgressMode {
+    mode.unwrap_or_else(|| {
+        // Bars are drawn on stderr
+        if std::io::stderr().is_terminal() {
+            ProgressMode::Bar
+        } else {
+            ProgressMode::Plain
+        }
+    })
+}
+
+/// Progress bars for `bar` mode; hidden in the others
+pub fn multi_progress(mode: ProgressMode) -> MultiProgress {
+    if mode == ProgressMode::Bar {
+        MultiProgress::new()
+    } else {
+        MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+    }
+}
+
+/// How often plain progress prints a `progress` line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressInterval {
+    /// Each time a download passes a multiple of this percentage
+    Percent(u64),
+    Every(Duration),
+}
+
+impl Default for ProgressInterval {
+    fn default() -> Self {
+        ProgressInterval::Percent(10)
+    }
+}
+
+impl std::str::FromStr for ProgressInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(percent) = s.trim().strip_suffix('%') {
+            return match percent.trim().parse::<u64>() {
+                Ok(p @ 1..=100) => Ok(ProgressInterval::Percent(p)),
+                _ => bail!("Invalid percentage '{}'. Use 1% to 100%", s),
+            };
+        }
+        match parse_duration(s)? {
+            0 => bail!("Progress interval must be at least 1s"),
+            secs => Ok(ProgressInterval::Every(Duration::from_secs(secs))),
+        }
+    }
+}
+
+/// Least time between plain `progress` lines for a download whose size is
+/// not known, when the interval is a percentage
+const UNKNOWN_SIZE_INTERVAL: Duration = Duration::from_secs(30);
+
+struct PlainDownload {
+    name: String,
+    started: Instant,
+    last_line: Instant,
+    /// Percentage step the last line was printed at
+    last_step: u64,
+}
+
+/// Writer for plain progress lines on stderr
+pub struct PlainProgress {
+    interval: ProgressInterval,
+    downloads: HashMap<DownloadId, PlainDownload>,
+}
+
+impl PlainProgress {
+    pub fn new(interval: ProgressInterval) -> Self {
+        Self {
+            interval,
+            downloads: HashMap::new(),
+        }
+    }
+
+    pub fn started(&mut self, id: DownloadId, input: &str) {
+        let now = Instant::now();
+        self.downloads.insert(
+            id,
+            PlainDownload {
+                name: input.to_string(),
+                started: now,
+                last_line: now,
+                last_step: 0,
+            },
+        );
+        eprintln!("{} started {}", id.to_gid(), input);
+    }
+
+    pub fn progress(&mut self, id: DownloadId, progress: &DownloadProgress) {
+        let Some(download) = self.downloads.get_mut(&id) else {
+            return;
+        };
+        let now = Instant::now();
+        if let Some(step) = due(
+            self.interval,
+            progress,
+            now.duration_since(download.last_line),
+            download.last_step,
+        ) {
+            download.last_line = now;
+            download.last_step = step;
+            eprintln!("{} {}", id.to_gid(), progress_line(progress));
+        }
+    }
+
+    pub fn completed(&mut self, id: DownloadId, path: Option<PathBuf>) {
+        let Some(download) = self.downloads.get(&id) else {
+            return;
+        };
+        let name = path.map_or_else(|| download.name.clone(), |p| p.display().to_string());
+        eprintln!(
+            "{} done in {}: {}",
+            id.to_gid(),
+            format_duration(download.started.elapsed().as_secs()),
+            name
+        );
+    }
+
+    pub fn failed(&mut self, id: Option<DownloadId>, input: &str, error: &str) {
+        match id {
+            Some(id) => eprintln!("{} failed: {}", id.to_gid(), error),
+            None => eprintln!("failed to add {}: {}", input, error),
+        }
+    }
+}
+
+/// Whether a `progress` line is due; returns the percentage step to remember
+fn due(
+    interval: ProgressInterval,
+    progress: &DownloadProgress,
+    since_last: Duration,
+    last_step: u64,
+) -> Option<u64> {
+    match (interval, progress.total_size) {
+        (ProgressInterval::Percent(percent), Some(total)) if total > 0 => {
+            let step = progress.completed_size * 100 / total / percent * percent;
+            (step > last_step && step < 100).then_some(step)
+        }
+        (ProgressInterval::Percent(_), _) => {
+            (since_last >= UNKNOWN_SIZE_INTERVAL).then_some(last_step)
+        }
+        (ProgressInterval::Every(every), _) => (since_last >= every).then_some(last_step),
+    }
+}
+
+fn progress_line(progress: &DownloadProgress) -> String {
+    let speed = format!("{}/s", format_speed(progress.download_speed));
+    match progress.total_size {
+        Some(total) => {
+            let eta = progress
+                .eta_seconds
+                .map_or_else(|| "--".to_string(), format_duration);
+            format!(
+                "{:.0}% {} / {} {} ETA {}",
+                progress.percentage(),
+                format_size(progress.completed_size),
+                format_size(total),
+                speed,
+                eta
+            )
+        }
+        None => format!("{} {}", format_size(progress.completed_size), speed),
+    }
+}
+
+/// Line-based progress: JSON records or plain lines
+pub enum ProgressLines {
+    Json(JsonProgress),
+    Plain(PlainProgress),
+}
+
+impl ProgressLines {
+    /// The writer for `mode`, or `None` when progress is shown as bars or not
+    /// at all
+    pub fn open(
+        mode: ProgressMode,
+        fd: Option<i32>,
+        interval: ProgressInterval,
+    ) -> Result<Option<Self>> {
+        if fd.is_some() && mode != ProgressMode::Json {
+            bail!("--progress-fd needs --progress json");
+        }
+        Ok(match mode {
+            ProgressMode::Json => Some(ProgressLines::Json(JsonProgress::open(fd)?)),
+            ProgressMode::Plain => Some(ProgressLines::Plain(PlainProgress::new(interval))),
+            ProgressMode::Bar | ProgressMode::None => None,
+        })
+    }
+
+    pub fn started(&mut self, id: DownloadId, input: &str) -> Result<()> {
+        match self {
+            ProgressLines::Json(json) => json.started(id, input),
+            ProgressLines::Plain(plain) => {
+                plain.started(id, input);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn progress(&mut self, id: DownloadId, progress: &DownloadProgress) -> Result<()> {
+        match self {
+            ProgressLines::Json(json) => json.progress(id, progress),
+            ProgressLines::Plain(plain) => {
+                plain.progress(id, progress);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn completed(&mut self, id: DownloadId, path: Option<PathBuf>) -> Result<()> {
+        match self {
+            ProgressLines::Json(json) => json.completed(id, path),
+            ProgressLines::Plain(plain) => {
+                plain.completed(id, path);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn failed(
+        &mut self,
+        id: Option<DownloadId>,
+        input: &str,
+        kind: &str,
+        error: &str,
+        retryable: bool,
+    ) -> Result<()> {
+        match self {
+            ProgressLines::Json(json) => json.failed(id, input, kind, error, retryable),
+            ProgressLines::Plain(plain) => {
+                plain.failed(id, input, error);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Short kind of an engine error, e.g. `network` or `invalid_input`, from the
+/// `Network { .. }` form the engine records
+pub fn error_kind(debug: &str) -> String {
+    let mut kind = String::new();
+    for c in debug.chars().take_while(|c| c.is_ascii_alphanumeric()) {
+        if c.is_ascii_uppercase() && !kind.is_empty() {
+            kind.push('_');
+        }
+        kind.push(c.to_ascii_lowercase());
+    }
+    if kind.is_empty() {
+        kind.push_str("unknown");
+    }
+    kind
+}
+
+#[cfg(unix)]
+fn open_fd(fd: i32) -> Result<std::fs::File> {
+    use std::os::unix::io::FromRawFd;
+
+    // Descriptors gosh opens itself are close-on-exec; one passed in by the
+    // parent is not
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    if flags < 0 {
+        bail!("--progress-fd {} is not an open file descriptor", fd);
+    }
+    if flags & libc::FD_CLOEXEC != 0 {
+        bail!(
+            "--progress-fd {} was not passed in by the parent process",
+            fd
+        );
+    }
+    // SAFETY: the descriptor is open and inherited for us to write to;
+    // nothing else in gosh owns it
+    Ok(unsafe { std::fs::File::from_raw_fd(fd) })
+}
+
+#[cfg(not(unix))]
+fn open_fd(_fd: i32) -> Result<std::fs::File> {
+    bail!("--progress-fd is only supported on Unix")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_lines_follow_the_interval() {
+        let progress = |completed, total| DownloadProgress {
+            total_size: total,
+            completed_size: completed,
+            ..Default::default()
+        };
+        let tenth = ProgressInterval::Percent(10);
+        let now = Duration::ZERO;
+
+        assert_eq!(due(tenth, &progress(50, Some(1000)), now, 0), None);
+        assert_eq!(due(tenth, &progress(250, Some(1000)), now, 0), Some(20));
+        assert_eq!(due(tenth, &progress(290, Some(1000)), now, 20), None);
+        // Completion gets its own line
+        assert_eq!(due(tenth, &progress(1000, Some(1000)), now, 90), None);
+        // Unknown sizes fall back to time
+        assert_eq!(due(tenth, &progress(10, None), now, 0), None);
+        assert_eq!(
+            due(tenth, &progress(10, None), UNKNOWN_SIZE_INTERVAL, 0),
+            Some(0)
+        );
+
+        let every = "30s".parse::<ProgressInterval>().unwrap();
+        assert_eq!(due(every, &progress(999, Some(1000)), now, 0), None);
+        assert_eq!(
+            due(
+                every,
+                &progress(999, Some(1000)),
+                Duration::from_secs(30),
+                0
+            ),
+            Some(0)
+        );
+
+        assert!("0%".parse::<ProgressInterval>().is_err());
+        assert!("0s".parse::<ProgressInterval>().is_err());
+        assert_eq!(progress_line(&progress(512, None)), "512 B 0 B/s");
+    }
+
+    #[test]
+    fn error_kind_is_snake_case_variant() {
+        assert_eq!(error_kind("Network { kind: HttpStatus(404) }"), "network");
+        assert_eq!(
+            error_kind("InvalidInput { field: \"url\" }"),
+            "invalid_input"
+        );
+        assert_eq!(error_kind("NotFound(\"x\")"), "not_found");
+        assert_eq!(error_kind(""), "unknown");
+    }
+
+    #[test]
+    fn records_are_tagged_by_event() {
+        let record = Record::Failed {
+            gid: None,
+            input: "http://example.com/a.iso",
+            kind: "add",
+            error: "bad url",
+            retryable: false,
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"event":"failed","input":"http://example.com/a.iso","kind":"add","error":"bad url","retryable":false}"#
+        );
+
+        let record = Record::Progress {
+            gid: "0011223344556677".to_string(),
+            bytes: 10,
+            total: None,
+            speed: 5,
+            upload_speed: 0,
+            eta: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"event":"progress","gid":"0011223344556677","bytes":10,"total":null,"speed":5,"upload_speed":0,"eta":null}"#
+        );
+    }
+}
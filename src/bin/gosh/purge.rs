@@ -0,0 +1,171 @@
+//! Clearing finished downloads from the history
+//!
+//! The engine keeps completed and failed downloads until they are removed.
+//! `gosh purge`, the TUI's clear action and `[general] retention` remove them
+//! the same way `gosh cancel` does, dropping their CLI extras with them.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use gosh_dl::{DownloadEngine, DownloadId, DownloadState, DownloadStatus};
+use std::path::Path;
+
+use crate::config::CliConfig;
+use crate::store::Store;
+use crate::util::parse_duration;
+
+/// Which finished downloads to remove
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurgeFilter {
+    pub completed: bool,
+    pub failed: bool,
+    /// Only those finished at least this many seconds ago
+    pub older_than: Option<u64>,
+}
+
+impl PurgeFilter {
+    pub fn matches(&self, dl: &DownloadStatus, store: &Store, now: DateTime<Utc>) -> bool {
+        let wanted = match dl.state {
+            DownloadState::Completed => self.completed,
+            DownloadState::Error { .. } => self.failed,
+            _ => false,
+        };
+        wanted
+            && self.older_than.is_none_or(|secs| {
+                finished_at(dl, store) <= now - TimeDelta::seconds(secs.min(i64::MAX as u64) as i64)
+            })
+    }
+}
+
+/// When a download finished. The engine does not record when a download
+/// failed, so failed ones go by the time the supervisor saw them fail, or
+/// when they were added if it did not.
+pub fn finished_at(dl: &DownloadStatus, store: &Store) -> DateTime<Utc> {
+    dl.completed_at
+        .or_else(|| store.get(dl.id).and_then(|e| e.failed_at))
+        .unwrap_or(dl.created_at)
+}
+
+/// Downloads the filter selects, oldest first
+pub fn select(
+    downloads: Vec<DownloadStatus>,
+    store: &Store,
+    filter: PurgeFilter,
+) -> Vec<DownloadStatus> {
+    let now = Utc::now();
+    let mut selected: Vec<_> = downloads
+        .into_iter()
+        .filter(|dl| filter.matches(dl, store, now))
+        .collect();
+    selected.sort_by_key(|dl| finished_at(dl, store));
+    selected
+}
+
+/// Remove one download from the engine and the CLI state
+pub async fn purge(
+    engine: &DownloadEngine,
+    state_path: &Path,
+    id: DownloadId,
+    delete_files: bool,
+) -> Result<()> {
+    engine.cancel(id, delete_files).await?;
+    Store::update(state_path, |store| store.remove(id))?;
+    Ok(())
+}
+
+/// Apply `[general] retention`, returning how many downloads were removed
+pub async fn apply_retention(engine: &DownloadEngine, config: &CliConfig) -> Result<usize> {
+    let retention = &config.general.retention;
+    let mut removed = 0;
+    for (completed, limit) in [(true, &retention.completed), (false, &retention.failed)] {
+        let Some(limit) = limit else {
+            continue;
+        };
+        let filter = PurgeFilter {
+            completed,
+            failed: !completed,
+            // Checked by CliConfig::validate
+            older_than: Some(parse_duration(limit).context("Invalid retention")?),
+        };
+        let store = Store::load(&config.state_path())?;
+        for dl in select(engine.list(), &store, filter) {
+            match purge(engine, &config.state_path(), dl.id, false).await {
+                Ok(()) => removed += 1,
+                Err(e) => tracing::warn!("Retention could not remove {}: {:#}", dl.id.to_gid(), e),
+            }
+        }
+    }
+    if removed > 0 {
+        tracing::info!(
+            "Retention: removed {} download(s) from the history",
+            removed
+        );
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+    use gosh_dl::DownloadKind;
+
+    #[test]
+    fn filter_by_state_and_age() {
+        let now = Utc::now();
+        let mut done = status(DownloadKind::Http, DownloadState::Completed, 10, 10);
+        done.created_at = now - TimeDelta::days(30);
+        done.completed_at = Some(now - TimeDelta::days(2));
+        let failed = status(
+            DownloadKind::Http,
+            DownloadState::Error {
+                kind: "network".to_string(),
+                message: "timed out".to_string(),
+                retryable: true,
+            },
+            10,
+            0,
+        );
+        let active = status(DownloadKind::Http, DownloadState::Downloading, 10, 5);
+        let mut store = Store::default();
+
+        let completed = PurgeFilter {
+            completed: true,
+            ..Default::default()
+        };
+        assert!(completed.matches(&done, &store, now));
+        assert!(
+            !completed.matches(&failed, &store, now) && !completed.matches(&active, &store, now)
+        );
+
+        let week = PurgeFilter {
+            older_than: Some(7 * 86_400),
+            ..completed
+        };
+        assert!(!week.matches(&done, &store, now));
+        let day = PurgeFilter {
+            older_than: Some(86_400),
+            ..completed
+        };
+        assert!(day.matches(&done, &store, now));
+
+        let both = PurgeFilter {
+            failed: true,
+            ..completed
+        };
+        let selected = select(vec![failed.clone(), active, done], &store, both);
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[0].state, DownloadState::Completed);
+
+        // Failed downloads age from when they failed, not when they were added
+        let mut failed = failed;
+        failed.created_at = now - TimeDelta::days(30);
+        let failed_week = PurgeFilter {
+            failed: true,
+            older_than: Some(7 * 86_400),
+            ..Default::default()
+        };
+        assert!(failed_week.matches(&failed, &store, now));
+        store.entry(failed.id).failed_at = Some(now - TimeDelta::days(1));
+        assert!(!failed_week.matches(&failed, &store, now));
+    }
+}
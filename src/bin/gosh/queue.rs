@@ -0,0 +1,329 @@
+//! Queue order
+//!
+//! The engine starts waiting downloads by priority and then in the order they
+//! entered its queue, with no way to reposition them afterwards. The CLI keeps
+//! its own order in the state store: lists follow it, downloads resumed
+//! together are handed to the engine front first, and while gosh runs the
+//! supervisor pauses a download that gets a slot out of turn as soon as it
+//! starts, resuming it once everything ahead of it has started.
+
+use anyhow::{bail, Result};
+use gosh_dl::{DownloadId, DownloadState, DownloadStatus, EngineError};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use crate::exit_code::{Category, CategoryError};
+use crate::store::Store;
+use crate::supervisor::is_seeding;
+
+/// Where to move a download within the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueMove {
+    Top,
+    Bottom,
+    Up,
+    Down,
+    /// 1-based position
+    To(usize),
+}
+
+impl std::str::FromStr for QueueMove {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            // A bad argument, like an unknown value the parser rejects
+            other => match other.parse::<usize>() {
+                Ok(0) => bail!(CategoryError::new(
+                    Category::Usage,
+                    "Queue positions start at 1"
+                )),
+                Ok(pos) => Ok(Self::To(pos)),
+                Err(_) => bail!(CategoryError::new(
+                    Category::Usage,
+                    format!(
+                        "Invalid position '{}'. Use top, bottom, up, down or a number",
+                        s
+                    )
+                )),
+            },
+        }
+    }
+}
+
+/// Whether a download is waiting for its turn: queued in the engine, or
+/// paused by gosh (for the queue, a schedule rule, a quota or a start time)
+/// rather than by hand
+pub fn is_waiting(dl: &DownloadStatus, store: &Store) -> bool {
+    match dl.state {
+        DownloadState::Queued => true,
+        DownloadState::Paused => store.get(dl.id).is_some_and(|e| {
+            e.queue_held
+                || e.schedule_paused
+                || e.quota_paused
+                || ((e.start_at.is_some() || e.after.is_some()) && !e.dependency_failed)
+        }),
+        _ => false,
+    }
+}
+
+/// Sort downloads into queue order: the ones not waiting first, oldest
+/// first, then the waiting ones front first. Waiting downloads the store
+/// does not know yet go last, oldest first.
+pub fn sort_by_queue(downloads: &mut [DownloadStatus], store: &Store) {
+    downloads.sort_by_key(|d| {
+        if is_waiting(d, store) {
+            (
+                true,
+                store.queue_index(d.id).unwrap_or(usize::MAX),
+                d.created_at,
+            )
+        } else {
+            (false, 0, d.created_at)
+        }
+    });
+}
+
+/// Put ids into queue order, keeping the given order for unknown ones
+pub fn sort_ids_by_queue(ids: &mut [DownloadId], store: &Store) {
+    ids.sort_by_key(|id| store.queue_index(*id).unwrap_or(usize::MAX));
+}
+
+/// Move `id` within the queue of waiting downloads among `downloads`,
+/// returning its new 1-based position and the length of the queue
+pub fn move_in_queue(
+    store: &mut Store,
+    downloads: &[DownloadStatus],
+    id: DownloadId,
+    to: QueueMove,
+) -> Result<(usize, usize)> {
+    let mut waiting: Vec<DownloadStatus> = downloads
+        .iter()
+        .filter(|d| is_waiting(d, store))
+        .cloned()
+        .collect();
+    sort_by_queue(&mut waiting, store);
+    let mut order: Vec<DownloadId> = waiting.iter().map(|d| d.id).collect();
+
+    let Some(from) = order.iter().position(|other| *other == id) else {
+        if downloads.iter().any(|d| d.id == id) {
+            bail!(CategoryError::new(
+                Category::InvalidInput,
+                format!("{} is not waiting in the queue", id.to_gid())
+            ));
+        }
+        bail!(EngineError::NotFound(id.to_gid()));
+    };
+    let last = order.len() - 1;
+    let target = match to {
+        QueueMove::Top => 0,
+        QueueMove::Bottom => last,
+        QueueMove::Up => from.saturating_sub(1),
+        QueueMove::Down => (from + 1).min(last),
+        QueueMove::To(pos) => (pos - 1).min(last),
+    };
+
+    order.remove(from);
+    order.insert(target, id);
+    store.set_queue(order);
+    Ok((target + 1, last + 1))
+}
+
+/// Downloads to pause and to resume so that waiting ones start in queue order
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct QueuePlan {
+    pub hold: Vec<DownloadId>,
+    pub release: Vec<DownloadId>,
+}
+
+/// Give the free slots out of `max` to the waiting downloads at the front of
+/// the queue, higher priorities first, and hold back the rest.
+///
+/// The engine cannot pause a download still waiting for a slot, so one that
+/// is not its turn is held once it starts: `started` are the downloads that
+/// took a slot since the last check, which still count as waiting.
+pub fn plan_starts(
+    downloads: &[DownloadStatus],
+    store: &Store,
+    max: usize,
+    started: &HashSet<DownloadId>,
+) -> QueuePlan {
+    // Downloads that just started are not waiting any more, but still need
+    // their place in the queue
+    let mut sorted = downloads.to_vec();
+    sorted.sort_by_key(|d| {
+        (
+            Reverse(d.priority),
+            store.queue_index(d.id).unwrap_or(usize::MAX),
+            d.created_at,
+        )
+    });
+
+    let held = |d: &DownloadStatus| {
+        d.state == DownloadState::Paused && store.get(d.id).is_some_and(|e| e.queue_held)
+    };
+    let just_started = |d: &DownloadStatus| d.state.is_active() && started.contains(&d.id);
+    let running = sorted
+        .iter()
+        .filter(|d| d.state.is_active() && !is_seeding(d) && !just_started(d))
+        .count();
+    let room = max.saturating_sub(running);
+
+    let mut plan = QueuePlan::default();
+    let waiting = sorted
+        .iter()
+        .filter(|d| d.state == DownloadState::Queued || held(d) || just_started(d));
+    for (i, dl) in waiting.enumerate() {
+        if i < room {
+            if held(dl) {
+                plan.release.push(dl.id);
+            }
+        } else if just_started(dl) {
+            plan.hold.push(dl.id);
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+    use gosh_dl::{DownloadKind, DownloadState};
+
+    fn queue(n: usize) -> Vec<DownloadStatus> {
+        (0..n)
+            .map(|i| {
+                let mut dl = status(DownloadKind::Http, DownloadState::Queued, 100, 0);
+                dl.created_at += chrono::Duration::seconds(i as i64);
+                dl
+            })
+            .collect()
+    }
+
+    fn order(store: &Store, downloads: &[DownloadStatus]) -> Vec<DownloadId> {
+        let mut sorted = downloads.to_vec();
+        sort_by_queue(&mut sorted, store);
+        sorted.iter().map(|d| d.id).collect()
+    }
+
+    #[test]
+    fn parses_positions() {
+        assert_eq!("top".parse::<QueueMove>().unwrap(), QueueMove::Top);
+        assert_eq!("Down".parse::<QueueMove>().unwrap(), QueueMove::Down);
+        assert_eq!("3".parse::<QueueMove>().unwrap(), QueueMove::To(3));
+        assert!("0".parse::<QueueMove>().is_err());
+        let err = "first".parse::<QueueMove>().unwrap_err();
+        assert_eq!(Category::of_error(&err), Category::Usage);
+    }
+
+    #[test]
+    fn moves_within_queue() {
+        let downloads = queue(4);
+        let ids: Vec<DownloadId> = downloads.iter().map(|d| d.id).collect();
+        let mut store = Store::default();
+
+        // Unknown downloads fall back to creation order
+        assert_eq!(order(&store, &downloads), ids);
+
+        let pos = move_in_queue(&mut store, &downloads, ids[3], QueueMove::Top).unwrap();
+        assert_eq!(pos, (1, 4));
+        assert_eq!(
+            order(&store, &downloads),
+            vec![ids[3], ids[0], ids[1], ids[2]]
+        );
+
+        move_in_queue(&mut store, &downloads, ids[3], QueueMove::Down).unwrap();
+        move_in_queue(&mut store, &downloads, ids[1], QueueMove::To(99)).unwrap();
+        assert_eq!(
+            order(&store, &downloads),
+            vec![ids[0], ids[3], ids[2], ids[1]]
+        );
+
+        // Downloads added later join at the back
+        let mut more = downloads.clone();
+        more.extend(queue(1));
+        assert_eq!(order(&store, &more)[4], more[4].id);
+    }
+
+    #[test]
+    fn positions_count_only_waiting_downloads() {
+        let mut downloads = queue(4);
+        downloads[0].state = DownloadState::Completed;
+        downloads[1].state = DownloadState::Downloading;
+        let ids: Vec<DownloadId> = downloads.iter().map(|d| d.id).collect();
+        let mut store = Store::default();
+
+        // Second of the two waiting, not behind the finished downloads
+        let pos = move_in_queue(&mut store, &downloads, ids[2], QueueMove::To(2)).unwrap();
+        assert_eq!(pos, (2, 2));
+        assert_eq!(
+            order(&store, &downloads),
+            vec![ids[0], ids[1], ids[3], ids[2]]
+        );
+        assert_eq!(store.queue.len(), 2);
+
+        let err = move_in_queue(&mut store, &downloads, ids[0], QueueMove::Top).unwrap_err();
+        assert_eq!(Category::of_error(&err), Category::InvalidInput);
+
+        // Paused by gosh still waits; paused by hand does not
+        downloads[3].state = DownloadState::Paused;
+        assert_eq!(
+            move_in_queue(&mut store, &downloads, ids[2], QueueMove::Top).unwrap(),
+            (1, 1)
+        );
+        store.entry(ids[3]).schedule_paused = true;
+        assert_eq!(
+            move_in_queue(&mut store, &downloads, ids[2], QueueMove::Bottom).unwrap(),
+            (2, 2)
+        );
+    }
+
+    #[test]
+    fn moved_to_top_starts_first() {
+        let mut downloads = queue(4);
+        downloads[0].state = DownloadState::Downloading;
+        let ids: Vec<DownloadId> = downloads.iter().map(|d| d.id).collect();
+        let mut store = Store::default();
+        move_in_queue(&mut store, &downloads, ids[3], QueueMove::Top).unwrap();
+
+        // The only slot is taken, and queued downloads cannot be paused
+        let plan = plan_starts(&downloads, &store, 1, &HashSet::new());
+        assert_eq!(plan, QueuePlan::default());
+
+        // Once it frees up, the engine starts the next one in its own order,
+        // which is held back for the download moved to the top
+        let step = |downloads: &mut [DownloadStatus], store: &mut Store, start: usize| {
+            downloads[start].state = DownloadState::Downloading;
+            let plan = plan_starts(downloads, store, 1, &HashSet::from([ids[start]]));
+            for id in &plan.hold {
+                store.entry(*id).queue_held = true;
+                downloads.iter_mut().find(|d| d.id == *id).unwrap().state = DownloadState::Paused;
+            }
+            plan
+        };
+        downloads[0].state = DownloadState::Completed;
+        let plan = step(&mut downloads, &mut store, 1);
+        assert_eq!(plan.hold, vec![ids[1]]);
+        assert!(plan.release.is_empty());
+
+        // Its turn: nothing to do
+        let plan = step(&mut downloads, &mut store, 3);
+        assert_eq!(plan, QueuePlan::default());
+
+        // The held download goes before the one the engine started instead
+        downloads[3].state = DownloadState::Completed;
+        let plan = step(&mut downloads, &mut store, 2);
+        assert_eq!(plan.hold, vec![ids[2]]);
+        assert_eq!(plan.release, vec![ids[1]]);
+
+        // Priority still comes first
+        downloads[2].priority = gosh_dl::DownloadPriority::High;
+        let plan = plan_starts(&downloads, &store, 1, &HashSet::new());
+        assert_eq!(plan.release, vec![ids[2]]);
+    }
+}
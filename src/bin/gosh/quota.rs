@@ -0,0 +1,358 @@
+//! Data-cap quotas
+//!
+//! Traffic is counted per local day in the CLI state file, from the engine's
+//! progress events, and summed over each cap's period. While a cap is used
+//! up the supervisor keeps the downloads it covers paused, and resumes them
+//! once the period resets. Downloads in an exempt category, or with an
+//! exempt tag, are neither counted nor paused.
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Months, NaiveDate, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::config::QuotaConfig;
+use crate::format::format_size;
+use crate::store::DownloadExtras;
+use crate::util::parse_speed;
+
+/// Traffic per local day
+pub type Usage = BTreeMap<NaiveDate, Traffic>;
+
+/// Days of usage kept; enough for the longest period
+const KEEP_DAYS: i64 = 40;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Traffic {
+    pub download: u64,
+    pub upload: u64,
+}
+
+impl Traffic {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn add(&mut self, other: Traffic) {
+        self.download += other.download;
+        self.upload += other.upload;
+    }
+
+    pub fn get(&self, direction: Direction) -> u64 {
+        match direction {
+            Direction::Download => self.download,
+            Direction::Upload => self.upload,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+pub const PERIODS: [Period; 3] = [Period::Daily, Period::Weekly, Period::Monthly];
+
+impl Period {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Download,
+    Upload,
+}
+
+impl Direction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Download => "download",
+            Self::Upload => "upload",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cap {
+    pub period: Period,
+    pub direction: Direction,
+    pub limit: u64,
+}
+
+impl fmt::Display for Cap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} cap of {}",
+            self.period.as_str(),
+            self.direction.as_str(),
+            format_size(self.limit)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quota {
+    pub caps: Vec<Cap>,
+    reset_day: u32,
+    week_start: Weekday,
+    exempt: Vec<String>,
+}
+
+impl Default for Quota {
+    fn default() -> Self {
+        Self {
+            caps: Vec::new(),
+            reset_day: 1,
+            week_start: Weekday::Mon,
+            exempt: Vec::new(),
+        }
+    }
+}
+
+impl Quota {
+    pub fn from_config(config: &QuotaConfig) -> Result<Self> {
+        let limits = [
+            (Period::Daily, Direction::Download, &config.daily_download),
+            (Period::Daily, Direction::Upload, &config.daily_upload),
+            (Period::Weekly, Direction::Download, &config.weekly_download),
+            (Period::Weekly, Direction::Upload, &config.weekly_upload),
+            (
+                Period::Monthly,
+                Direction::Download,
+                &config.monthly_download,
+            ),
+            (Period::Monthly, Direction::Upload, &config.monthly_upload),
+        ];
+        let mut caps = Vec::new();
+        for (period, direction, limit) in limits {
+            let Some(limit) = limit else {
+                continue;
+            };
+            let limit = parse_speed(limit).with_context(|| {
+                format!(
+                    "quota.{}_{} is not a valid size",
+                    period.as_str(),
+                    direction.as_str()
+                )
+            })?;
+            caps.push(Cap {
+                period,
+                direction,
+                limit,
+            });
+        }
+
+        if !(1..=28).contains(&config.reset_day) {
+            bail!("quota.reset_day must be between 1 and 28");
+        }
+        let week_start = config.week_start.trim().parse::<Weekday>().map_err(|_| {
+            anyhow::anyhow!(
+                "quota.week_start: Unknown day '{}'. Use mon-sun",
+                config.week_start
+            )
+        })?;
+
+        Ok(Self {
+            caps,
+            reset_day: config.reset_day,
+            week_start,
+            exempt: config
+                .exempt
+                .iter()
+                .map(|t| t.trim().to_lowercase())
+                .collect(),
+        })
+    }
+
+    /// First day of the period that `today` falls in
+    pub fn period_start(&self, period: Period, today: NaiveDate) -> NaiveDate {
+        match period {
+            Period::Daily => today,
+            Period::Weekly => {
+                let back = (7 + today.weekday().num_days_from_monday()
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                today - TimeDelta::days(back.into())
+            }
+            Period::Monthly => {
+                // reset_day is at most 28, so it exists in every month
+                let this_month = today.with_day(self.reset_day).unwrap_or(today);
+                if today >= this_month {
+                    this_month
+                } else {
+                    this_month - Months::new(1)
+                }
+            }
+        }
+    }
+
+    /// Day the period that `today` falls in ends (exclusive)
+    pub fn next_reset(&self, period: Period, today: NaiveDate) -> NaiveDate {
+        let start = self.period_start(period, today);
+        match period {
+            Period::Daily => start + TimeDelta::days(1),
+            Period::Weekly => start + TimeDelta::days(7),
+            Period::Monthly => start + Months::new(1),
+        }
+    }
+
+    /// Traffic counted so far in the period that `today` falls in
+    pub fn used(&self, usage: &Usage, period: Period, today: NaiveDate) -> Traffic {
+        let mut total = Traffic::default();
+        for traffic in usage
+            .range(self.period_start(period, today)..=today)
+            .map(|(_, t)| t)
+        {
+            total.add(*traffic);
+        }
+        total
+    }
+
+    /// Caps that are used up
+    pub fn exceeded(&self, usage: &Usage, today: NaiveDate) -> Vec<Cap> {
+        self.caps
+            .iter()
+            .filter(|cap| self.used(usage, cap.period, today).get(cap.direction) >= cap.limit)
+            .copied()
+            .collect()
+    }
+
+    pub fn exempt(&self) -> &[String] {
+        &self.exempt
+    }
+
+    /// Whether a download's traffic is left out of the quota
+    pub fn is_exempt(&self, extras: Option<&DownloadExtras>) -> bool {
+        extras.is_some_and(|e| self.exempt.iter().any(|tag| e.has_tag(tag)))
+    }
+}
+
+/// Add traffic to a day, dropping days too old to matter
+pub fn record(usage: &mut Usage, day: NaiveDate, traffic: Traffic) {
+    usage.entry(day).or_default().add(traffic);
+    let oldest = day - TimeDelta::days(KEEP_DAYS);
+    usage.retain(|d, _| *d >= oldest);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn periods_follow_reset_days() {
+        let quota = Quota::from_config(&QuotaConfig {
+            reset_day: 15,
+            week_start: "sat".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        // A Monday
+        let today = date(2026, 10, 19);
+
+        assert_eq!(quota.period_start(Period::Daily, today), today);
+        assert_eq!(
+            quota.period_start(Period::Weekly, today),
+            date(2026, 10, 17)
+        );
+        assert_eq!(quota.next_reset(Period::Weekly, today), date(2026, 10, 24));
+        assert_eq!(
+            quota.period_start(Period::Monthly, today),
+            date(2026, 10, 15)
+        );
+        assert_eq!(
+            quota.period_start(Period::Monthly, date(2026, 10, 14)),
+            date(2026, 9, 15)
+        );
+        assert_eq!(quota.next_reset(Period::Monthly, today), date(2026, 11, 15));
+    }
+
+    #[test]
+    fn caps_sum_usage_over_their_period() {
+        let quota = Quota::from_config(&QuotaConfig {
+            daily_download: Some("10K".to_string()),
+            weekly_upload: Some("1M".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let today = date(2026, 10, 21);
+        let mut usage = Usage::new();
+        record(
+            &mut usage,
+            date(2026, 10, 18),
+            Traffic {
+                download: 50_000,
+                upload: 1_000_000,
+            },
+        );
+        record(
+            &mut usage,
+            date(2026, 10, 19),
+            Traffic {
+                download: 0,
+                upload: 48_000,
+            },
+        );
+        record(
+            &mut usage,
+            today,
+            Traffic {
+                download: 10_000,
+                upload: 1_000,
+            },
+        );
+
+        assert_eq!(quota.used(&usage, Period::Weekly, today).upload, 49_000);
+        assert!(quota.exceeded(&usage, today).is_empty());
+
+        record(
+            &mut usage,
+            today,
+            Traffic {
+                download: 240,
+                upload: 1_000_000,
+            },
+        );
+        let exceeded = quota.exceeded(&usage, today);
+        assert_eq!(exceeded.len(), 2);
+        assert_eq!(exceeded[0].to_string(), "daily download cap of 10.0 KB");
+
+        record(&mut usage, date(2026, 12, 1), Traffic::default());
+        assert_eq!(usage.keys().next(), Some(&date(2026, 12, 1)));
+    }
+
+    #[test]
+    fn config_is_validated() {
+        let bad_size = QuotaConfig {
+            monthly_upload: Some("lots".to_string()),
+            ..Default::default()
+        };
+        assert!(Quota::from_config(&bad_size).is_err());
+        let bad_day = QuotaConfig {
+            reset_day: 31,
+            ..Default::default()
+        };
+        assert!(Quota::from_config(&bad_day).is_err());
+        let bad_week = QuotaConfig {
+            week_start: "weekdays".to_string(),
+            ..Default::default()
+        };
+        assert!(Quota::from_config(&bad_week).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! End-of-run summary for direct mode and `add --wait`
+//!
+//! Tracks each download from when it is added until it completes or fails,
+//! for the summary table printed at the end and the JSON `--report`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use gosh_dl::{DownloadEngine, DownloadId, DownloadProgress, DownloadState, DownloadStatus};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::exit_code::Category;
+
+/// How a download ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Completed,
+    Failed,
+    Interrupted,
+}
+
+/// What became of the `--checksum` check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumResult {
+    /// No checksum was given
+    None,
+    Verified,
+    Mismatch,
+    /// A checksum was given but the download did not get that far
+    NotChecked,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEntry {
+    /// `None` for an input that could not be added
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gid: Option<String>,
+    pub input: String,
+    pub outcome: Outcome,
+    /// Where the file is, once completed
+    pub path: Option<PathBuf>,
+    /// Bytes
+    pub size: Option<u64>,
+    /// Seconds from being added to finishing
+    pub elapsed: f64,
+    /// Bytes per second over the bytes fetched in this run
+    pub average_speed: Option<u64>,
+    /// Most connections (or peers, for torrents) seen at once
+    pub connections: u32,
+    pub checksum: ChecksumResult,
+    pub error: Option<String>,
+    /// Why it failed, as `gosh help exit-codes` names it
+    pub category: Option<Category>,
+}
+
+struct Tracked {
+    index: usize,
+    started: Instant,
+    /// Bytes already there when tracking started
+    initial: u64,
+    checksum: bool,
+    finished: bool,
+}
+
+/// Outcomes of the downloads in one run, in the order they were added
+#[derive(Default)]
+pub struct Report {
+    entries: Vec<ReportEntry>,
+    tracked: HashMap<DownloadId, Tracked>,
+}
+
+#[derive(Serialize)]
+struct ReportFile<'a> {
+    generated_at: DateTime<Utc>,
+    completed: usize,
+    failed: usize,
+    downloads: &'a [ReportEntry],
+}
+
+impl Report {
+    /// Start timing a download; `checksum` is whether one was given for it
+    pub fn track(&mut self, id: DownloadId, input: &str, checksum: bool, engine: &DownloadEngine) {
+        let initial = engine
+            .status(id)
+            .map_or(0, |status| status.progress.completed_size);
+        self.tracked.insert(
+            id,
+            Tracked {
+                index: self.entries.len(),
+                started: Instant::now(),
+                initial,
+                checksum,
+                finished: false,
+            },
+        );
+        self.entries.push(ReportEntry {
+            gid: Some(id.to_gid()),
+            input: input.to_string(),
+            outcome: Outcome::Interrupted,
+            path: None,
+            size: None,
+            elapsed: 0.0,
+            average_speed: None,
+            connections: 0,
+            checksum: if checksum {
+                ChecksumResult::NotChecked
+            } else {
+                ChecksumResult::None
+            },
+            error: None,
+            category: None,
+        });
+    }
+
+    /// Record an input the engine refused
+    pub fn failed_to_add(&mut self, input: &str, error: &str, category: Category) {
+        self.entries.push(ReportEntry {
+            gid: None,
+            input: input.to_string(),
+            outcome: Outcome::Failed,
+            path: None,
+            size: None,
+            elapsed: 0.0,
+            average_speed: None,
+            connections: 0,
+            checksum: ChecksumResult::None,
+            error: Some(error.to_string()),
+            category: Some(category),
+        });
+    }
+
+    pub fn progress(&mut self, id: DownloadId, progress: &DownloadProgress) {
+        if let Some(tracked) = self.tracked.get(&id) {
+            let entry = &mut self.entries[tracked.index];
+            entry.connections = entry.connections.max(progress.connections);
+        }
+    }
+
+    /// Record how a download ended; only the first call for it counts
+    pub fn finish(
+        &mut self,
+        id: DownloadId,
+        outcome: Outcome,
+        error: Option<&str>,
+        engine: &DownloadEngine,
+    ) {
+        let Some(tracked) = self.tracked.get_mut(&id) else {
+            return;
+        };
+        if tracked.finished {
+            return;
+        }
+        tracked.finished = true;
+
+        let elapsed = tracked.started.elapsed().as_secs_f64();
+        let entry = &mut self.entries[tracked.index];
+        entry.outcome = outcome;
+        entry.elapsed = elapsed;
+        entry.error = error.map(str::to_string);
+        if outcome == Outcome::Failed {
+            entry.category = Some(Category::Error);
+        }
+
+        let Some(status) = engine.status(id) else {
+            return;
+        };
+        if let (Outcome::Failed, DownloadState::Error { kind, .. }) = (outcome, &status.state) {
+            entry.category = Some(Category::of_kind(kind));
+        }
+        let progress = &status.progress;
+        entry.connections = entry.connections.max(progress.connections);
+        entry.size = progress.total_size;
+        if outcome == Outcome::Completed {
+            entry.size = entry.size.or(Some(progress.completed_size));
+            entry.path = Some(output_path(&status));
+            let fetched = progress.completed_size.saturating_sub(tracked.initial);
+            if elapsed > 0.0 {
+                entry.average_speed = Some((fetched as f64 / elapsed) as u64);
+            }
+        }
+        if tracked.checksum {
+            entry.checksum = match (&status.state, outcome) {
+                (_, Outcome::Completed) => ChecksumResult::Verified,
+                (DownloadState::Error { kind, .. }, _) if kind.contains("HashMismatch") => {
+                    ChecksumResult::Mismatch
+                }
+                _ => ChecksumResult::NotChecked,
+            };
+        }
+    }
+
+    /// Mark every download still running as interrupted
+    pub fn interrupt_remaining(&mut self, engine: &DownloadEngine) {
+        let ids: Vec<DownloadId> = self
+            .tracked
+            .iter()
+            .filter(|(_, tracked)| !tracked.finished)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            self.finish(id, Outcome::Interrupted, None, engine);
+        }
+    }
+
+    pub fn entries(&self) -> &[ReportEntry] {
+        &self.entries
+    }
+
+    /// What the failed downloads have in common, for the exit code
+    pub fn failure(&self) -> Option<Category> {
+        let failures: Vec<Category> = self.entries.iter().filter_map(|e| e.category).collect();
+        Category::of_failures(&failures)
+    }
+
+    /// Write the report as JSON to `path`
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let count = |outcome| self.entries.iter().filter(|e| e.outcome == outcome).count();
+        let file = ReportFile {
+            generated_at: Utc::now(),
+            completed: count(Outcome::Completed),
+            failed: count(Outcome::Failed),
+            downloads: &self.entries,
+        };
+        let json = serde_json::to_string_pretty(&file)?;
+        std::fs::write(path, json + "\n")
+            .with_context(|| format!("Failed to write report {}", path.display()))
+    }
+}
+
+/// Where a download's file (or torrent directory) is
+pub fn output_path(status: &DownloadStatus) -> PathBuf {
+    let meta = &status.metadata;
+    meta.save_dir
+        .join(meta.filename.as_deref().unwrap_or(&meta.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_inputs_are_reported_without_a_gid() {
+        let mut report = Report::default();
+        report.failed_to_add(
+            "http://example.com/a.iso",
+            "bad url",
+            Category::InvalidInput,
+        );
+
+        let entry = &report.entries()[0];
+        assert_eq!(entry.outcome, Outcome::Failed);
+        assert_eq!(entry.error.as_deref(), Some("bad url"));
+        assert_eq!(report.failure(), Some(Category::InvalidInput));
+
+        let json = serde_json::to_value(entry).unwrap();
+        assert!(json.get("gid").is_none());
+        assert_eq!(json["outcome"], "failed");
+        assert_eq!(json["checksum"], "none");
+        assert_eq!(json["category"], "invalid_input");
+    }
+}
@@ -0,0 +1,278 @@
+//! Retrying failed downloads
+//!
+//! The engine only resumes paused downloads; one that failed stays failed.
+//! A retry adds it again from the URL and options the engine kept, under a
+//! new ID, moves the CLI's extras (category, tags, seeding limits, queue
+//! place) over and then drops the failed record, keeping any files written.
+//! Torrents added from a file are added again from the torrent data the
+//! engine stored; only without it do they come back as a magnet link of
+//! their info hash, with their metadata fetched again from peers.
+//!
+//! With `[engine] auto_retry` set, the TUI and `gosh top` do this by
+//! themselves for retryable failures, waiting longer after each attempt.
+
+use anyhow::{bail, Context, Result};
+use chrono::TimeDelta;
+use gosh_dl::{
+    DownloadEngine, DownloadId, DownloadKind, DownloadOptions, DownloadState, DownloadStatus,
+};
+use std::path::Path;
+
+use crate::config::AutoRetryConfig;
+use crate::input::url_parser::{parse_input, ParsedInput};
+use crate::store::Store;
+use crate::torrent_file::MetadataReader;
+use crate::util::parse_duration;
+
+/// When to retry failed downloads automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoRetry {
+    /// Seconds before the first attempt
+    interval: u64,
+    /// Longest wait in seconds
+    max_interval: u64,
+    pub max_attempts: u32,
+}
+
+impl AutoRetry {
+    pub fn from_config(config: &AutoRetryConfig) -> Result<Self> {
+        let interval = parse_duration(&config.interval)
+            .context("engine.auto_retry.interval is not a valid duration")?;
+        let max_interval = parse_duration(&config.max_interval)
+            .context("engine.auto_retry.max_interval is not a valid duration")?;
+        if interval == 0 {
+            bail!("engine.auto_retry.interval must be at least 1s");
+        }
+        if config.max_attempts == 0 {
+            bail!("engine.auto_retry.max_attempts must be at least 1");
+        }
+        Ok(Self {
+            interval,
+            max_interval: max_interval.max(interval),
+            max_attempts: config.max_attempts,
+        })
+    }
+
+    /// Wait before the next attempt, after `attempts` earlier ones
+    pub fn delay(&self, attempts: u32) -> TimeDelta {
+        let secs = self
+            .interval
+            .saturating_mul(1 << attempts.min(32))
+            .min(self.max_interval);
+        TimeDelta::seconds(secs as i64)
+    }
+}
+
+pub fn is_failed(dl: &DownloadStatus) -> bool {
+    matches!(dl.state, DownloadState::Error { .. })
+}
+
+/// Whether the engine thinks the failure may go away by trying again
+pub fn is_retryable(dl: &DownloadStatus) -> bool {
+    matches!(
+        dl.state,
+        DownloadState::Error {
+            retryable: true,
+            ..
+        }
+    )
+}
+
+/// What to add again: the download's own source, or `new_url` in its place
+pub fn source(dl: &DownloadStatus, new_url: Option<&str>) -> Result<ParsedInput> {
+    let is_http = dl.kind == DownloadKind::Http;
+    if let Some(url) = new_url {
+        let input = parse_input(url)?;
+        match (is_http, &input) {
+            (true, ParsedInput::Http(_))
+            | (false, ParsedInput::Magnet(_))
+            | (false, ParsedInput::TorrentFile(_)) => {}
+            (true, _) => bail!("--new-url must be an HTTP URL for an HTTP download"),
+            (false, _) => bail!("--new-url must be a magnet link or torrent file for a torrent"),
+        }
+        return Ok(input);
+    }
+
+    let meta = &dl.metadata;
+    if is_http {
+        let url = meta
+            .url
+            .as_deref()
+            .context("Download has no URL to retry")?;
+        return Ok(ParsedInput::Http(url.to_string()));
+    }
+    if let Some(ref magnet) = meta.magnet_uri {
+        return Ok(ParsedInput::Magnet(magnet.clone()));
+    }
+    let hash = meta
+        .info_hash
+        .as_deref()
+        .context("Torrent has no info hash to retry; use --new-url")?;
+    Ok(ParsedInput::Magnet(format!(
+        "magnet:?xt=urn:btih:{}&dn={}",
+        hash,
+        urlencoding::encode(&meta.name)
+    )))
+}
+
+/// Options the download was added with, as far as the engine kept them
+pub fn options(dl: &DownloadStatus) -> DownloadOptions {
+    let meta = &dl.metadata;
+    DownloadOptions {
+        priority: dl.priority,
+        save_dir: Some(meta.save_dir.clone()),
+        filename: meta
+            .filename
+            .clone()
+            .filter(|_| dl.kind == DownloadKind::Http),
+        user_agent: meta.user_agent.clone(),
+        referer: meta.referer.clone(),
+        headers: meta.headers.clone(),
+        cookies: (!meta.cookies.is_empty()).then(|| meta.cookies.clone()),
+        checksum: meta.checksum.clone(),
+        mirrors: meta.mirrors.clone(),
+        ..Default::default()
+    }
+}
+
+/// The torrent data the engine stored for a torrent added from a file, so
+/// that it comes back with its trackers rather than as a bare magnet link
+async fn stored_torrent(database_path: &Path, dl: &DownloadStatus) -> Option<Vec<u8>> {
+    if dl.kind == DownloadKind::Http || dl.metadata.magnet_uri.is_some() {
+        return None;
+    }
+    let loaded = match MetadataReader::open(database_path).await {
+        Ok(reader) => reader.load(dl.id).await,
+        Err(e) => Err(e),
+    };
+    loaded
+        .inspect_err(|e| tracing::warn!("Retrying {} as a magnet link: {:#}", dl.id.to_gid(), e))
+        .ok()
+        .flatten()
+}
+
+/// Add a failed download again and retire the old one, returning the new ID.
+/// `attempt` counts automatic retries; it is 0 for one made by hand.
+pub async fn retry(
+    engine: &DownloadEngine,
+    state_path: &Path,
+    database_path: &Path,
+    dl: &DownloadStatus,
+    new_url: Option<&str>,
+    attempt: u32,
+) -> Result<DownloadId> {
+    let input = source(dl, new_url)?;
+    let options = options(dl);
+    let stored = match new_url {
+        Some(_) => None,
+        None => stored_torrent(database_path, dl).await,
+    };
+    let id = match (stored, &input) {
+        (Some(data), _) => engine.add_torrent(&data, options).await?,
+        (None, ParsedInput::Http(url)) => engine.add_http(url, options).await?,
+        (None, ParsedInput::Magnet(uri)) => engine.add_magnet(uri, options).await?,
+        (None, ParsedInput::TorrentFile(path)) => {
+            let data = tokio::fs::read(path)
+                .await
+                .with_context(|| format!("Failed to read torrent file: {}", path.display()))?;
+            engine.add_torrent(&data, options).await?
+        }
+    };
+
+    Store::update(state_path, |store| {
+        store.rename(dl.id, id);
+        if attempt > 0 {
+            store.entry(id).retry_attempts = attempt;
+        }
+    })?;
+    if let Err(e) = engine.cancel(dl.id, false).await {
+        tracing::warn!(
+            "Failed to remove retried download {}: {}",
+            dl.id.to_gid(),
+            e
+        );
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+
+    fn failed(kind: DownloadKind, retryable: bool) -> DownloadStatus {
+        let state = DownloadState::Error {
+            kind: "network".to_string(),
+            message: "connection reset".to_string(),
+            retryable,
+        };
+        status(kind, state, 100, 10)
+    }
+
+    #[test]
+    fn sources() {
+        let mut http = failed(DownloadKind::Http, false);
+        http.metadata.url = Some("https://example.com/a.iso".to_string());
+        assert!(is_failed(&http) && !is_retryable(&http));
+        assert!(matches!(
+            source(&http, None).unwrap(),
+            ParsedInput::Http(url) if url == "https://example.com/a.iso"
+        ));
+        assert!(matches!(
+            source(&http, Some("https://mirror.org/a.iso")).unwrap(),
+            ParsedInput::Http(url) if url == "https://mirror.org/a.iso"
+        ));
+        assert!(source(&http, Some("magnet:?xt=urn:btih:abc")).is_err());
+
+        let mut torrent = failed(DownloadKind::Torrent, true);
+        torrent.metadata.name = "big file".to_string();
+        assert!(source(&torrent, None).is_err());
+        torrent.metadata.info_hash = Some("abc".to_string());
+        assert!(matches!(
+            source(&torrent, None).unwrap(),
+            ParsedInput::Magnet(uri) if uri == "magnet:?xt=urn:btih:abc&dn=big%20file"
+        ));
+        assert!(source(&torrent, Some("https://example.com/a.torrent")).is_err());
+    }
+
+    #[tokio::test]
+    async fn torrent_files_come_back_from_stored_data() {
+        use gosh_dl::{SqliteStorage, Storage};
+
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let database = tempdir.path().join("gosh.db");
+        let mut torrent = failed(DownloadKind::Torrent, true);
+        let storage = SqliteStorage::new(&database).await.unwrap();
+        storage.save_download(&torrent).await.unwrap();
+        storage
+            .save_torrent_data(torrent.id, b"d8:announce3:urle")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            stored_torrent(&database, &torrent).await.as_deref(),
+            Some(&b"d8:announce3:urle"[..])
+        );
+        // Nothing stored for it: falls back to a magnet link
+        let other = failed(DownloadKind::Torrent, true);
+        assert_eq!(stored_torrent(&database, &other).await, None);
+        // Magnets are added again from their link
+        torrent.metadata.magnet_uri = Some("magnet:?xt=urn:btih:abc".to_string());
+        assert_eq!(stored_torrent(&database, &torrent).await, None);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = AutoRetry::from_config(&AutoRetryConfig::default()).unwrap();
+        let minutes = |attempts| policy.delay(attempts).num_minutes();
+        assert_eq!([minutes(0), minutes(1), minutes(2)], [10, 20, 40]);
+        assert_eq!(minutes(3), 60);
+        assert_eq!(minutes(40), 60);
+
+        let bad = AutoRetryConfig {
+            interval: "0s".to_string(),
+            ..Default::default()
+        };
+        assert!(AutoRetry::from_config(&bad).is_err());
+    }
+}
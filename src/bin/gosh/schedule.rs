@@ -0,0 +1,381 @@
+//! Time-of-day rules for bandwidth limits and queue actions
+//!
+//! The engine's own schedule works in whole hours, so rules are evaluated
+//! here to the minute. The supervisor hands the limits of the rule in force
+//! to the engine and carries out its actions.
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike, Weekday};
+use std::fmt;
+
+use crate::config::{ScheduleConfig, ScheduleRuleConfig};
+use crate::format::format_speed;
+use crate::util::parse_speed;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// A time of day, in minutes since midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ClockTime(u16);
+
+impl ClockTime {
+    pub fn of(at: NaiveDateTime) -> Self {
+        Self((at.hour() * 60 + at.minute()) as u16)
+    }
+
+    /// The start of hour `hour`; 24 wraps to midnight
+    pub fn from_hour(hour: u8) -> Self {
+        Self((hour as u16 % 24) * 60)
+    }
+}
+
+impl std::str::FromStr for ClockTime {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parsed = s.trim().split_once(':').and_then(|(h, m)| {
+            let (h, m) = (h.parse::<u16>().ok()?, m.parse::<u16>().ok()?);
+            (h < 24 && m < 60 && s.trim().len() <= 5).then_some(Self(h * 60 + m))
+        });
+        parsed.with_context(|| format!("Invalid time '{}'. Use HH:MM", s))
+    }
+}
+
+impl fmt::Display for ClockTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / 60, self.0 % 60)
+    }
+}
+
+/// Something a rule does besides limiting bandwidth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleAction {
+    /// Pause every running download when the rule starts
+    PauseAll,
+    /// Resume every paused download when the rule starts
+    ResumeAll,
+    /// Keep at most this many downloads running while the rule is in force
+    MaxConcurrentDownloads(usize),
+    /// Pause seeding torrents while the rule is in force
+    SeedingOff,
+}
+
+impl std::str::FromStr for RuleAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized: String = s.split_whitespace().collect();
+        match normalized.to_lowercase().as_str() {
+            "pause_all" => Ok(Self::PauseAll),
+            "resume_all" => Ok(Self::ResumeAll),
+            "seeding=off" => Ok(Self::SeedingOff),
+            other => match other.strip_prefix("max_concurrent_downloads=") {
+                Some(n) => match n.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(Self::MaxConcurrentDownloads(n)),
+                    _ => bail!("max_concurrent_downloads must be at least 1, got '{}'", n),
+                },
+                None => bail!(
+                    "Unknown action '{}'. Use pause_all, resume_all, max_concurrent_downloads=N or seeding=off",
+                    s
+                ),
+            },
+        }
+    }
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PauseAll => f.write_str("pause_all"),
+            Self::ResumeAll => f.write_str("resume_all"),
+            Self::MaxConcurrentDownloads(n) => write!(f, "max_concurrent_downloads={}", n),
+            Self::SeedingOff => f.write_str("seeding=off"),
+        }
+    }
+}
+
+/// A validated schedule rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub start: ClockTime,
+    /// Exclusive; before `start` means the rule runs past midnight, equal to
+    /// `start` means all day
+    pub end: ClockTime,
+    /// Days the rule starts on (empty = every day)
+    pub days: Vec<Weekday>,
+    pub download_limit: Option<u64>,
+    pub upload_limit: Option<u64>,
+    pub actions: Vec<RuleAction>,
+}
+
+impl Rule {
+    pub fn from_config(config: &ScheduleRuleConfig) -> Result<Self> {
+        let speed = |s: &Option<String>| s.as_deref().map(parse_speed).transpose();
+        Ok(Self {
+            start: config.start.parse()?,
+            end: config.end.parse()?,
+            days: parse_days(&config.days)?,
+            download_limit: speed(&config.download_limit).context("Invalid download_limit")?,
+            upload_limit: speed(&config.upload_limit).context("Invalid upload_limit")?,
+            actions: config
+                .actions
+                .iter()
+                .map(|a| a.parse())
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Whether the rule is in force at `at` (local time)
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        let now = ClockTime::of(at);
+        let today = self.runs_on(at.weekday());
+        let yesterday = self.runs_on(at.weekday().pred());
+        if self.start < self.end {
+            today && now >= self.start && now < self.end
+        } else if self.start > self.end {
+            (today && now >= self.start) || (yesterday && now < self.end)
+        } else {
+            today
+        }
+    }
+
+    fn runs_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    pub fn has(&self, action: RuleAction) -> bool {
+        self.actions.contains(&action)
+    }
+
+    pub fn max_concurrent(&self) -> Option<usize> {
+        self.actions.iter().find_map(|a| match a {
+            RuleAction::MaxConcurrentDownloads(n) => Some(*n),
+            _ => None,
+        })
+    }
+
+    /// The engine rule carrying this rule's limits, valid all day
+    pub fn engine_rule(&self) -> gosh_dl::ScheduleRule {
+        gosh_dl::ScheduleRule::new(0, 23, Vec::new(), self.download_limit, self.upload_limit)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} {}", self.start, self.end, format_days(&self.days))?;
+        if let Some(limit) = self.download_limit {
+            write!(f, ", down {}/s", format_speed(limit))?;
+        }
+        if let Some(limit) = self.upload_limit {
+            write!(f, ", up {}/s", format_speed(limit))?;
+        }
+        for action in &self.actions {
+            write!(f, ", {}", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// All rules; the first one in force wins
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schedule {
+    pub rules: Vec<Rule>,
+}
+
+impl Schedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                Rule::from_config(rule).with_context(|| format!("schedule.rules[{}]", i))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Index of the rule in force at `at`
+    pub fn active(&self, at: NaiveDateTime) -> Option<usize> {
+        self.rules.iter().position(|rule| rule.contains(at))
+    }
+
+    /// When the rule in force next changes, and the rule taking over
+    pub fn next_change(&self, at: NaiveDateTime) -> Option<(NaiveDateTime, Option<usize>)> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let current = self.active(at);
+        // Rules repeat weekly, so a change comes within eight days or never
+        let mut minute = at
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(at);
+        for _ in 0..8 * MINUTES_PER_DAY as u32 {
+            minute += Duration::minutes(1);
+            let next = self.active(minute);
+            if next != current {
+                return Some((minute, next));
+            }
+        }
+        None
+    }
+
+    /// Engine rules for the limits in force at `at`
+    pub fn engine_rules(&self, at: NaiveDateTime) -> Vec<gosh_dl::ScheduleRule> {
+        self.active(at)
+            .map(|i| vec![self.rules[i].engine_rule()])
+            .unwrap_or_default()
+    }
+}
+
+/// Parse `days`: `all`, `weekdays`, `weekends` or a list like `mon,wed,fri`
+pub fn parse_days(s: &str) -> Result<Vec<Weekday>> {
+    match s.trim().to_lowercase().as_str() {
+        "all" | "" => Ok(Vec::new()),
+        "weekdays" => Ok(vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ]),
+        "weekends" => Ok(vec![Weekday::Sat, Weekday::Sun]),
+        other => other
+            .split(',')
+            .map(|d| match d.trim() {
+                "mon" => Ok(Weekday::Mon),
+                "tue" => Ok(Weekday::Tue),
+                "wed" => Ok(Weekday::Wed),
+                "thu" => Ok(Weekday::Thu),
+                "fri" => Ok(Weekday::Fri),
+                "sat" => Ok(Weekday::Sat),
+                "sun" => Ok(Weekday::Sun),
+                _ => bail!(
+                    "Unknown day '{}'. Use mon-sun, weekdays, weekends or all",
+                    d.trim()
+                ),
+            })
+            .collect(),
+    }
+}
+
+pub fn format_days(days: &[Weekday]) -> String {
+    const WEEKDAYS: [Weekday; 5] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ];
+    let mut sorted = days.to_vec();
+    sorted.sort_by_key(|d| d.num_days_from_monday());
+    sorted.dedup();
+    match sorted.as_slice() {
+        [] => "all".to_string(),
+        s if s.len() == 7 => "all".to_string(),
+        s if s == WEEKDAYS => "weekdays".to_string(),
+        [Weekday::Sat, Weekday::Sun] => "weekends".to_string(),
+        s => s
+            .iter()
+            .map(|d| d.to_string().to_lowercase())
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2026-10-19 is a Monday
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn rule(start: &str, end: &str, days: &str) -> Rule {
+        Rule::from_config(&ScheduleRuleConfig {
+            start: start.to_string(),
+            end: end.to_string(),
+            days: days.to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_times_and_actions() {
+        assert_eq!("07:05".parse::<ClockTime>().unwrap().to_string(), "07:05");
+        assert!("24:00".parse::<ClockTime>().is_err());
+        assert!("7".parse::<ClockTime>().is_err());
+
+        assert_eq!(
+            "max_concurrent_downloads = 2"
+                .parse::<RuleAction>()
+                .unwrap(),
+            RuleAction::MaxConcurrentDownloads(2)
+        );
+        assert!("max_concurrent_downloads=0".parse::<RuleAction>().is_err());
+        assert!("stop".parse::<RuleAction>().is_err());
+
+        assert!(parse_days("mon,fri").is_ok());
+        assert!(parse_days("mon,funday").is_err());
+        assert_eq!(format_days(&parse_days("weekdays").unwrap()), "weekdays");
+    }
+
+    #[test]
+    fn reads_whole_hour_rules() {
+        let config: ScheduleConfig = toml::from_str(
+            "[[rules]]\nstart_hour = 22\nend_hour = 6\ndays = \"all\"\n\
+             [[rules]]\nstart_hour = 0\nend_hour = 23\ndays = \"all\"\n",
+        )
+        .unwrap();
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        // end_hour covered the whole hour
+        assert_eq!(schedule.rules[0].to_string(), "22:00-07:00 all");
+        assert!(schedule.rules[0].contains(at(20, 6, 59)));
+        assert_eq!(schedule.rules[1].to_string(), "00:00-00:00 all");
+    }
+
+    #[test]
+    fn overnight_range_belongs_to_its_start_day() {
+        let night = rule("22:30", "06:15", "fri");
+
+        assert!(!night.contains(at(23, 22, 29)));
+        assert!(night.contains(at(23, 22, 30)));
+        // Saturday morning is still Friday night
+        assert!(night.contains(at(24, 6, 14)));
+        assert!(!night.contains(at(24, 6, 15)));
+        assert!(!night.contains(at(24, 22, 30)));
+        // Friday morning belongs to Thursday night, which is not scheduled
+        assert!(!night.contains(at(23, 1, 0)));
+
+        assert!(rule("00:00", "00:00", "all").contains(at(20, 13, 0)));
+    }
+
+    #[test]
+    fn finds_next_change() {
+        let schedule = Schedule {
+            rules: vec![
+                rule("09:00", "17:30", "weekdays"),
+                rule("08:00", "20:00", "all"),
+            ],
+        };
+
+        assert_eq!(schedule.active(at(19, 12, 0)), Some(0));
+        assert_eq!(
+            schedule.next_change(at(19, 12, 0)),
+            Some((at(19, 17, 30), Some(1)))
+        );
+        assert_eq!(
+            schedule.next_change(at(24, 21, 0)),
+            Some((at(25, 8, 0), Some(1)))
+        );
+        assert_eq!(Schedule::default().next_change(at(19, 12, 0)), None);
+    }
+}
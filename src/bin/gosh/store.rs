@@ -0,0 +1,409 @@
+//! CLI-side persistent state
+//!
+//! The engine only persists what it knows about. Per-download settings that
+//! the CLI enforces itself (seeding limits and the like) are kept in a small
+//! JSON file next to the engine database, keyed by download UUID.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use gosh_dl::DownloadId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+use crate::hold::AfterFailure;
+use crate::quota::Usage;
+use crate::util::{parse_duration, validate_seed_ratio};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Store {
+    /// Per-download extras, keyed by UUID
+    pub downloads: BTreeMap<String, DownloadExtras>,
+
+    /// Queue order as UUIDs, front first (see `queue`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub queue: Vec<String>,
+
+    /// Traffic counted towards the quota, per local day (see `quota`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadExtras {
+    /// Seed ratio limit for this torrent (overrides engine.seed_ratio)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_ratio: Option<f64>,
+
+    /// Seeding time limit in seconds (overrides torrent.seed_time)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_time: Option<u64>,
+
+    /// Seeding idle limit in seconds (overrides torrent.seed_idle)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_idle: Option<u64>,
+
+    /// Write a .torrent once the magnet's metadata has been fetched
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub save_torrent: bool,
+
+    /// Where to write it (file or directory; None = next to the download)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub torrent_path: Option<PathBuf>,
+
+    /// Keep the download paused until this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<DateTime<Utc>>,
+
+    /// Keep the download paused until this download completes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<DownloadId>,
+
+    /// What to do if the `after` download fails
+    #[serde(skip_serializing_if = "is_default")]
+    pub after_failure: AfterFailure,
+
+    /// The `after` download failed and `after_failure` is `fail`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dependency_failed: bool,
+
+    /// Paused by a schedule rule; resumed when the rule ends
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub schedule_paused: bool,
+
+    /// Paused because a quota cap is used up; resumed when it resets
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub quota_paused: bool,
+
+    /// Paused until the downloads ahead of it in the queue have started
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub queue_held: bool,
+
+    /// Category the download was added under (see `category`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    /// Tags given with `--tag`, lowercase
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Automatic retries made so far (see `retry`)
+    #[serde(skip_serializing_if = "is_default")]
+    pub retry_attempts: u32,
+
+    /// When the failed download is retried next
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_retry: Option<DateTime<Utc>>,
+
+    /// When the download failed, which the engine does not record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_at: Option<DateTime<Utc>>,
+
+    /// When the torrent started seeding, for the seed time limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seeding_since: Option<DateTime<Utc>>,
+
+    /// When the seeding torrent last uploaded, for the idle limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_upload: Option<DateTime<Utc>>,
+
+    /// Bytes uploaded, for the per-download seed ratio
+    #[serde(skip_serializing_if = "is_default")]
+    pub uploaded: u64,
+}
+
+impl DownloadExtras {
+    /// Build the seeding limits given on the command line, validating them
+    pub fn seed_limits(
+        seed_ratio: Option<f64>,
+        seed_time: Option<&str>,
+        seed_idle: Option<&str>,
+    ) -> Result<Self> {
+        Ok(Self {
+            seed_ratio: validate_seed_ratio(seed_ratio)?,
+            seed_time: seed_time
+                .map(parse_duration)
+                .transpose()
+                .context("Invalid --seed-time")?,
+            seed_idle: seed_idle
+                .map(parse_duration)
+                .transpose()
+                .context("Invalid --seed-idle")?,
+            ..Default::default()
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Tags to filter by: the category, then any `--tag`s
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.category
+            .as_deref()
+            .into_iter()
+            .chain(self.tags.iter().map(String::as_str))
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.labels().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Drop any `--start-at`/`--after` hold
+    pub fn clear_hold(&mut self) {
+        self.start_at = None;
+        self.after = None;
+        self.after_failure = AfterFailure::default();
+        self.dependency_failed = false;
+    }
+}
+
+impl Store {
+    /// Load the store, returning an empty one if the file does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read state file: {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse state file: {}", path.display()))
+    }
+
+    /// Write the store atomically (unique temp file + rename)
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let dir = directory(path);
+        std::fs::create_dir_all(dir)?;
+        let mut tmp = NamedTempFile::new_in(dir)
+            .with_context(|| format!("Failed to write state file in {}", dir.display()))?;
+        serde_json::to_writer_pretty(&mut tmp, self)?;
+        tmp.persist(path)
+            .with_context(|| format!("Failed to write state file: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Load, modify and save the store in one step.
+    ///
+    /// A lock on the store is held throughout, so that changes made by other
+    /// `gosh` invocations at the same time are not lost. Nothing is written
+    /// if `f` leaves the store as it was.
+    pub fn update<T>(path: &Path, f: impl FnOnce(&mut Store) -> T) -> Result<T> {
+        let _lock = lock(path)?;
+        let mut store = Self::load(path)?;
+        let before = store.clone();
+        let result = f(&mut store);
+        if store != before {
+            store.save(path)?;
+        }
+        Ok(result)
+    }
+
+    pub fn get(&self, id: DownloadId) -> Option<&DownloadExtras> {
+        self.downloads.get(&key(id))
+    }
+
+    pub fn entry(&mut self, id: DownloadId) -> &mut DownloadExtras {
+        self.downloads.entry(key(id)).or_default()
+    }
+
+    pub fn remove(&mut self, id: DownloadId) {
+        self.downloads.remove(&key(id));
+    }
+
+    /// Drop a download's hold so it may start now
+    pub fn clear_hold(&mut self, id: DownloadId) {
+        if let Some(extras) = self.downloads.get_mut(&key(id)) {
+            extras.clear_hold();
+            if extras.is_empty() {
+                self.remove(id);
+            }
+        }
+    }
+
+    /// Stop holding a download back for its turn in the queue
+    pub fn clear_queue_hold(&mut self, id: DownloadId) {
+        if let Some(extras) = self.downloads.get_mut(&key(id)) {
+            extras.queue_held = false;
+            if extras.is_empty() {
+                self.remove(id);
+            }
+        }
+    }
+
+    /// Hand a download's extras, queue place and dependents over to a new ID
+    pub fn rename(&mut self, from: DownloadId, to: DownloadId) {
+        if let Some(mut extras) = self.downloads.remove(&key(from)) {
+            extras.schedule_paused = false;
+            extras.quota_paused = false;
+            extras.queue_held = false;
+            extras.retry_attempts = 0;
+            extras.next_retry = None;
+            extras.failed_at = None;
+            extras.seeding_since = None;
+            extras.last_upload = None;
+            extras.uploaded = 0;
+            self.downloads.insert(key(to), extras);
+        }
+        let (from_key, to_key) = (key(from), key(to));
+        if let Some(k) = self.queue.iter_mut().find(|k| **k == from_key) {
+            *k = to_key;
+        }
+        for extras in self.downloads.values_mut() {
+            if extras.after == Some(from) {
+                extras.after = Some(to);
+            }
+        }
+    }
+
+    pub fn has_tag(&self, id: DownloadId, tag: &str) -> bool {
+        self.get(id).is_some_and(|extras| extras.has_tag(tag))
+    }
+
+    /// Index of a download in the stored queue order
+    pub fn queue_index(&self, id: DownloadId) -> Option<usize> {
+        let key = key(id);
+        self.queue.iter().position(|k| *k == key)
+    }
+
+    pub fn set_queue(&mut self, ids: impl IntoIterator<Item = DownloadId>) {
+        self.queue = ids.into_iter().map(key).collect();
+    }
+}
+
+fn directory(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Take the advisory lock that guards read-modify-write of the store; it is
+/// released when the returned file is dropped
+fn lock(path: &Path) -> Result<File> {
+    let lock_path = path.with_extension("json.lock");
+    std::fs::create_dir_all(directory(path))?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open lock file: {}", lock_path.display()))?;
+    file.lock_exclusive()
+        .with_context(|| format!("Failed to lock state file: {}", lock_path.display()))?;
+    Ok(file)
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+fn key(id: DownloadId) -> String {
+    id.as_uuid().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn missing_file_loads_empty() {
+        let tempdir = TempDir::new().unwrap();
+        let store = Store::load(&tempdir.path().join("state.json")).unwrap();
+        assert!(store.downloads.is_empty());
+    }
+
+    #[test]
+    fn update_round_trips() {
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("state.json");
+        let id = DownloadId::new();
+
+        Store::update(&path, |store| {
+            store.entry(id).seed_time = Some(3600);
+        })
+        .unwrap();
+
+        let store = Store::load(&path).unwrap();
+        assert_eq!(store.get(id).unwrap().seed_time, Some(3600));
+
+        Store::update(&path, |store| store.remove(id)).unwrap();
+        assert!(Store::load(&path).unwrap().get(id).is_none());
+    }
+
+    #[test]
+    fn concurrent_updates_keep_every_change() {
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("state.json");
+        let ids: Vec<DownloadId> = (0..8).map(|_| DownloadId::new()).collect();
+
+        std::thread::scope(|scope| {
+            for id in &ids {
+                let path = &path;
+                scope.spawn(move || {
+                    Store::update(path, |store| store.entry(*id).seed_time = Some(60)).unwrap();
+                });
+            }
+        });
+
+        let store = Store::load(&path).unwrap();
+        assert!(ids.iter().all(|id| store.get(*id).is_some()));
+    }
+
+    #[test]
+    fn unchanged_store_is_not_written() {
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("state.json");
+        Store::update(&path, |store| store.get(DownloadId::new()).is_some()).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn seed_limits_parse_durations() {
+        let extras = DownloadExtras::seed_limits(Some(1.5), Some("24h"), Some("30m")).unwrap();
+        assert_eq!(extras.seed_ratio, Some(1.5));
+        assert_eq!(extras.seed_time, Some(86_400));
+        assert_eq!(extras.seed_idle, Some(1800));
+
+        assert!(DownloadExtras::seed_limits(None, Some("soon"), None).is_err());
+        assert!(DownloadExtras::seed_limits(Some(-1.0), None, None).is_err());
+        assert!(DownloadExtras::seed_limits(None, None, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn category_counts_as_a_tag() {
+        let extras = DownloadExtras {
+            category: Some("linux".to_string()),
+            tags: vec!["iso".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(extras.labels().collect::<Vec<_>>(), ["linux", "iso"]);
+        assert!(extras.has_tag("Linux") && extras.has_tag("iso"));
+        assert!(!extras.has_tag("work"));
+    }
+
+    #[test]
+    fn rename_moves_everything() {
+        let (old, new, other) = (DownloadId::new(), DownloadId::new(), DownloadId::new());
+        let mut store = Store::default();
+        store.entry(old).tags = vec!["iso".to_string()];
+        store.entry(old).quota_paused = true;
+        store.entry(other).after = Some(old);
+        store.set_queue([other, old]);
+
+        store.rename(old, new);
+
+        assert!(store.get(old).is_none());
+        let extras = store.get(new).unwrap();
+        assert!(extras.has_tag("iso") && !extras.quota_paused);
+        assert_eq!(store.get(other).unwrap().after, Some(new));
+        assert_eq!(store.queue_index(new), Some(1));
+    }
+}
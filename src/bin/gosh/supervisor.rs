@@ -0,0 +1,1472 @@
+//! Policy enforcement for long-running modes
+//!
+//! The engine has no notion of seeding time, idle limits, a cap on how many
+//! torrents seed at once, downloads held until later, minute-precise
+//! schedule rules, data caps, category hooks or retrying a failed download
+//! later, so the supervisor tracks those from engine events and acts on them.
+//! It is driven by the TUI, direct mode and `add --wait`.
+
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use gosh_dl::{
+    DownloadEngine, DownloadEvent, DownloadId, DownloadKind, DownloadState, DownloadStatus,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::category;
+use crate::config::{CliConfig, SeedingLimitAction};
+use crate::hold::{evaluate, AfterFailure, Verdict};
+use crate::queue::{plan_starts, sort_by_queue};
+use crate::quota::{self, Cap, Direction, Quota, Traffic};
+use crate::retry::{self, is_retryable, AutoRetry};
+use crate::schedule::{Rule, RuleAction, Schedule};
+use crate::store::Store;
+use crate::torrent_file::{
+    build_torrent_file, torrent_file_path, write_torrent_file, MetadataReader,
+};
+use crate::util::parse_duration;
+
+/// Minimum time between two policy evaluations
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often upload totals and last-upload times are written to the store
+const SEED_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often traffic counted towards quota caps is written to the store
+const TRAFFIC_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct Supervisor {
+    config: CliConfig,
+    state_path: PathBuf,
+    store: Store,
+
+    /// Bytes uploaded, integrated from progress events on top of the total
+    /// in the store. The engine keeps its own counters to itself, so this is
+    /// an estimate from the upload rate.
+    uploaded: HashMap<DownloadId, f64>,
+
+    /// Last progress sample per download (for upload integration)
+    last_sample: HashMap<DownloadId, Instant>,
+
+    /// Torrents currently seeding
+    seeds: HashMap<DownloadId, SeedTracker>,
+
+    /// When upload totals and seeding times were last written to the store
+    last_seed_flush: Instant,
+
+    /// Engine database access for fetched magnet metadata, opened on first use
+    metadata: Option<MetadataReader>,
+
+    /// Magnets whose .torrent has been written (or did not need to be)
+    metadata_done: HashSet<DownloadId>,
+
+    schedule: Schedule,
+
+    /// Rule whose limits and start actions have been applied (None = not yet)
+    schedule_applied: Option<Option<usize>>,
+
+    quota: Quota,
+
+    /// Traffic counted since the last flush, not yet in the store
+    traffic: Traffic,
+
+    /// When counted traffic was last written to the store
+    last_traffic_flush: Instant,
+
+    /// Last completed size per download (for download deltas)
+    last_completed: HashMap<DownloadId, u64>,
+
+    /// Downloads created after this count all their data
+    session_start: DateTime<Utc>,
+
+    /// Caps used up as of the last tick
+    quota_exceeded: Vec<Cap>,
+
+    /// Whether failures are retried under `[engine] auto_retry`
+    retries: bool,
+
+    auto_retry: Option<AutoRetry>,
+
+    /// Failed downloads out of retries, already reported
+    retries_exhausted: HashSet<DownloadId>,
+
+    /// Downloads waiting for a slot as of the last tick (None before the
+    /// first, when every running download may have just started)
+    waiting: Option<HashSet<DownloadId>>,
+
+    /// Downloads that could not be held for their turn, already reported
+    hold_failed: HashSet<DownloadId>,
+
+    last_tick: Option<Instant>,
+}
+
+/// Seeding times, kept in the store so that limits survive restarts
+struct SeedTracker {
+    since: DateTime<Utc>,
+    last_upload: DateTime<Utc>,
+}
+
+/// Something the supervisor did on its own. The details are shown by the
+/// TUI; other modes rely on the supervisor's log lines.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub enum SupervisorAction {
+    SeedingStopped {
+        id: DownloadId,
+        name: String,
+        limit: SeedLimit,
+        action: SeedingLimitAction,
+    },
+    MetadataSaved {
+        path: PathBuf,
+    },
+    /// A held download's start time passed or its dependency completed
+    Released {
+        name: String,
+    },
+    DependencyFailed {
+        name: String,
+        action: AfterFailure,
+    },
+    /// A schedule rule came into force, or the last one ended
+    ScheduleChanged {
+        rule: Option<String>,
+    },
+    /// A quota cap was used up, or the caps reset (empty)
+    QuotaChanged {
+        exceeded: Vec<String>,
+    },
+    /// A failed download will be retried at `at`
+    RetryScheduled {
+        name: String,
+        at: DateTime<Utc>,
+        attempt: u32,
+        max_attempts: u32,
+    },
+    /// A failed download was added again
+    Retried {
+        name: String,
+        attempt: u32,
+        max_attempts: u32,
+    },
+    /// A failed download has used up its automatic retries
+    RetriesExhausted {
+        name: String,
+        attempts: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedLimit {
+    Ratio,
+    Time,
+    Idle,
+    MaxSeeding,
+}
+
+impl fmt::Display for SeedLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ratio => "seed ratio reached",
+            Self::Time => "seed time reached",
+            Self::Idle => "seeding idle",
+            Self::MaxSeeding => "too many seeding torrents",
+        })
+    }
+}
+
+/// Seeding progress of one torrent, for display
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone)]
+pub struct SeedingInfo {
+    pub name: String,
+    pub ratio: f64,
+    pub target_ratio: Option<f64>,
+    pub time_left: Option<Duration>,
+}
+
+/// Whether a download has finished downloading and is uploading to peers.
+///
+/// The engine reports a complete torrent as Downloading until it stops
+/// seeding, so a full torrent in an active state counts as seeding too.
+pub fn is_seeding(status: &DownloadStatus) -> bool {
+    match status.state {
+        DownloadState::Seeding => true,
+        DownloadState::Downloading | DownloadState::Connecting => {
+            status.kind != DownloadKind::Http
+                && status
+                    .progress
+                    .total_size
+                    .is_some_and(|total| total > 0 && status.progress.completed_size >= total)
+        }
+        _ => false,
+    }
+}
+
+impl Supervisor {
+    /// Supervisor for a run that ends with its downloads (direct mode and
+    /// `add --wait`), which reports failures instead of retrying them
+    pub fn new(config: &CliConfig) -> Self {
+        let state_path = config.state_path();
+        let store = Store::load(&state_path).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring unreadable CLI state: {:#}", e);
+            Store::default()
+        });
+
+        Self {
+            config: config.clone(),
+            state_path,
+            store,
+            uploaded: HashMap::new(),
+            last_sample: HashMap::new(),
+            seeds: HashMap::new(),
+            last_seed_flush: Instant::now(),
+            metadata: None,
+            metadata_done: HashSet::new(),
+            schedule: schedule(config),
+            schedule_applied: None,
+            quota: quota(config),
+            traffic: Traffic::default(),
+            last_traffic_flush: Instant::now(),
+            last_completed: HashMap::new(),
+            // Allow for downloads the invoking command has just added
+            session_start: Utc::now() - TimeDelta::minutes(1),
+            quota_exceeded: Vec::new(),
+            retries: false,
+            auto_retry: None,
+            retries_exhausted: HashSet::new(),
+            waiting: None,
+            hold_failed: HashSet::new(),
+            last_tick: None,
+        }
+    }
+
+    /// Supervisor for a session that keeps running (the TUI and `gosh top`),
+    /// which also retries failures under `[engine] auto_retry`
+    pub fn with_retries(config: &CliConfig) -> Self {
+        let mut supervisor = Self::new(config);
+        supervisor.retries = true;
+        supervisor.auto_retry = auto_retry(config);
+        supervisor
+    }
+
+    /// Pick up configuration changes (e.g. from the TUI settings dialog)
+    #[cfg(feature = "tui")]
+    pub fn set_config(&mut self, config: &CliConfig) {
+        self.config = config.clone();
+        let schedule = schedule(config);
+        if schedule != self.schedule {
+            self.schedule = schedule;
+            self.schedule_applied = None;
+        }
+        self.quota = quota(config);
+        if self.retries {
+            self.auto_retry = auto_retry(config);
+        }
+    }
+
+    /// Feed an engine event to the supervisor
+    pub fn handle_event(&mut self, engine: &DownloadEngine, event: &DownloadEvent) {
+        match event {
+            DownloadEvent::Completed { id } => self.run_hook(engine, *id, None),
+            DownloadEvent::Failed { id, error, .. } => {
+                self.run_hook(engine, *id, Some(error));
+                // For retention, which ages failed downloads by this
+                let now = Utc::now();
+                self.store.entry(*id).failed_at = Some(now);
+                self.update_store(|store| store.entry(*id).failed_at = Some(now));
+            }
+            DownloadEvent::Progress { id, progress } => {
+                let now = Instant::now();
+                let mut traffic = Traffic::default();
+                if let Some(last) = self.last_sample.insert(*id, now) {
+                    let elapsed = now.duration_since(last).as_secs_f64();
+                    let uploaded = progress.upload_speed as f64 * elapsed;
+                    let stored = self.store.get(*id).map_or(0, |e| e.uploaded);
+                    *self.uploaded.entry(*id).or_insert(stored as f64) += uploaded;
+                    traffic.upload = uploaded as u64;
+                }
+                // Data from earlier sessions is not counted again
+                let last = match self.last_completed.insert(*id, progress.completed_size) {
+                    Some(last) => last,
+                    None => engine
+                        .status(*id)
+                        .filter(|dl| dl.created_at < self.session_start)
+                        .map_or(0, |_| progress.completed_size),
+                };
+                traffic.download = progress.completed_size.saturating_sub(last);
+                // Usage is only kept while there are caps to count it against
+                if !self.quota.caps.is_empty() && !self.quota.is_exempt(self.store.get(*id)) {
+                    self.traffic.add(traffic);
+                }
+            }
+            DownloadEvent::Removed { id } => {
+                self.uploaded.remove(id);
+                self.last_sample.remove(id);
+                self.last_completed.remove(id);
+                self.seeds.remove(id);
+                self.hold_failed.remove(id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Evaluate policies and act on them. Cheap to call often; work is
+    /// throttled to once per second.
+    pub async fn tick(&mut self, engine: &DownloadEngine) -> Vec<SupervisorAction> {
+        if self
+            .last_tick
+            .is_some_and(|last| last.elapsed() < TICK_INTERVAL)
+        {
+            return Vec::new();
+        }
+        self.last_tick = Some(Instant::now());
+
+        // Other gosh invocations may have added limits or holds meanwhile
+        match Store::load(&self.state_path) {
+            Ok(store) => self.store = store,
+            Err(e) => tracing::debug!("Keeping previous CLI state: {:#}", e),
+        }
+        if self.last_traffic_flush.elapsed() >= TRAFFIC_FLUSH_INTERVAL {
+            self.flush_traffic();
+        }
+
+        let downloads = engine.list();
+        self.track_seeds(&downloads);
+        if self.last_seed_flush.elapsed() >= SEED_FLUSH_INTERVAL {
+            self.flush_seeds();
+        }
+
+        let mut actions = self.apply_schedule(engine, &downloads).await;
+        actions.extend(self.enforce_quota(engine, &downloads).await);
+        actions.extend(self.auto_retry(engine, &downloads).await);
+        self.enforce_queue_order(engine, &downloads).await;
+        let mut stopped = Vec::new();
+
+        let reached: Vec<_> = downloads
+            .iter()
+            .filter_map(|dl| self.reached_limit(dl).map(|limit| (dl, limit)))
+            .collect();
+        let action = self.config.torrent.seeding_limit_action;
+        for (dl, limit) in reached {
+            if self.apply(engine, dl.id, action).await {
+                stopped.push(dl.id);
+                actions.push(SupervisorAction::SeedingStopped {
+                    id: dl.id,
+                    name: dl.metadata.name.clone(),
+                    limit,
+                    action,
+                });
+            }
+        }
+
+        // Enforce the seeding cap, pausing the torrents that have seeded longest
+        if let Some(max) = self.config.torrent.max_seeding {
+            let mut seeding: Vec<_> = downloads
+                .iter()
+                .filter(|d| self.seeds.contains_key(&d.id) && !stopped.contains(&d.id))
+                .collect();
+            seeding.sort_by_key(|d| self.seeds[&d.id].since);
+            let excess = seeding.len().saturating_sub(max);
+            for dl in seeding.into_iter().take(excess) {
+                if self.apply(engine, dl.id, SeedingLimitAction::Pause).await {
+                    stopped.push(dl.id);
+                    actions.push(SupervisorAction::SeedingStopped {
+                        id: dl.id,
+                        name: dl.metadata.name.clone(),
+                        limit: SeedLimit::MaxSeeding,
+                        action: SeedingLimitAction::Pause,
+                    });
+                }
+            }
+        }
+
+        for id in stopped {
+            self.stop_tracking(id);
+        }
+
+        for dl in &downloads {
+            if let Some(path) = self.save_metadata(dl).await {
+                actions.push(SupervisorAction::MetadataSaved { path });
+            }
+            if let Some(action) = self.enforce_hold(engine, dl, &downloads).await {
+                actions.push(action);
+            }
+        }
+
+        actions
+    }
+
+    /// CLI state as of the last tick
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Bytes uploaded per download, for those that uploaded this session
+    pub fn uploaded(&self) -> &HashMap<DownloadId, f64> {
+        &self.uploaded
+    }
+
+    /// Remaining ratio and time for every seeding torrent
+    #[cfg(feature = "tui")]
+    pub fn seeding_overview(&self, downloads: &[DownloadStatus]) -> Vec<SeedingInfo> {
+        downloads
+            .iter()
+            .filter(|dl| is_seeding(dl))
+            .map(|dl| {
+                let time_left = self
+                    .seeds
+                    .get(&dl.id)
+                    .zip(self.seed_time_limit(dl.id))
+                    .map(|(tracker, limit)| limit.saturating_sub(elapsed(tracker.since)));
+                let global = self.config.engine.seed_ratio;
+                SeedingInfo {
+                    name: dl.metadata.name.clone(),
+                    ratio: self.ratio(dl),
+                    target_ratio: self
+                        .target_ratio(dl.id)
+                        .or((global > 0.0).then_some(global)),
+                    time_left,
+                }
+            })
+            .collect()
+    }
+
+    /// Run the download's category hook for a completion or (with `error`)
+    /// a failure
+    fn run_hook(&mut self, engine: &DownloadEngine, id: DownloadId, error: Option<&str>) {
+        // Added since the last tick
+        if self.store.get(id).is_none() {
+            if let Ok(store) = Store::load(&self.state_path) {
+                self.store = store;
+            }
+        }
+        let Some(extras) = self.store.get(id) else {
+            return;
+        };
+        let Some(category) = extras
+            .category
+            .as_deref()
+            .and_then(|name| category::named(&self.config.categories, name).ok())
+        else {
+            return;
+        };
+        let hook = match error {
+            Some(_) => category.on_error.as_deref(),
+            None => category.on_complete.as_deref(),
+        };
+        let (Some(hook), Some(dl)) = (hook, engine.status(id)) else {
+            return;
+        };
+
+        tracing::info!("Running hook for {}: {}", id.to_gid(), hook);
+        if let Err(e) = category::run_hook(hook, &dl, extras, error) {
+            tracing::warn!("{:#}", e);
+        }
+    }
+
+    /// Write the .torrent for a magnet once its metadata is in, if asked to
+    async fn save_metadata(&mut self, dl: &DownloadStatus) -> Option<PathBuf> {
+        if dl.kind != DownloadKind::Magnet
+            || dl.torrent_info.is_none()
+            || self.metadata_done.contains(&dl.id)
+        {
+            return None;
+        }
+
+        let extras = self.store.get(dl.id).cloned().unwrap_or_default();
+        if !extras.save_torrent && !self.config.torrent.save_metadata {
+            self.metadata_done.insert(dl.id);
+            return None;
+        }
+
+        let target = extras
+            .torrent_path
+            .clone()
+            .or_else(|| self.config.torrent.metadata_dir.clone());
+        let path = torrent_file_path(target.as_deref(), &dl.metadata.save_dir, &dl.metadata.name);
+        if !extras.save_torrent && path.exists() {
+            // Saved by an earlier session
+            self.metadata_done.insert(dl.id);
+            return None;
+        }
+
+        if self.metadata.is_none() {
+            match MetadataReader::open(&self.config.general.database_path).await {
+                Ok(reader) => self.metadata = Some(reader),
+                Err(e) => {
+                    tracing::warn!("Cannot read magnet metadata: {:#}", e);
+                    self.metadata_done.insert(dl.id);
+                    return None;
+                }
+            }
+        }
+        let reader = self.metadata.as_ref()?;
+
+        // The engine persists the metadata shortly after it arrives; retry next tick
+        let raw = match reader.load(dl.id).await {
+            Ok(Some(raw)) => raw,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::debug!("Metadata for {} not readable yet: {:#}", dl.id.to_gid(), e);
+                return None;
+            }
+        };
+
+        self.metadata_done.insert(dl.id);
+        let written = build_torrent_file(&raw, dl.metadata.magnet_uri.as_deref())
+            .and_then(|data| write_torrent_file(&path, &data));
+        if let Err(e) = written {
+            tracing::warn!("Failed to save .torrent for {}: {:#}", dl.id.to_gid(), e);
+            return None;
+        }
+
+        tracing::info!(
+            "Saved metadata for {} to {}",
+            dl.id.to_gid(),
+            path.display()
+        );
+        if extras.save_torrent {
+            self.store.entry(dl.id).save_torrent = false;
+            let updated = Store::update(&self.state_path, |store| {
+                let entry = store.entry(dl.id);
+                entry.save_torrent = false;
+                entry.torrent_path = None;
+            });
+            if let Err(e) = updated {
+                tracing::warn!("Failed to update CLI state: {:#}", e);
+            }
+        }
+        Some(path)
+    }
+
+    /// Keep a held download paused, and start it once it may
+    async fn enforce_hold(
+        &mut self,
+        engine: &DownloadEngine,
+        dl: &DownloadStatus,
+        downloads: &[DownloadStatus],
+    ) -> Option<SupervisorAction> {
+        let extras = self.store.get(dl.id)?.clone();
+        let verdict = evaluate(&extras, downloads, &self.store, Utc::now())?;
+
+        match verdict {
+            Verdict::Wait => {
+                // It got a slot before we could pause it
+                if dl.state.is_active() {
+                    if let Err(e) = engine.pause(dl.id).await {
+                        tracing::debug!("Failed to hold {}: {}", dl.id.to_gid(), e);
+                    }
+                }
+                None
+            }
+            Verdict::Release => {
+                self.release(engine, dl).await;
+                Some(SupervisorAction::Released {
+                    name: dl.metadata.name.clone(),
+                })
+            }
+            Verdict::DependencyFailed => {
+                let action = extras.after_failure;
+                tracing::info!(
+                    "Dependency of {} failed ({})",
+                    dl.id.to_gid(),
+                    action.as_str()
+                );
+                match action {
+                    AfterFailure::Fail => {
+                        self.store.entry(dl.id).dependency_failed = true;
+                        self.update_store(|store| store.entry(dl.id).dependency_failed = true);
+                        if dl.state.is_active() {
+                            let _ = engine.pause(dl.id).await;
+                        }
+                    }
+                    AfterFailure::Start => self.release(engine, dl).await,
+                    AfterFailure::Cancel => {
+                        if let Err(e) = engine.cancel(dl.id, false).await {
+                            tracing::warn!("Failed to cancel {}: {}", dl.id.to_gid(), e);
+                            return None;
+                        }
+                        self.forget(dl.id);
+                    }
+                }
+                Some(SupervisorAction::DependencyFailed {
+                    name: dl.metadata.name.clone(),
+                    action,
+                })
+            }
+        }
+    }
+
+    /// Apply the rule in force: its limits and start actions when it comes
+    /// into force, and its standing restrictions on every tick
+    async fn apply_schedule(
+        &mut self,
+        engine: &DownloadEngine,
+        downloads: &[DownloadStatus],
+    ) -> Vec<SupervisorAction> {
+        let mut actions = Vec::new();
+        let active = self.schedule.active(Local::now().naive_local());
+        let rule = active.map(|i| self.schedule.rules[i].clone());
+
+        if self.schedule_applied != Some(active) {
+            // Skip the notice for "no rule" on startup
+            let announce = self.schedule_applied.is_some() || rule.is_some();
+            self.schedule_applied = Some(active);
+            engine.set_schedule_rules(rule.iter().map(Rule::engine_rule).collect());
+            if announce {
+                let description = rule.as_ref().map(|r| r.to_string());
+                tracing::info!(
+                    "Schedule: {}",
+                    description.as_deref().unwrap_or("no rule in force")
+                );
+                actions.push(SupervisorAction::ScheduleChanged { rule: description });
+            }
+            self.start_rule(engine, downloads, rule.as_ref()).await;
+        }
+
+        let Some(rule) = rule else {
+            return actions;
+        };
+        if rule.has(RuleAction::SeedingOff) {
+            let seeding: Vec<DownloadId> = self.seeds.keys().copied().collect();
+            for id in seeding {
+                self.schedule_pause(engine, id).await;
+                self.seeds.remove(&id);
+            }
+        }
+        if let Some(max) = rule.max_concurrent() {
+            self.limit_running(engine, downloads, max).await;
+        }
+        actions
+    }
+
+    /// Undo what the previous rule paused and run the new rule's start actions
+    async fn start_rule(
+        &mut self,
+        engine: &DownloadEngine,
+        downloads: &[DownloadStatus],
+        rule: Option<&Rule>,
+    ) {
+        let has = |action| rule.is_some_and(|r| r.has(action));
+
+        if has(RuleAction::PauseAll) {
+            for dl in downloads.iter().filter(|d| d.state.is_active()) {
+                self.schedule_pause(engine, dl.id).await;
+            }
+            return;
+        }
+
+        // Downloads still restricted by the new rule stay paused; a running
+        // cap resumes its share itself
+        let keeps_cap = rule.is_some_and(|r| r.max_concurrent().is_some());
+        for dl in downloads {
+            if !self.is_schedule_paused(dl.id)
+                || keeps_cap
+                || (has(RuleAction::SeedingOff) && is_complete_torrent(dl))
+            {
+                continue;
+            }
+            self.schedule_resume(engine, dl).await;
+        }
+
+        if has(RuleAction::ResumeAll) {
+            for dl in downloads {
+                let held = self.store.get(dl.id).is_some_and(|e| {
+                    e.start_at.is_some() || e.after.is_some() || e.quota_paused || e.queue_held
+                });
+                if dl.state == DownloadState::Paused && !held {
+                    if let Err(e) = engine.resume(dl.id).await {
+                        tracing::debug!("Schedule could not resume {}: {}", dl.id.to_gid(), e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keep at most `max` downloads (not counting seeds) running, pausing the
+    /// ones furthest back in the queue and resuming them as room frees up
+    async fn limit_running(
+        &mut self,
+        engine: &DownloadEngine,
+        downloads: &[DownloadStatus],
+        max: usize,
+    ) {
+        let mut sorted = downloads.to_vec();
+        sort_by_queue(&mut sorted, &self.store);
+        let running: Vec<_> = sorted
+            .iter()
+            .filter(|d| d.state.is_active() && !is_seeding(d))
+            .collect();
+
+        if running.len() > max {
+            for dl in &running[max..] {
+                self.schedule_pause(engine, dl.id).await;
+            }
+            return;
+        }
+
+        // Queued downloads will take free slots on their own
+        let waiting = sorted
+            .iter()
+            .filter(|d| d.state == DownloadState::Queued)
+            .count();
+        let room = max.saturating_sub(running.len() + waiting);
+        let resumable: Vec<_> = sorted
+            .iter()
+            .filter(|d| self.is_schedule_paused(d.id) && !is_complete_torrent(d))
+            .take(room)
+            .cloned()
+            .collect();
+        for dl in &resumable {
+            self.schedule_resume(engine, dl).await;
+        }
+    }
+
+    fn is_schedule_paused(&self, id: DownloadId) -> bool {
+        self.store.get(id).is_some_and(|e| e.schedule_paused)
+    }
+
+    async fn schedule_pause(&mut self, engine: &DownloadEngine, id: DownloadId) {
+        match engine.pause(id).await {
+            Ok(()) => {
+                tracing::info!("Schedule paused {}", id.to_gid());
+                self.store.entry(id).schedule_paused = true;
+                self.update_store(|store| store.entry(id).schedule_paused = true);
+            }
+            Err(e) => tracing::debug!("Schedule could not pause {}: {}", id.to_gid(), e),
+        }
+    }
+
+    async fn schedule_resume(&mut self, engine: &DownloadEngine, dl: &DownloadStatus) {
+        let id = dl.id;
+        self.store.entry(id).schedule_paused = false;
+        self.update_store(|store| {
+            store.entry(id).schedule_paused = false;
+            if store.get(id).is_some_and(|e| e.is_empty()) {
+                store.remove(id);
+            }
+        });
+        // Resumed by hand in the meantime, or still over a quota
+        if dl.state != DownloadState::Paused || self.is_quota_paused(id) {
+            return;
+        }
+        match engine.resume(id).await {
+            Ok(()) => tracing::info!("Schedule resumed {}", id.to_gid()),
+            Err(e) => tracing::debug!("Schedule could not resume {}: {}", id.to_gid(), e),
+        }
+    }
+
+    /// Start waiting downloads in the order `gosh move` set, which the engine
+    /// does not know about, by pausing the ones it starts out of turn. Only
+    /// needed once an order has been set.
+    async fn enforce_queue_order(&mut self, engine: &DownloadEngine, downloads: &[DownloadStatus]) {
+        let waiting = downloads
+            .iter()
+            .filter(|d| d.state == DownloadState::Queued)
+            .map(|d| d.id)
+            .collect();
+        let started: HashSet<_> = match self.waiting.replace(waiting) {
+            Some(was_waiting) => was_waiting,
+            None => downloads.iter().map(|d| d.id).collect(),
+        };
+        if self.store.queue.is_empty() {
+            return;
+        }
+        let rule = self
+            .schedule
+            .active(Local::now().naive_local())
+            .map(|i| &self.schedule.rules[i]);
+        if rule.is_some_and(|r| r.has(RuleAction::PauseAll)) {
+            return;
+        }
+        let max = self.config.engine.max_concurrent_downloads;
+        let max = rule
+            .and_then(Rule::max_concurrent)
+            .map_or(max, |n| n.min(max));
+        let capped = self
+            .quota_exceeded
+            .iter()
+            .any(|c| c.direction == Direction::Download);
+
+        let plan = plan_starts(downloads, &self.store, max, &started);
+        for id in plan.hold {
+            match engine.pause(id).await {
+                Ok(()) => {
+                    tracing::debug!("Holding {} until its turn in the queue", id.to_gid());
+                    self.hold_failed.remove(&id);
+                    self.store.entry(id).queue_held = true;
+                    self.update_store(|store| store.entry(id).queue_held = true);
+                }
+                Err(e) => {
+                    if self.hold_failed.insert(id) {
+                        tracing::warn!("Could not hold {} for its turn: {}", id.to_gid(), e);
+                    }
+                }
+            }
+        }
+        for id in plan.release {
+            // It would only be paused again
+            if capped && !self.quota.is_exempt(self.store.get(id)) {
+                continue;
+            }
+            self.store.clear_queue_hold(id);
+            self.update_store(|store| store.clear_queue_hold(id));
+            match engine.resume(id).await {
+                Ok(()) => tracing::info!("Starting {}, next in the queue", id.to_gid()),
+                Err(e) => tracing::debug!("Could not start {}: {}", id.to_gid(), e),
+            }
+        }
+    }
+
+    /// Count the traffic since the last flush towards today
+    fn flush_traffic(&mut self) {
+        self.last_traffic_flush = Instant::now();
+        if self.traffic.is_empty() {
+            return;
+        }
+        let traffic = std::mem::take(&mut self.traffic);
+        let today = Local::now().date_naive();
+        quota::record(&mut self.store.usage, today, traffic);
+        self.update_store(|store| quota::record(&mut store.usage, today, traffic));
+    }
+
+    /// Keep the downloads a used-up cap covers paused, and resume them once
+    /// it resets
+    async fn enforce_quota(
+        &mut self,
+        engine: &DownloadEngine,
+        downloads: &[DownloadStatus],
+    ) -> Option<SupervisorAction> {
+        // Traffic not written to the store yet counts too
+        let today = Local::now().date_naive();
+        let mut usage = self.store.usage.clone();
+        quota::record(&mut usage, today, self.traffic);
+        let exceeded = self.quota.exceeded(&usage, today);
+        let capped = |direction| exceeded.iter().any(|c| c.direction == direction);
+        let (download, upload) = (capped(Direction::Download), capped(Direction::Upload));
+
+        for dl in downloads {
+            // Only torrents upload; a complete one downloads nothing more
+            let blocked = !self.quota.is_exempt(self.store.get(dl.id))
+                && ((download && !is_complete_torrent(dl))
+                    || (upload && dl.kind != DownloadKind::Http));
+            if blocked && dl.state.is_active() {
+                self.quota_pause(engine, dl.id).await;
+            } else if !blocked && self.is_quota_paused(dl.id) {
+                self.quota_resume(engine, dl).await;
+            }
+        }
+
+        if exceeded == self.quota_exceeded {
+            return None;
+        }
+        let exceeded_names: Vec<String> = exceeded.iter().map(Cap::to_string).collect();
+        if exceeded_names.is_empty() {
+            tracing::info!("Quota: within all caps");
+        } else {
+            tracing::info!("Quota: {} used up", exceeded_names.join(", "));
+        }
+        self.quota_exceeded = exceeded;
+        Some(SupervisorAction::QuotaChanged {
+            exceeded: exceeded_names,
+        })
+    }
+
+    fn is_quota_paused(&self, id: DownloadId) -> bool {
+        self.store.get(id).is_some_and(|e| e.quota_paused)
+    }
+
+    async fn quota_pause(&mut self, engine: &DownloadEngine, id: DownloadId) {
+        match engine.pause(id).await {
+            Ok(()) => {
+                tracing::info!("Quota paused {}", id.to_gid());
+                self.store.entry(id).quota_paused = true;
+                self.update_store(|store| store.entry(id).quota_paused = true);
+            }
+            Err(e) => tracing::debug!("Quota could not pause {}: {}", id.to_gid(), e),
+        }
+    }
+
+    async fn quota_resume(&mut self, engine: &DownloadEngine, dl: &DownloadStatus) {
+        let id = dl.id;
+        self.store.entry(id).quota_paused = false;
+        self.update_store(|store| {
+            store.entry(id).quota_paused = false;
+            if store.get(id).is_some_and(|e| e.is_empty()) {
+                store.remove(id);
+            }
+        });
+        // Resumed by hand, or still held back for another reason
+        let held = self
+            .store
+            .get(id)
+            .is_some_and(|e| e.schedule_paused || e.start_at.is_some() || e.after.is_some());
+        if dl.state != DownloadState::Paused || held {
+            return;
+        }
+        match engine.resume(id).await {
+            Ok(()) => tracing::info!("Quota resumed {}", id.to_gid()),
+            Err(e) => tracing::debug!("Quota could not resume {}: {}", id.to_gid(), e),
+        }
+    }
+
+    /// Give retryable failures a time for their next attempt, and make the
+    /// attempts that are due
+    async fn auto_retry(
+        &mut self,
+        engine: &DownloadEngine,
+        downloads: &[DownloadStatus],
+    ) -> Vec<SupervisorAction> {
+        let mut actions = Vec::new();
+        let Some(policy) = self.auto_retry else {
+            return actions;
+        };
+        let now = Utc::now();
+        // A retried download would only be paused again
+        let pause_all = self
+            .schedule
+            .active(Local::now().naive_local())
+            .is_some_and(|i| self.schedule.rules[i].has(RuleAction::PauseAll));
+        let capped = self
+            .quota_exceeded
+            .iter()
+            .any(|c| c.direction == Direction::Download);
+
+        for dl in downloads.iter().filter(|d| is_retryable(d)) {
+            let extras = self.store.get(dl.id).cloned().unwrap_or_default();
+            let name = dl.metadata.name.clone();
+            let attempt = extras.retry_attempts + 1;
+            match extras.next_retry {
+                None if extras.retry_attempts >= policy.max_attempts => {
+                    if self.retries_exhausted.insert(dl.id) {
+                        tracing::warn!(
+                            "Giving up on {} after {} retries",
+                            dl.id.to_gid(),
+                            extras.retry_attempts
+                        );
+                        actions.push(SupervisorAction::RetriesExhausted {
+                            name,
+                            attempts: extras.retry_attempts,
+                        });
+                    }
+                }
+                None => {
+                    let at = now + policy.delay(extras.retry_attempts);
+                    self.store.entry(dl.id).next_retry = Some(at);
+                    self.update_store(|store| store.entry(dl.id).next_retry = Some(at));
+                    tracing::info!(
+                        "Retrying {} at {} (attempt {} of {})",
+                        dl.id.to_gid(),
+                        at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                        attempt,
+                        policy.max_attempts
+                    );
+                    actions.push(SupervisorAction::RetryScheduled {
+                        name,
+                        at,
+                        attempt,
+                        max_attempts: policy.max_attempts,
+                    });
+                }
+                Some(at) if at <= now => {
+                    if pause_all || (capped && !self.quota.is_exempt(Some(&extras))) {
+                        continue;
+                    }
+                    match retry::retry(
+                        engine,
+                        &self.state_path,
+                        &self.config.general.database_path,
+                        dl,
+                        None,
+                        attempt,
+                    )
+                    .await
+                    {
+                        Ok(id) => {
+                            tracing::info!(
+                                "Retry {} of {}: {} added again as {}",
+                                attempt,
+                                policy.max_attempts,
+                                dl.id.to_gid(),
+                                id.to_gid()
+                            );
+                            actions.push(SupervisorAction::Retried {
+                                name,
+                                attempt,
+                                max_attempts: policy.max_attempts,
+                            });
+                        }
+                        Err(e) => {
+                            // Counts as an attempt; the next one is scheduled
+                            // on the following tick
+                            tracing::warn!(
+                                "Retry {} of {} for {} failed: {:#}",
+                                attempt,
+                                policy.max_attempts,
+                                dl.id.to_gid(),
+                                e
+                            );
+                            self.update_store(|store| {
+                                let extras = store.entry(dl.id);
+                                extras.retry_attempts = attempt;
+                                extras.next_retry = None;
+                            });
+                        }
+                    }
+                    if let Ok(store) = Store::load(&self.state_path) {
+                        self.store = store;
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        actions
+    }
+
+    async fn release(&mut self, engine: &DownloadEngine, dl: &DownloadStatus) {
+        self.store.entry(dl.id).clear_hold();
+        self.update_store(|store| store.entry(dl.id).clear_hold());
+        if dl.state == DownloadState::Paused {
+            if let Err(e) = engine.resume(dl.id).await {
+                tracing::warn!("Failed to start {}: {}", dl.id.to_gid(), e);
+            }
+        }
+    }
+
+    fn update_store(&self, f: impl FnOnce(&mut Store)) {
+        if let Err(e) = Store::update(&self.state_path, f) {
+            tracing::warn!("Failed to update CLI state: {:#}", e);
+        }
+    }
+
+    /// Pick up torrents that started seeding, carrying on with the times in
+    /// the store for those that seeded before
+    fn track_seeds(&mut self, downloads: &[DownloadStatus]) {
+        let now = Utc::now();
+        let tracked = |d: &DownloadStatus| is_seeding(d) && self.has_seed_limit(d.id);
+        let seeding: Vec<&DownloadStatus> = downloads.iter().filter(|d| tracked(d)).collect();
+        self.seeds
+            .retain(|id, _| seeding.iter().any(|d| d.id == *id));
+        for dl in seeding {
+            if !self.seeds.contains_key(&dl.id) {
+                let extras = self.store.get(dl.id);
+                let tracker = match extras.and_then(|e| e.seeding_since) {
+                    Some(since) => SeedTracker {
+                        since,
+                        last_upload: extras.and_then(|e| e.last_upload).unwrap_or(since),
+                    },
+                    None => {
+                        let start = |store: &mut Store| {
+                            let extras = store.entry(dl.id);
+                            extras.seeding_since = Some(now);
+                            extras.last_upload = Some(now);
+                        };
+                        start(&mut self.store);
+                        self.update_store(start);
+                        SeedTracker {
+                            since: now,
+                            last_upload: now,
+                        }
+                    }
+                };
+                self.seeds.insert(dl.id, tracker);
+            }
+            if dl.progress.upload_speed > 0 {
+                if let Some(tracker) = self.seeds.get_mut(&dl.id) {
+                    tracker.last_upload = now;
+                }
+            }
+        }
+    }
+
+    /// Write upload totals and last-upload times that moved on to the store
+    fn flush_seeds(&mut self) {
+        self.last_seed_flush = Instant::now();
+        let uploaded: Vec<(DownloadId, u64)> = self
+            .uploaded
+            .iter()
+            .map(|(id, bytes)| (*id, *bytes as u64))
+            .filter(|(id, _)| self.has_seed_limit(*id))
+            .filter(|(id, bytes)| self.store.get(*id).map_or(0, |e| e.uploaded) != *bytes)
+            .collect();
+        let last_upload: Vec<(DownloadId, DateTime<Utc>)> = self
+            .seeds
+            .iter()
+            .filter(|(id, t)| {
+                self.store.get(**id).and_then(|e| e.last_upload) != Some(t.last_upload)
+            })
+            .map(|(id, t)| (*id, t.last_upload))
+            .collect();
+        if uploaded.is_empty() && last_upload.is_empty() {
+            return;
+        }
+        let apply = |store: &mut Store| {
+            for (id, bytes) in &uploaded {
+                store.entry(*id).uploaded = *bytes;
+            }
+            for (id, at) in &last_upload {
+                store.entry(*id).last_upload = Some(*at);
+            }
+        };
+        apply(&mut self.store);
+        self.update_store(apply);
+    }
+
+    /// Forget the seeding times of a torrent the supervisor stopped, so that
+    /// it starts over if resumed by hand
+    fn stop_tracking(&mut self, id: DownloadId) {
+        if self.seeds.remove(&id).is_none() || self.store.get(id).is_none() {
+            return;
+        }
+        let clear = |store: &mut Store| {
+            if store.get(id).is_some() {
+                let extras = store.entry(id);
+                extras.seeding_since = None;
+                extras.last_upload = None;
+            }
+        };
+        clear(&mut self.store);
+        self.update_store(clear);
+    }
+
+    /// Whether a seeding limit or the seeding cap can stop the torrent, so
+    /// its seeding times are worth keeping. The engine applies
+    /// `engine.seed_ratio` on its own.
+    fn has_seed_limit(&self, id: DownloadId) -> bool {
+        self.config.torrent.max_seeding.is_some()
+            || self.target_ratio(id).is_some()
+            || self.seed_time_limit(id).is_some()
+            || self.seed_idle_limit(id).is_some()
+    }
+
+    fn reached_limit(&self, dl: &DownloadStatus) -> Option<SeedLimit> {
+        let tracker = self.seeds.get(&dl.id)?;
+
+        if let Some(target) = self.target_ratio(dl.id) {
+            if self.ratio(dl) >= target {
+                return Some(SeedLimit::Ratio);
+            }
+        }
+        if let Some(limit) = self.seed_time_limit(dl.id) {
+            if elapsed(tracker.since) >= limit {
+                return Some(SeedLimit::Time);
+            }
+        }
+        if let Some(limit) = self.seed_idle_limit(dl.id) {
+            if elapsed(tracker.last_upload) >= limit {
+                return Some(SeedLimit::Idle);
+            }
+        }
+        None
+    }
+
+    fn ratio(&self, dl: &DownloadStatus) -> f64 {
+        let downloaded = dl.progress.total_size.unwrap_or(dl.progress.completed_size);
+        let uploaded = match self.uploaded.get(&dl.id) {
+            Some(bytes) => *bytes,
+            None => self.store.get(dl.id).map_or(0, |e| e.uploaded) as f64,
+        };
+        if downloaded == 0 {
+            0.0
+        } else {
+            uploaded / downloaded as f64
+        }
+    }
+
+    /// The download's own ratio limit; 0 means seed without one. The
+    /// engine stops every torrent at `engine.seed_ratio` itself.
+    fn target_ratio(&self, id: DownloadId) -> Option<f64> {
+        let ratio = self.store.get(id).and_then(|e| e.seed_ratio)?;
+        (ratio > 0.0).then_some(ratio)
+    }
+
+    fn seed_time_limit(&self, id: DownloadId) -> Option<Duration> {
+        self.store
+            .get(id)
+            .and_then(|e| e.seed_time)
+            .or_else(|| {
+                self.config
+                    .torrent
+                    .seed_time
+                    .as_deref()
+                    .and_then(config_secs)
+            })
+            .map(Duration::from_secs)
+    }
+
+    fn seed_idle_limit(&self, id: DownloadId) -> Option<Duration> {
+        self.store
+            .get(id)
+            .and_then(|e| e.seed_idle)
+            .or_else(|| {
+                self.config
+                    .torrent
+                    .seed_idle
+                    .as_deref()
+                    .and_then(config_secs)
+            })
+            .map(Duration::from_secs)
+    }
+
+    async fn apply(
+        &mut self,
+        engine: &DownloadEngine,
+        id: DownloadId,
+        action: SeedingLimitAction,
+    ) -> bool {
+        let result = match action {
+            SeedingLimitAction::Pause => engine.pause(id).await,
+            SeedingLimitAction::Remove => engine.cancel(id, false).await,
+            SeedingLimitAction::RemoveWithFiles => engine.cancel(id, true).await,
+        };
+        match result {
+            Ok(()) => {
+                tracing::info!("Stopped seeding {} ({})", id.to_gid(), action.as_str());
+                if action != SeedingLimitAction::Pause {
+                    self.forget(id);
+                }
+                true
+            }
+            Err(e) => {
+                tracing::warn!("Failed to stop seeding {}: {}", id.to_gid(), e);
+                false
+            }
+        }
+    }
+
+    /// Drop the persisted extras of a download that no longer exists
+    fn forget(&mut self, id: DownloadId) {
+        self.store.remove(id);
+        if let Err(e) = Store::update(&self.state_path, |store| store.remove(id)) {
+            tracing::warn!("Failed to update CLI state: {:#}", e);
+        }
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        // Direct mode and `add --wait` exit right after the last download
+        self.flush_traffic();
+        self.flush_seeds();
+    }
+}
+
+/// Time since `at`, zero if it lies in the future
+fn elapsed(at: DateTime<Utc>) -> Duration {
+    (Utc::now() - at).to_std().unwrap_or_default()
+}
+
+/// Whether a torrent has all its data, so that running it means seeding
+fn is_complete_torrent(dl: &DownloadStatus) -> bool {
+    dl.kind != DownloadKind::Http
+        && (dl.state == DownloadState::Completed
+            || dl
+                .progress
+                .total_size
+                .is_some_and(|total| total > 0 && dl.progress.completed_size >= total))
+}
+
+/// The config is validated at load time, so errors here only come from
+/// settings edited since; those leave the schedule empty.
+fn schedule(config: &CliConfig) -> Schedule {
+    Schedule::from_config(&config.schedule).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring schedule: {:#}", e);
+        Schedule::default()
+    })
+}
+
+/// Like `schedule`, an invalid quota edited since loading is dropped
+fn quota(config: &CliConfig) -> Quota {
+    Quota::from_config(&config.quota).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring quota: {:#}", e);
+        Quota::default()
+    })
+}
+
+/// Like `schedule`, invalid auto-retry settings edited since loading turn it off
+fn auto_retry(config: &CliConfig) -> Option<AutoRetry> {
+    let auto_retry = config.engine.auto_retry.as_ref()?;
+    AutoRetry::from_config(auto_retry)
+        .inspect_err(|e| tracing::warn!("Ignoring auto_retry: {:#}", e))
+        .ok()
+}
+
+/// Config durations are validated at load time, so a parse failure here is
+/// treated as "no limit".
+fn config_secs(s: &str) -> Option<u64> {
+    parse_duration(s).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+
+    #[test]
+    fn complete_active_torrent_counts_as_seeding() {
+        assert!(is_seeding(&status(
+            DownloadKind::Torrent,
+            DownloadState::Downloading,
+            10,
+            10
+        )));
+        assert!(is_seeding(&status(
+            DownloadKind::Magnet,
+            DownloadState::Seeding,
+            10,
+            10
+        )));
+        assert!(!is_seeding(&status(
+            DownloadKind::Torrent,
+            DownloadState::Downloading,
+            10,
+            5
+        )));
+        assert!(!is_seeding(&status(
+            DownloadKind::Torrent,
+            DownloadState::Paused,
+            10,
+            10
+        )));
+        assert!(!is_seeding(&status(
+            DownloadKind::Http,
+            DownloadState::Downloading,
+            10,
+            10
+        )));
+    }
+
+    #[test]
+    fn per_download_limits_override_config() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let mut config = CliConfig::default();
+        config.general.database_path = tempdir.path().join("gosh.db");
+        config.torrent.seed_time = Some("1h".to_string());
+        config.engine.seed_ratio = 0.0;
+        let mut supervisor = Supervisor::new(&config);
+        let id = DownloadId::new();
+
+        assert_eq!(
+            supervisor.seed_time_limit(id),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(supervisor.target_ratio(id), None);
+
+        let extras = supervisor.store.entry(id);
+        extras.seed_time = Some(60);
+        extras.seed_ratio = Some(2.0);
+        assert_eq!(
+            supervisor.seed_time_limit(id),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(supervisor.target_ratio(id), Some(2.0));
+    }
+
+    #[test]
+    fn seeding_times_carry_over_from_the_store() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let mut config = CliConfig::default();
+        config.general.database_path = tempdir.path().join("gosh.db");
+        let dl = status(DownloadKind::Torrent, DownloadState::Seeding, 10, 10);
+        let other = status(DownloadKind::Torrent, DownloadState::Seeding, 10, 10);
+        let started = Utc::now() - TimeDelta::hours(2);
+        Store::update(&config.state_path(), |store| {
+            let extras = store.entry(dl.id);
+            extras.seed_time = Some(3600);
+            extras.seeding_since = Some(started);
+            extras.uploaded = 25;
+            store.entry(other.id).seed_ratio = Some(2.0);
+        })
+        .unwrap();
+
+        let mut supervisor = Supervisor::new(&config);
+        supervisor.track_seeds(std::slice::from_ref(&dl));
+        assert_eq!(supervisor.reached_limit(&dl), Some(SeedLimit::Time));
+        assert_eq!(supervisor.ratio(&dl), 2.5);
+
+        // A new seed is written down straight away
+        supervisor.track_seeds(&[dl.clone(), other.clone()]);
+        let store = Store::load(&config.state_path()).unwrap();
+        assert!(store.get(other.id).unwrap().seeding_since.is_some());
+
+        supervisor.stop_tracking(dl.id);
+        let store = Store::load(&config.state_path()).unwrap();
+        assert_eq!(store.get(dl.id).unwrap().seeding_since, None);
+        assert_eq!(store.get(dl.id).unwrap().uploaded, 25);
+    }
+
+    #[test]
+    fn seeds_without_limits_are_not_tracked() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let mut config = CliConfig::default();
+        config.general.database_path = tempdir.path().join("gosh.db");
+        let dl = status(DownloadKind::Torrent, DownloadState::Seeding, 10, 10);
+
+        let mut supervisor = Supervisor::new(&config);
+        supervisor.uploaded.insert(dl.id, 5.0);
+        supervisor.track_seeds(std::slice::from_ref(&dl));
+        supervisor.flush_seeds();
+        assert!(supervisor.seeds.is_empty());
+        let store = Store::load(&config.state_path()).unwrap();
+        assert!(store.get(dl.id).is_none());
+
+        // The seeding cap can stop any of them
+        config.torrent.max_seeding = Some(1);
+        let mut supervisor = Supervisor::new(&config);
+        supervisor.track_seeds(std::slice::from_ref(&dl));
+        let store = Store::load(&config.state_path()).unwrap();
+        assert!(store.get(dl.id).unwrap().seeding_since.is_some());
+    }
+
+    #[tokio::test]
+    async fn only_long_running_sessions_retry() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let mut config = CliConfig::default();
+        config.general.database_path = tempdir.path().join("gosh.db");
+        config.general.download_dir = tempdir.path().to_path_buf();
+        config.engine.auto_retry = Some(Default::default());
+        let engine = DownloadEngine::new(config.to_engine_config())
+            .await
+            .unwrap();
+
+        let mut dl = status(
+            DownloadKind::Http,
+            DownloadState::Error {
+                kind: "network".to_string(),
+                message: "connection reset".to_string(),
+                retryable: true,
+            },
+            100,
+            10,
+        );
+        dl.metadata.url = Some("http://127.0.0.1:9/a.iso".to_string());
+        dl.metadata.save_dir = tempdir.path().to_path_buf();
+        Store::update(&config.state_path(), |store| {
+            store.entry(dl.id).next_retry = Some(Utc::now() - TimeDelta::minutes(1));
+        })
+        .unwrap();
+
+        // Direct mode and `add --wait` report the failure and leave it be
+        let mut supervisor = Supervisor::new(&config);
+        let actions = supervisor
+            .auto_retry(&engine, std::slice::from_ref(&dl))
+            .await;
+        assert!(actions.is_empty());
+        assert!(engine.list().is_empty());
+
+        let mut supervisor = Supervisor::with_retries(&config);
+        let actions = supervisor
+            .auto_retry(&engine, std::slice::from_ref(&dl))
+            .await;
+        assert!(matches!(
+            actions[..],
+            [SupervisorAction::Retried { attempt: 1, .. }]
+        ));
+        assert_eq!(engine.list().len(), 1);
+
+        engine.shutdown().await.unwrap();
+    }
+}
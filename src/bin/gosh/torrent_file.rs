@@ -0,0 +1,134 @@
+//! Writing `.torrent` files for magnet downloads
+//!
+//! Once a magnet's metadata has been fetched (BEP 9) the engine persists the
+//! info dictionary to its database. We read it back from there and add the
+//! magnet's trackers and web seeds so the written file is usable on its own.
+
+use anyhow::{Context, Result};
+use gosh_dl::torrent::{BencodeValue, MagnetUri};
+use gosh_dl::{DownloadId, SqliteStorage, Storage};
+use std::path::{Path, PathBuf};
+
+/// Read-only access to the torrent data the engine stored for a download
+pub struct MetadataReader {
+    storage: SqliteStorage,
+}
+
+impl MetadataReader {
+    pub async fn open(database_path: &Path) -> Result<Self> {
+        let storage = SqliteStorage::new(database_path)
+            .await
+            .with_context(|| format!("Failed to open database: {}", database_path.display()))?;
+        Ok(Self { storage })
+    }
+
+    /// Raw bencoded torrent data, if the metadata has been fetched yet
+    pub async fn load(&self, id: DownloadId) -> Result<Option<Vec<u8>>> {
+        Ok(self.storage.load_torrent_data(id).await?)
+    }
+}
+
+/// Build a complete `.torrent` from fetched metadata and the magnet it came from
+pub fn build_torrent_file(raw: &[u8], magnet_uri: Option<&str>) -> Result<Vec<u8>> {
+    let mut torrent = BencodeValue::parse_exact(raw).context("Invalid torrent metadata")?;
+    let magnet = magnet_uri.and_then(|uri| MagnetUri::parse(uri).ok());
+
+    if let (Some(dict), Some(magnet)) = (torrent.as_dict_mut(), magnet) {
+        let bytes = |s: &str| BencodeValue::Bytes(s.as_bytes().to_vec());
+
+        if let Some(first) = magnet.trackers.first() {
+            dict.entry(b"announce".to_vec())
+                .or_insert_with(|| bytes(first));
+            dict.entry(b"announce-list".to_vec()).or_insert_with(|| {
+                BencodeValue::List(
+                    magnet
+                        .trackers
+                        .iter()
+                        .map(|t| BencodeValue::List(vec![bytes(t)]))
+                        .collect(),
+                )
+            });
+        }
+        if !magnet.web_seeds.is_empty() {
+            dict.entry(b"url-list".to_vec()).or_insert_with(|| {
+                BencodeValue::List(magnet.web_seeds.iter().map(|s| bytes(s)).collect())
+            });
+        }
+    }
+
+    Ok(torrent.encode())
+}
+
+/// Where to write the `.torrent` for a download named `name`.
+///
+/// `target` may be a file path, a directory, or `None` for `fallback_dir`.
+pub fn torrent_file_path(target: Option<&Path>, fallback_dir: &Path, name: &str) -> PathBuf {
+    let file_name = format!("{}.torrent", name);
+    match target {
+        Some(path) if path.is_dir() => path.join(file_name),
+        Some(path) => path.to_path_buf(),
+        None => fallback_dir.join(file_name),
+    }
+}
+
+/// Write a `.torrent` file, creating parent directories as needed
+pub fn write_torrent_file(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, data)
+        .with_context(|| format!("Failed to write torrent file: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gosh_dl::torrent::Metainfo;
+
+    const INFO: &[u8] =
+        b"d4:infod6:lengthi5e4:name5:a.bin12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+
+    #[test]
+    fn adds_magnet_trackers() {
+        let magnet = "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567\
+                      &tr=udp%3A%2F%2Ftracker.example%3A6969&ws=http%3A%2F%2Fseed.example%2Fa.bin";
+        let data = build_torrent_file(INFO, Some(magnet)).unwrap();
+        let metainfo = Metainfo::parse(&data).unwrap();
+
+        assert_eq!(
+            metainfo.announce.as_deref(),
+            Some("udp://tracker.example:6969")
+        );
+        assert_eq!(
+            metainfo.url_list,
+            vec!["http://seed.example/a.bin".to_string()]
+        );
+        assert_eq!(metainfo.info.name, "a.bin");
+    }
+
+    #[test]
+    fn keeps_metadata_without_magnet() {
+        assert_eq!(build_torrent_file(INFO, None).unwrap(), INFO);
+    }
+
+    #[test]
+    fn path_defaults_next_to_download() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(
+            torrent_file_path(None, Path::new("/data"), "a.bin"),
+            PathBuf::from("/data/a.bin.torrent")
+        );
+        assert_eq!(
+            torrent_file_path(Some(dir.path()), Path::new("/data"), "a.bin"),
+            dir.path().join("a.bin.torrent")
+        );
+        assert_eq!(
+            torrent_file_path(
+                Some(Path::new("/tmp/x.torrent")),
+                Path::new("/data"),
+                "a.bin"
+            ),
+            PathBuf::from("/tmp/x.torrent")
+        );
+    }
+}
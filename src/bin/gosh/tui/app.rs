@@ -13,12 +13,23 @@ use std::time::{Duration, Instant};
 use throbber_widgets_tui::ThrobberState;
 
 use crate::config::CliConfig;
+use crate::format::format_local_time;
+use crate::hold::{held, state_label, Hold};
+use crate::purge::{self, PurgeFilter};
+use crate::queue::{is_waiting, move_in_queue, sort_by_queue, QueueMove};
+use crate::store::Store;
+use crate::supervisor::{SeedingInfo, Supervisor, SupervisorAction};
 use crate::util::truncate_str;
 
+use super::chunks::{ChunkLoader, ChunkMap};
 use super::event::{self, AppEvent, EventHandler};
+use super::schedule_editor::{self, KeyOutcome, RuleEditor};
 use super::theme::Theme;
 use super::ui;
 
+/// Index of the Schedule tab in the settings dialog
+pub const SCHEDULE_TAB: usize = 4;
+
 /// TUI Application state
 pub struct TuiApp {
     /// The download engine
@@ -90,17 +101,17 @@ pub struct TuiApp {
     /// Active search/filter state
     pub search: Option<SearchState>,
 
+    /// Sort order of the peers panel
+    pub peer_sort: PeerSort,
+
     /// Peak download speed observed
     pub peak_download_speed: u64,
 
     /// Peak upload speed observed
     pub peak_upload_speed: u64,
 
-    /// Chunk states for the selected download
-    pub chunk_states: Vec<ChunkState>,
-
-    /// Number of chunks for the selected download
-    pub chunk_count: usize,
+    /// Piece/segment map for the selected download
+    chunks: ChunkLoader,
 
     /// Activity log entries
     pub activity_log: VecDeque<ActivityEntry>,
@@ -111,6 +122,12 @@ pub struct TuiApp {
     /// Scroll offset for activity log
     pub activity_log_scroll: usize,
 
+    /// Enforces seeding limits and other CLI-side policies
+    supervisor: Supervisor,
+
+    /// Seeding torrents with their remaining ratio and time
+    pub seeding: Vec<SeedingInfo>,
+
     /// Should quit
     should_quit: bool,
 }
@@ -134,6 +151,66 @@ pub enum RightPanelFocus {
     Graph,
     Details,
     ChunkMap,
+    Peers,
+}
+
+/// Sortable columns of the peers panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerColumn {
+    Address,
+    Client,
+    Flags,
+    Progress,
+    Down,
+    Up,
+}
+
+impl PeerColumn {
+    pub const ALL: [PeerColumn; 6] = [
+        Self::Address,
+        Self::Client,
+        Self::Flags,
+        Self::Progress,
+        Self::Down,
+        Self::Up,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Address => "Address",
+            Self::Client => "Client",
+            Self::Flags => "Flags",
+            Self::Progress => "Progress",
+            Self::Down => "Down",
+            Self::Up => "Up",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Address => Self::Client,
+            Self::Client => Self::Flags,
+            Self::Flags => Self::Progress,
+            Self::Progress => Self::Down,
+            Self::Down => Self::Up,
+            Self::Up => Self::Address,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerSort {
+    pub column: PeerColumn,
+    pub descending: bool,
+}
+
+impl Default for PeerSort {
+    fn default() -> Self {
+        Self {
+            column: PeerColumn::Down,
+            descending: true,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -146,6 +223,15 @@ pub enum DialogState {
         id: gosh_dl::DownloadId,
         delete_files: bool,
     },
+    /// Retry downloads, some of which failed with a non-retryable error
+    ConfirmRetry {
+        ids: Vec<gosh_dl::DownloadId>,
+        permanent: usize,
+    },
+    /// Remove completed downloads from the history, keeping their files
+    ConfirmClear {
+        ids: Vec<gosh_dl::DownloadId>,
+    },
     Error {
         message: String,
     },
@@ -155,6 +241,8 @@ pub enum DialogState {
         editing: Option<String>,
         draft: Box<CliConfig>,
         dirty: bool,
+        /// Rule open on the Schedule tab
+        rule_editor: Option<RuleEditor>,
     },
     BatchImport {
         phase: BatchPhase,
@@ -195,6 +283,7 @@ pub enum SearchScope {
     Name,
     Url,
     State,
+    Tag,
 }
 
 impl Default for SearchState {
@@ -207,6 +296,37 @@ impl Default for SearchState {
     }
 }
 
+impl SearchState {
+    /// Whether a download fits the query, ignoring case
+    pub fn matches(&self, dl: &DownloadStatus, store: &Store) -> bool {
+        let query = self.query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        let hit = |s: &str| s.to_lowercase().contains(&query);
+        let name = || hit(&dl.metadata.name);
+        let url = || {
+            [&dl.metadata.url, &dl.metadata.magnet_uri]
+                .into_iter()
+                .flatten()
+                .any(|u| hit(u))
+        };
+        let state = || hit(&state_label(dl, store));
+        let tag = || {
+            store
+                .get(dl.id)
+                .is_some_and(|extras| extras.labels().any(hit))
+        };
+        match self.scope {
+            SearchScope::All => name() || url() || state() || tag(),
+            SearchScope::Name => name(),
+            SearchScope::Url => url(),
+            SearchScope::State => state(),
+            SearchScope::Tag => tag(),
+        }
+    }
+}
+
 impl SearchScope {
     pub fn label(&self) -> &'static str {
         match self {
@@ -214,6 +334,7 @@ impl SearchScope {
             Self::Name => "Name",
             Self::Url => "URL",
             Self::State => "State",
+            Self::Tag => "Tag",
         }
     }
     pub fn next(&self) -> Self {
@@ -221,7 +342,8 @@ impl SearchScope {
             Self::All => Self::Name,
             Self::Name => Self::Url,
             Self::Url => Self::State,
-            Self::State => Self::All,
+            Self::State => Self::Tag,
+            Self::Tag => Self::All,
         }
     }
 }
@@ -232,6 +354,8 @@ pub enum ChunkState {
     Downloading,
     Complete,
     Failed,
+    /// Not checked: the torrent is too large to hash for the map
+    Unknown,
 }
 
 pub struct ActivityEntry {
@@ -270,11 +394,14 @@ impl TuiApp {
 
         let engine_config = config.to_engine_config();
         let engine = DownloadEngine::new(engine_config).await?;
+        purge::apply_retention(&engine, &config).await?;
 
         // Get initial download list
         let downloads = engine.list();
 
         let theme = Theme::from_name(&config.tui.theme);
+        let supervisor = Supervisor::with_retries(&config);
+        let chunks = ChunkLoader::new(config.general.database_path.clone());
 
         let (terminal_width, terminal_height) = crossterm::terminal::size().unwrap_or((80, 24));
 
@@ -310,13 +437,15 @@ impl TuiApp {
             terminal_height,
             right_panel_focus: RightPanelFocus::Details,
             search: None,
+            peer_sort: PeerSort::default(),
             peak_download_speed: 0,
             peak_upload_speed: 0,
-            chunk_states: Vec::new(),
-            chunk_count: 0,
+            chunks,
             activity_log: VecDeque::new(),
             show_activity_log: false,
             activity_log_scroll: 0,
+            supervisor,
+            seeding: Vec::new(),
             should_quit: false,
         })
     }
@@ -325,27 +454,53 @@ impl TuiApp {
         &self.theme
     }
 
+    /// Move the selected download past its neighbour in the current view
     fn reorder_download(&mut self, direction: i32) {
-        let len = self.downloads.len();
-        if len < 2 {
-            return;
-        }
-
         let new_idx = if direction > 0 {
-            if self.selected + 1 >= len {
-                return;
-            }
             self.selected + 1
         } else {
-            if self.selected == 0 {
-                return;
+            match self.selected.checked_sub(1) {
+                Some(idx) => idx,
+                None => return,
             }
-            self.selected - 1
         };
+        let (Some(dl), Some(neighbour)) = (
+            self.downloads.get(self.selected),
+            self.downloads.get(new_idx),
+        ) else {
+            return;
+        };
+        let (id, neighbour) = (dl.id, neighbour.id);
+
+        // Positions are over the whole waiting queue, not just this view
+        let mut all = self.engine.list();
+        let result = Store::update(&self.config.state_path(), |store| {
+            sort_by_queue(&mut all, store);
+            let to = match all
+                .iter()
+                .filter(|d| is_waiting(d, store))
+                .position(|d| d.id == neighbour)
+            {
+                Some(pos) => QueueMove::To(pos + 1),
+                // The neighbour is running or finished
+                None if direction > 0 => QueueMove::Down,
+                None => QueueMove::Up,
+            };
+            move_in_queue(store, &all, id, to)
+        });
 
-        self.downloads.swap(self.selected, new_idx);
-        self.selected = new_idx;
-        self.adjust_scroll(self.last_visible_height);
+        match result {
+            Ok(Ok(_)) => {
+                self.refresh_downloads();
+                if let Some(idx) = self.downloads.iter().position(|d| d.id == id) {
+                    self.selected = idx;
+                }
+                self.adjust_scroll(self.last_visible_height);
+            }
+            Ok(Err(e)) | Err(e) => {
+                self.push_toast(format!("Move failed: {}", e), ToastLevel::Error);
+            }
+        }
     }
 
     fn detect_layout_mode(&mut self) {
@@ -388,10 +543,14 @@ impl TuiApp {
                     }
                 }
                 AppEvent::Engine(event) => {
+                    self.supervisor.handle_event(&self.engine, &event);
                     self.handle_engine_event(event);
                 }
                 AppEvent::Tick => {
+                    self.run_supervisor().await;
                     self.update_stats();
+                    self.refresh_peers();
+                    self.chunks.refresh(self.downloads.get(self.selected)).await;
                 }
                 AppEvent::Resync => {
                     // Full resync after missed broadcast events
@@ -448,26 +607,20 @@ impl TuiApp {
                                 input.insert(byte_pos, c);
                                 *cursor += 1;
                             }
-                            crossterm::event::KeyCode::Backspace => {
-                                if *cursor > 0 {
-                                    *cursor -= 1;
-                                    let byte_pos = input
-                                        .char_indices()
-                                        .nth(*cursor)
-                                        .map(|(i, _)| i)
-                                        .unwrap_or(input.len());
-                                    input.remove(byte_pos);
-                                }
+                            crossterm::event::KeyCode::Backspace if *cursor > 0 => {
+                                *cursor -= 1;
+                                let byte_pos = input
+                                    .char_indices()
+                                    .nth(*cursor)
+                                    .map(|(i, _)| i)
+                                    .unwrap_or(input.len());
+                                input.remove(byte_pos);
                             }
-                            crossterm::event::KeyCode::Left => {
-                                if *cursor > 0 {
-                                    *cursor -= 1;
-                                }
+                            crossterm::event::KeyCode::Left if *cursor > 0 => {
+                                *cursor -= 1;
                             }
-                            crossterm::event::KeyCode::Right => {
-                                if *cursor < input.chars().count() {
-                                    *cursor += 1;
-                                }
+                            crossterm::event::KeyCode::Right if *cursor < input.chars().count() => {
+                                *cursor += 1;
                             }
                             _ => {}
                         }
@@ -489,6 +642,26 @@ impl TuiApp {
                     }
                     return Ok(false);
                 }
+                DialogState::ConfirmRetry { ids, .. } => {
+                    if event::is_escape(event) || event::is_key(event, 'n') {
+                        self.dialog = None;
+                    } else if event::is_key(event, 'y') || event::is_enter(event) {
+                        let ids = std::mem::take(ids);
+                        self.dialog = None;
+                        self.retry_downloads(&ids).await;
+                    }
+                    return Ok(false);
+                }
+                DialogState::ConfirmClear { ids } => {
+                    if event::is_escape(event) || event::is_key(event, 'n') {
+                        self.dialog = None;
+                    } else if event::is_key(event, 'y') || event::is_enter(event) {
+                        let ids = std::mem::take(ids);
+                        self.dialog = None;
+                        self.clear_completed(&ids).await;
+                    }
+                    return Ok(false);
+                }
                 DialogState::Error { .. } => {
                     if event::is_escape(event)
                         || event::is_enter(event)
@@ -504,6 +677,7 @@ impl TuiApp {
                     editing,
                     draft,
                     dirty,
+                    rule_editor,
                 } => {
                     if let crossterm::event::Event::Key(key) = event {
                         if editing.is_some() {
@@ -513,13 +687,27 @@ impl TuiApp {
                                 }
                                 crossterm::event::KeyCode::Enter => {
                                     if let Some(val) = editing.take() {
-                                        Self::apply_settings_edit(
-                                            draft,
-                                            *active_tab,
-                                            *selected_row,
-                                            &val,
-                                        );
-                                        *dirty = true;
+                                        if let Some(open) = rule_editor {
+                                            let rule = &mut draft.schedule.rules[open.index];
+                                            match schedule_editor::apply_field(rule, open.row, &val)
+                                            {
+                                                Ok(()) => *dirty = true,
+                                                Err(e) => {
+                                                    self.push_toast(
+                                                        format!("{:#}", e),
+                                                        ToastLevel::Error,
+                                                    );
+                                                }
+                                            }
+                                        } else {
+                                            Self::apply_settings_edit(
+                                                draft,
+                                                *active_tab,
+                                                *selected_row,
+                                                &val,
+                                            );
+                                            *dirty = true;
+                                        }
                                     }
                                 }
                                 crossterm::event::KeyCode::Backspace => {
@@ -535,6 +723,22 @@ impl TuiApp {
                                 _ => {}
                             }
                         } else {
+                            if *active_tab == SCHEDULE_TAB {
+                                match schedule_editor::handle_key(
+                                    key.code,
+                                    &mut draft.schedule.rules,
+                                    selected_row,
+                                    rule_editor,
+                                    editing,
+                                ) {
+                                    KeyOutcome::Ignored => {}
+                                    KeyOutcome::Handled => return Ok(false),
+                                    KeyOutcome::Changed => {
+                                        *dirty = true;
+                                        return Ok(false);
+                                    }
+                                }
+                            }
                             match key.code {
                                 crossterm::event::KeyCode::Esc => {
                                     if *dirty {
@@ -543,7 +747,12 @@ impl TuiApp {
                                             let _ = new_config.save(None);
                                             self.config = new_config;
                                             let engine_cfg = self.config.to_engine_config();
+                                            // The engine's config setter leaves its schedule alone
+                                            self.engine.set_schedule_rules(
+                                                engine_cfg.schedule_rules.clone(),
+                                            );
                                             let _ = self.engine.set_config(engine_cfg);
+                                            self.supervisor.set_config(&self.config);
                                             self.theme = Theme::from_name(&self.config.tui.theme);
                                             self.push_toast(
                                                 "Settings saved".to_string(),
@@ -553,26 +762,25 @@ impl TuiApp {
                                     }
                                     self.dialog = None;
                                 }
-                                crossterm::event::KeyCode::Left => {
-                                    if *active_tab > 0 {
-                                        *active_tab -= 1;
-                                        *selected_row = 0;
-                                    }
+                                crossterm::event::KeyCode::Left if *active_tab > 0 => {
+                                    *active_tab -= 1;
+                                    *selected_row = 0;
                                 }
-                                crossterm::event::KeyCode::Right => {
-                                    if *active_tab < 4 {
-                                        *active_tab += 1;
-                                        *selected_row = 0;
-                                    }
+                                crossterm::event::KeyCode::Right if *active_tab < 4 => {
+                                    *active_tab += 1;
+                                    *selected_row = 0;
                                 }
                                 crossterm::event::KeyCode::Up
-                                | crossterm::event::KeyCode::Char('k') => {
-                                    if *selected_row > 0 {
-                                        *selected_row -= 1;
-                                    }
+                                | crossterm::event::KeyCode::Char('k')
+                                    if *selected_row > 0 =>
+                                {
+                                    *selected_row -= 1;
                                 }
                                 crossterm::event::KeyCode::Down
-                                | crossterm::event::KeyCode::Char('j') => {
+                                | crossterm::event::KeyCode::Char('j')
+                                    if *selected_row + 1
+                                        < Self::settings_row_count(*active_tab) =>
+                                {
                                     *selected_row += 1;
                                 }
                                 crossterm::event::KeyCode::Char(n @ '1'..='5') => {
@@ -728,16 +936,16 @@ impl TuiApp {
                                     };
                                 }
                                 crossterm::event::KeyCode::Up
-                                | crossterm::event::KeyCode::Char('k') => {
-                                    if *selected > 0 {
-                                        *selected -= 1;
-                                    }
+                                | crossterm::event::KeyCode::Char('k')
+                                    if *selected > 0 =>
+                                {
+                                    *selected -= 1;
                                 }
                                 crossterm::event::KeyCode::Down
-                                | crossterm::event::KeyCode::Char('j') => {
-                                    if *selected + 1 < entries.len() {
-                                        *selected += 1;
-                                    }
+                                | crossterm::event::KeyCode::Char('j')
+                                    if *selected + 1 < entries.len() =>
+                                {
+                                    *selected += 1;
                                 }
                                 crossterm::event::KeyCode::Char(' ') => {
                                     if let Some(e) = entries.get_mut(*selected) {
@@ -786,6 +994,7 @@ impl TuiApp {
                 match key.code {
                     crossterm::event::KeyCode::Esc => {
                         self.search = None;
+                        self.refresh_downloads();
                         return Ok(false);
                     }
                     crossterm::event::KeyCode::Enter => {
@@ -809,6 +1018,7 @@ impl TuiApp {
                             search.query.insert(byte_pos, c);
                             search.cursor += 1;
                         }
+                        self.refresh_downloads();
                         return Ok(false);
                     }
                     crossterm::event::KeyCode::Backspace => {
@@ -822,6 +1032,7 @@ impl TuiApp {
                                 .unwrap_or(search.query.len());
                             search.query.remove(byte_pos);
                         }
+                        self.refresh_downloads();
                         return Ok(false);
                     }
                     _ => {}
@@ -875,6 +1086,28 @@ impl TuiApp {
                     delete_files: event::is_key(event, 'd'),
                 });
             }
+        } else if event::is_upper_key(event, 'R') {
+            // Retry the selected download if it failed, otherwise all failed
+            self.retry_failed().await;
+        } else if event::is_upper_key(event, 'C') {
+            // Clear completed downloads (with confirmation)
+            let filter = PurgeFilter {
+                completed: true,
+                ..Default::default()
+            };
+            let store = self.supervisor.store();
+            let ids: Vec<_> = purge::select(self.engine.list(), store, filter)
+                .iter()
+                .map(|dl| dl.id)
+                .collect();
+            if ids.is_empty() {
+                self.push_activity(
+                    ActivityLevel::Info,
+                    "No completed downloads to clear".to_string(),
+                );
+            } else {
+                self.dialog = Some(DialogState::ConfirmClear { ids });
+            }
         }
 
         // View mode
@@ -894,10 +1127,20 @@ impl TuiApp {
             self.right_panel_focus = match self.right_panel_focus {
                 RightPanelFocus::Graph => RightPanelFocus::Details,
                 RightPanelFocus::Details => RightPanelFocus::ChunkMap,
-                RightPanelFocus::ChunkMap => RightPanelFocus::Graph,
+                RightPanelFocus::ChunkMap if self.config.tui.show_peers => RightPanelFocus::Peers,
+                RightPanelFocus::ChunkMap | RightPanelFocus::Peers => RightPanelFocus::Graph,
             };
         }
 
+        // Peers panel sorting (o: next column, O: reverse)
+        if self.showing_peers() {
+            if event::is_key(event, 'o') {
+                self.peer_sort.column = self.peer_sort.column.next();
+            } else if event::is_upper_key(event, 'O') {
+                self.peer_sort.descending = !self.peer_sort.descending;
+            }
+        }
+
         // Toggle activity log
         if event::is_upper_key(event, 'L') {
             self.show_activity_log = !self.show_activity_log;
@@ -916,6 +1159,7 @@ impl TuiApp {
                 editing: None,
                 draft: Box::new(self.config.clone()),
                 dirty: false,
+                rule_editor: None,
             });
         }
 
@@ -1006,6 +1250,121 @@ impl TuiApp {
         }
     }
 
+    /// Let the supervisor enforce its policies and report what it did
+    async fn run_supervisor(&mut self) {
+        for action in self.supervisor.tick(&self.engine).await {
+            match action {
+                SupervisorAction::SeedingStopped {
+                    name,
+                    limit,
+                    action,
+                    ..
+                } => {
+                    self.push_activity(
+                        ActivityLevel::Info,
+                        format!(
+                            "Seeding stopped ({}, {}): {}",
+                            limit,
+                            action.as_str(),
+                            truncate_str(&name, 40)
+                        ),
+                    );
+                    self.refresh_downloads();
+                }
+                SupervisorAction::MetadataSaved { path } => {
+                    self.push_activity(
+                        ActivityLevel::Success,
+                        format!("Saved torrent: {}", path.display()),
+                    );
+                }
+                SupervisorAction::Released { name } => {
+                    self.push_activity(
+                        ActivityLevel::Info,
+                        format!("Starting: {}", truncate_str(&name, 50)),
+                    );
+                    self.refresh_downloads();
+                }
+                SupervisorAction::ScheduleChanged { rule } => {
+                    let message = match rule {
+                        Some(rule) => format!("Schedule: {}", rule),
+                        None => "Schedule: no rule in force".to_string(),
+                    };
+                    self.push_activity(ActivityLevel::Info, message);
+                    self.refresh_downloads();
+                }
+                SupervisorAction::QuotaChanged { exceeded } => {
+                    if exceeded.is_empty() {
+                        self.push_activity(
+                            ActivityLevel::Info,
+                            "Quota reset, resuming downloads".to_string(),
+                        );
+                    } else {
+                        self.push_activity(
+                            ActivityLevel::Warning,
+                            format!("Quota: {} used up, pausing", exceeded.join(", ")),
+                        );
+                    }
+                    self.refresh_downloads();
+                }
+                SupervisorAction::RetryScheduled {
+                    name,
+                    at,
+                    attempt,
+                    max_attempts,
+                } => {
+                    self.push_activity(
+                        ActivityLevel::Info,
+                        format!(
+                            "Retry {}/{} at {}: {}",
+                            attempt,
+                            max_attempts,
+                            format_local_time(at),
+                            truncate_str(&name, 40)
+                        ),
+                    );
+                }
+                SupervisorAction::Retried {
+                    name,
+                    attempt,
+                    max_attempts,
+                } => {
+                    self.push_activity(
+                        ActivityLevel::Info,
+                        format!(
+                            "Retrying ({}/{}): {}",
+                            attempt,
+                            max_attempts,
+                            truncate_str(&name, 40)
+                        ),
+                    );
+                    self.refresh_downloads();
+                }
+                SupervisorAction::RetriesExhausted { name, attempts } => {
+                    self.push_activity(
+                        ActivityLevel::Warning,
+                        format!(
+                            "Gave up after {} retries: {}",
+                            attempts,
+                            truncate_str(&name, 40)
+                        ),
+                    );
+                }
+                SupervisorAction::DependencyFailed { name, action } => {
+                    self.push_activity(
+                        ActivityLevel::Warning,
+                        format!(
+                            "Dependency failed ({}): {}",
+                            action.as_str(),
+                            truncate_str(&name, 40)
+                        ),
+                    );
+                    self.refresh_downloads();
+                }
+            }
+        }
+        self.seeding = self.supervisor.seeding_overview(&self.engine.active());
+    }
+
     /// Update global stats
     fn update_stats(&mut self) {
         let stats = self.engine.global_stats();
@@ -1023,9 +1382,6 @@ impl TuiApp {
             self.speed_history.pop_front();
         }
 
-        // Update chunk states for selected download
-        self.compute_chunk_states();
-
         // Advance throbber animation
         self.throbber_state.calc_next();
 
@@ -1047,46 +1403,27 @@ impl TuiApp {
         }
     }
 
-    pub fn compute_chunk_states(&mut self) {
-        if let Some(dl) = self.selected_download() {
-            let total = dl.progress.total_size.unwrap_or(0);
-            if total == 0 {
-                self.chunk_states.clear();
-                self.chunk_count = 0;
-                return;
-            }
-
-            let count = if let Some(ref ti) = dl.torrent_info {
-                ti.pieces_count.min(256)
-            } else {
-                let seg_size = 1024 * 1024_u64;
-                ((total / seg_size) as usize).clamp(1, 256)
-            };
-
-            let progress_ratio = dl.progress.completed_size as f64 / total as f64;
-            let completed_chunks = (count as f64 * progress_ratio) as usize;
-
-            let is_active = matches!(
-                dl.state,
-                DownloadState::Downloading | DownloadState::Connecting
-            );
+    /// Whether the peers panel replaces the details panel
+    pub fn showing_peers(&self) -> bool {
+        self.right_panel_focus == RightPanelFocus::Peers && self.config.tui.show_peers
+    }
 
-            self.chunk_count = count;
-            self.chunk_states = (0..count)
-                .map(|i| {
-                    if i < completed_chunks {
-                        ChunkState::Complete
-                    } else if i < completed_chunks + 3 && is_active {
-                        ChunkState::Downloading
-                    } else {
-                        ChunkState::Pending
-                    }
-                })
-                .collect();
-        } else {
-            self.chunk_states.clear();
-            self.chunk_count = 0;
+    /// Peer lists are not part of progress events; re-read the selected
+    /// download's while the peers panel is visible
+    fn refresh_peers(&mut self) {
+        if !self.showing_peers() {
+            return;
         }
+        if let Some(dl) = self.downloads.get_mut(self.selected) {
+            if let Some(status) = self.engine.status(dl.id) {
+                dl.peers = status.peers;
+            }
+        }
+    }
+
+    /// Piece/segment map of the selected download, once loaded
+    pub fn chunk_map(&self) -> Option<&ChunkMap> {
+        self.chunks.map()
     }
 
     pub fn push_activity(&mut self, level: ActivityLevel, message: String) {
@@ -1112,6 +1449,11 @@ impl TuiApp {
                 .filter(|d| matches!(d.state, DownloadState::Completed))
                 .collect(),
         };
+        let store = Store::load(&self.config.state_path()).unwrap_or_default();
+        if let Some(ref search) = self.search {
+            self.downloads.retain(|dl| search.matches(dl, &store));
+        }
+        sort_by_queue(&mut self.downloads, &store);
 
         // Adjust selection if needed
         if self.selected >= self.downloads.len() && !self.downloads.is_empty() {
@@ -1160,26 +1502,127 @@ impl TuiApp {
         use crate::input::url_parser::{parse_input, ParsedInput};
 
         let input = parse_input(url)?;
-        let options = gosh_dl::DownloadOptions::default();
-
-        let result = match input {
-            ParsedInput::Http(url) => self.engine.add_http(&url, options).await,
-            ParsedInput::Magnet(uri) => self.engine.add_magnet(&uri, options).await,
+        let category = crate::category::matching(&self.config.categories, &input);
+        let options = category
+            .map(crate::config::CategoryConfig::options)
+            .unwrap_or_default();
+
+        let result = match &input {
+            ParsedInput::Http(url) => self.engine.add_http(url, options).await,
+            ParsedInput::Magnet(uri) => self.engine.add_magnet(uri, options).await,
             ParsedInput::TorrentFile(path) => {
-                let data = tokio::fs::read(&path).await?;
+                let data = tokio::fs::read(path).await?;
                 self.engine.add_torrent(&data, options).await
             }
         };
 
-        if let Err(e) = result {
-            self.dialog = Some(DialogState::Error {
-                message: e.to_string(),
-            });
+        match result {
+            Ok(id) => {
+                if let Some(category) = category {
+                    let is_torrent = !matches!(input, ParsedInput::Http(_));
+                    Store::update(&self.config.state_path(), |store| {
+                        let extras = store.entry(id);
+                        extras.category = Some(category.name.clone());
+                        if is_torrent {
+                            extras.seed_ratio = category.seed_ratio;
+                        }
+                    })?;
+                }
+            }
+            Err(e) => {
+                self.dialog = Some(DialogState::Error {
+                    message: e.to_string(),
+                });
+            }
         }
 
         Ok(())
     }
 
+    /// Retry the selected download if it failed, otherwise every failed one.
+    /// Non-retryable errors are confirmed first.
+    async fn retry_failed(&mut self) {
+        let targets: Vec<DownloadStatus> = match self.selected_download() {
+            Some(dl) if crate::retry::is_failed(dl) => vec![dl.clone()],
+            _ => self
+                .engine
+                .list()
+                .into_iter()
+                .filter(crate::retry::is_failed)
+                .collect(),
+        };
+        if targets.is_empty() {
+            self.push_activity(
+                ActivityLevel::Info,
+                "No failed downloads to retry".to_string(),
+            );
+            return;
+        }
+
+        let ids: Vec<_> = targets.iter().map(|dl| dl.id).collect();
+        let permanent = targets
+            .iter()
+            .filter(|dl| !crate::retry::is_retryable(dl))
+            .count();
+        if permanent > 0 {
+            self.dialog = Some(DialogState::ConfirmRetry { ids, permanent });
+        } else {
+            self.retry_downloads(&ids).await;
+        }
+    }
+
+    async fn retry_downloads(&mut self, ids: &[gosh_dl::DownloadId]) {
+        let state_path = self.config.state_path();
+        for id in ids {
+            let Some(dl) = self.engine.status(*id) else {
+                continue;
+            };
+            match crate::retry::retry(
+                &self.engine,
+                &state_path,
+                &self.config.general.database_path,
+                &dl,
+                None,
+                0,
+            )
+            .await
+            {
+                Ok(_) => self.push_activity(
+                    ActivityLevel::Info,
+                    format!("Retrying: {}", truncate_str(&dl.metadata.name, 40)),
+                ),
+                Err(e) => {
+                    self.dialog = Some(DialogState::Error {
+                        message: format!("{:#}", e),
+                    });
+                }
+            }
+        }
+        self.refresh_downloads();
+    }
+
+    async fn clear_completed(&mut self, ids: &[gosh_dl::DownloadId]) {
+        let state_path = self.config.state_path();
+        let mut cleared = 0;
+        for id in ids {
+            match purge::purge(&self.engine, &state_path, *id, false).await {
+                Ok(()) => cleared += 1,
+                Err(e) => {
+                    self.dialog = Some(DialogState::Error {
+                        message: format!("{:#}", e),
+                    });
+                }
+            }
+        }
+        if cleared > 0 {
+            self.push_activity(
+                ActivityLevel::Info,
+                format!("Cleared {} completed download(s)", cleared),
+            );
+        }
+        self.refresh_downloads();
+    }
+
     /// Pause selected download
     async fn pause_selected(&mut self) -> Result<()> {
         if let Some(dl) = self.selected_download() {
@@ -1193,10 +1636,44 @@ impl TuiApp {
         Ok(())
     }
 
+    /// Why a download is held back, if it is
+    pub fn hold(&self, dl: &DownloadStatus) -> Option<Hold> {
+        held(dl, self.supervisor.store())
+    }
+
+    /// When a failed download is retried next, for display
+    pub fn next_retry(&self, dl: &DownloadStatus) -> Option<String> {
+        if !crate::retry::is_failed(dl) {
+            return None;
+        }
+        let extras = self.supervisor.store().get(dl.id)?;
+        let at = extras.next_retry?;
+        let max_attempts = self.config.engine.auto_retry.as_ref()?.max_attempts;
+        Some(format!(
+            "Retry {} ({}/{})",
+            format_local_time(at),
+            extras.retry_attempts + 1,
+            max_attempts
+        ))
+    }
+
+    /// Category and tags of a download, for display
+    pub fn tags(&self, dl: &DownloadStatus) -> Vec<String> {
+        self.supervisor
+            .store()
+            .get(dl.id)
+            .map(|extras| extras.labels().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
     /// Resume selected download
     async fn resume_selected(&mut self) -> Result<()> {
         if let Some(dl) = self.selected_download() {
             let id = dl.id;
+            // Resuming by hand overrides any --start-at/--after hold
+            if let Err(e) = Store::update(&self.config.state_path(), |store| store.clear_hold(id)) {
+                tracing::warn!("Failed to clear hold: {:#}", e);
+            }
             if let Err(e) = self.engine.resume(id).await {
                 self.dialog = Some(DialogState::Error {
                     message: e.to_string(),
@@ -1219,10 +1696,8 @@ impl TuiApp {
     // Settings helper: toggle a boolean setting
     pub fn toggle_settings_bool(draft: &mut CliConfig, tab: usize, row: usize) {
         match tab {
-            1 => {
-                if row == 10 {
-                    draft.engine.accept_invalid_certs = !draft.engine.accept_invalid_certs;
-                }
+            1 if row == 10 => {
+                draft.engine.accept_invalid_certs = !draft.engine.accept_invalid_certs;
             }
             2 => match row {
                 0 => draft.engine.enable_dht = !draft.engine.enable_dht,
@@ -1363,7 +1838,6 @@ impl TuiApp {
                 3 => "Show Peers",
                 _ => "",
             },
-            4 => "Schedule Rules (read-only)",
             _ => "",
         }
     }
@@ -1375,12 +1849,11 @@ impl TuiApp {
             1 => 11,
             2 => 5,
             3 => 4,
-            4 => 1,
             _ => 0,
         }
     }
 
-    // Settings helper: tab names
+    // Settings helper: tab names; the Schedule tab has its own editor
     pub fn settings_tab_names() -> &'static [&'static str] {
         &["General", "Network", "BitTorrent", "Interface", "Schedule"]
     }
@@ -1489,3 +1962,30 @@ fn restore_terminal(mut terminal: Terminal<CrosstermBackend<Stdout>>) -> Result<
     terminal.show_cursor()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+    use gosh_dl::DownloadKind;
+
+    #[test]
+    fn search_scopes() {
+        let dl = status(DownloadKind::Http, DownloadState::Paused, 10, 0);
+        let mut store = Store::default();
+        store.entry(dl.id).tags = vec!["work".to_string()];
+        let search = |query: &str, scope| SearchState {
+            query: query.to_string(),
+            cursor: 0,
+            scope,
+        };
+
+        assert!(search("FILE", SearchScope::Name).matches(&dl, &store));
+        assert!(search("paus", SearchScope::State).matches(&dl, &store));
+        assert!(search("wor", SearchScope::Tag).matches(&dl, &store));
+        assert!(!search("work", SearchScope::Name).matches(&dl, &store));
+        assert!(search("work", SearchScope::All).matches(&dl, &store));
+        assert!(!search("iso", SearchScope::All).matches(&dl, &store));
+        assert!(search("", SearchScope::Tag).matches(&dl, &store));
+    }
+}
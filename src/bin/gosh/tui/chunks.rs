@@ -0,0 +1,418 @@
+//! Piece and segment maps for the chunk map panel
+//!
+//! The engine does not expose live piece or segment state, so the map is built
+//! from what it persists: HTTP segment progress is written to the database
+//! every few seconds, and a torrent's pieces are found by hashing the data on
+//! disk in the background (the same check `gosh seed` does). Nothing tells
+//! which pieces arrived since, so that check runs once per selection and the
+//! map is a snapshot of that moment. Nor is there per-piece availability,
+//! only each peer's overall progress.
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use gosh_dl::torrent::{Metainfo, PieceManager};
+use gosh_dl::{
+    DownloadId, DownloadKind, DownloadState, DownloadStatus, Segment, SegmentState, SqliteStorage,
+    Storage,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use super::app::ChunkState;
+
+/// How often persisted state is re-read from the database
+const DB_REFRESH: Duration = Duration::from_secs(1);
+
+/// Byte blocks an HTTP download is split into before downsampling
+const HTTP_BLOCKS: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkKind {
+    Pieces,
+    Segments,
+}
+
+/// The map shown for the selected download
+#[derive(Debug, Clone)]
+pub struct ChunkMap {
+    pub kind: ChunkKind,
+    /// Torrent pieces, or fixed-size byte blocks of an HTTP download
+    pub states: Vec<ChunkState>,
+    /// Number of HTTP segments (connections)
+    pub segments: usize,
+    /// Distributed copies of the whole torrent among connected peers
+    /// (torrents only)
+    pub availability: Option<f64>,
+    /// When the pieces were checked, for a snapshot of a torrent's data
+    pub checked_at: Option<DateTime<Local>>,
+}
+
+impl ChunkMap {
+    /// Whether the pieces have not been checked yet
+    pub fn is_unknown(&self) -> bool {
+        self.states.iter().all(|s| *s == ChunkState::Unknown)
+    }
+
+    pub fn complete(&self) -> usize {
+        self.states
+            .iter()
+            .filter(|s| **s == ChunkState::Complete)
+            .count()
+    }
+}
+
+/// Loads the chunk map for the selected download, throttled per kind
+pub struct ChunkLoader {
+    database_path: PathBuf,
+    storage: Option<Arc<SqliteStorage>>,
+    selected: Option<DownloadId>,
+    map: Option<ChunkMap>,
+    last_load: Option<Instant>,
+    metainfo: Option<Arc<Metainfo>>,
+    verify: Option<JoinHandle<Result<Vec<ChunkState>>>>,
+    /// The selected torrent's data has been hashed, or is being hashed
+    verified: bool,
+}
+
+impl ChunkLoader {
+    pub fn new(database_path: PathBuf) -> Self {
+        Self {
+            database_path,
+            storage: None,
+            selected: None,
+            map: None,
+            last_load: None,
+            metainfo: None,
+            verify: None,
+            verified: false,
+        }
+    }
+
+    pub fn map(&self) -> Option<&ChunkMap> {
+        self.map.as_ref()
+    }
+
+    /// Bring the map up to date with the selected download
+    pub async fn refresh(&mut self, dl: Option<&DownloadStatus>) {
+        let Some(dl) = dl else {
+            self.reset(None);
+            return;
+        };
+        if self.selected != Some(dl.id) {
+            self.reset(Some(dl.id));
+        }
+
+        match dl.kind {
+            DownloadKind::Http => self.refresh_segments(dl).await,
+            DownloadKind::Torrent | DownloadKind::Magnet => self.refresh_pieces(dl).await,
+        }
+    }
+
+    fn reset(&mut self, selected: Option<DownloadId>) {
+        if let Some(task) = self.verify.take() {
+            task.abort();
+        }
+        self.selected = selected;
+        self.map = None;
+        self.last_load = None;
+        self.metainfo = None;
+        self.verified = false;
+    }
+
+    async fn storage(&mut self) -> Option<Arc<SqliteStorage>> {
+        if self.storage.is_none() {
+            match SqliteStorage::new(&self.database_path).await {
+                Ok(storage) => self.storage = Some(Arc::new(storage)),
+                Err(e) => tracing::debug!("Chunk map cannot open database: {}", e),
+            }
+        }
+        self.storage.clone()
+    }
+
+    async fn refresh_segments(&mut self, dl: &DownloadStatus) {
+        if self.last_load.is_some_and(|t| t.elapsed() < DB_REFRESH) {
+            return;
+        }
+        self.last_load = Some(Instant::now());
+
+        let total = dl.progress.total_size.unwrap_or(0);
+        if total == 0 {
+            self.map = None;
+            return;
+        }
+
+        let mut segments = match self.storage().await {
+            Some(storage) => storage.load_segments(dl.id).await.unwrap_or_default(),
+            None => Vec::new(),
+        };
+        // Completed downloads and single-connection streams have no segment
+        // rows; both fill the file front to back.
+        if segments.is_empty() || dl.state == DownloadState::Completed {
+            let mut stream = Segment::new(0, 0, total - 1);
+            stream.downloaded = dl.progress.completed_size.min(total);
+            if dl.state == DownloadState::Downloading {
+                stream.state = SegmentState::Downloading;
+            }
+            segments = vec![stream];
+        }
+
+        self.map = Some(ChunkMap {
+            kind: ChunkKind::Segments,
+            states: segment_blocks(&segments, total),
+            segments: segments.len(),
+            availability: None,
+            checked_at: None,
+        });
+    }
+
+    async fn refresh_pieces(&mut self, dl: &DownloadStatus) {
+        let availability = dl
+            .peers
+            .as_ref()
+            .filter(|peers| !peers.is_empty())
+            .map(|peers| peers.iter().map(|p| p.progress.clamp(0.0, 1.0)).sum());
+        let pieces = |states, checked_at| ChunkMap {
+            kind: ChunkKind::Pieces,
+            states,
+            segments: 0,
+            availability,
+            checked_at,
+        };
+
+        // Finished torrents have every piece; no need to hash the data again
+        if dl.state == DownloadState::Completed {
+            if let Some(ti) = &dl.torrent_info {
+                self.map = Some(pieces(vec![ChunkState::Complete; ti.pieces_count], None));
+            }
+            return;
+        }
+
+        if let Some(task) = self.verify.take_if(|task| task.is_finished()) {
+            match task.await {
+                Ok(Ok(states)) => self.map = Some(pieces(states, Some(Local::now()))),
+                Ok(Err(e)) => tracing::debug!("Piece check for {} failed: {}", dl.id, e),
+                Err(_) => {}
+            }
+        }
+        match &mut self.map {
+            Some(map) => map.availability = availability,
+            // Unknown until the check is done
+            None => {
+                if let Some(ti) = &dl.torrent_info {
+                    self.map = Some(pieces(vec![ChunkState::Unknown; ti.pieces_count], None));
+                }
+            }
+        }
+
+        // Hashing the data again while it downloads would compete with it for
+        // the disk, so it is checked once per selection
+        if self.verified {
+            return;
+        }
+
+        if self.metainfo.is_none() {
+            if self.last_load.is_some_and(|t| t.elapsed() < DB_REFRESH) {
+                return;
+            }
+            self.last_load = Some(Instant::now());
+            let Some(storage) = self.storage().await else {
+                return;
+            };
+            // Magnets have nothing stored until their metadata arrives
+            let Ok(Some(data)) = storage.load_torrent_data(dl.id).await else {
+                return;
+            };
+            match Metainfo::parse(&data) {
+                Ok(metainfo) => self.metainfo = Some(Arc::new(metainfo)),
+                Err(e) => {
+                    tracing::debug!("Stored torrent data for {} is invalid: {}", dl.id, e);
+                    return;
+                }
+            }
+        }
+        let Some(metainfo) = self.metainfo.clone() else {
+            return;
+        };
+
+        let save_dir = dl.metadata.save_dir.clone();
+        self.verified = true;
+        self.verify = Some(tokio::spawn(async move {
+            let pieces = PieceManager::new(metainfo, save_dir);
+            pieces.verify_existing().await?;
+            Ok((0..pieces.num_pieces())
+                .map(|i| {
+                    if pieces.have_piece(i) {
+                        ChunkState::Complete
+                    } else {
+                        ChunkState::Pending
+                    }
+                })
+                .collect())
+        }));
+    }
+}
+
+/// Split an HTTP download into fixed-size blocks and mark each from the
+/// segment covering it. The block at the edge of an active segment's
+/// downloaded prefix is the one being written.
+fn segment_blocks(segments: &[Segment], total: u64) -> Vec<ChunkState> {
+    let block_size = total.div_ceil(HTTP_BLOCKS).max(1);
+    let count = total.div_ceil(block_size);
+
+    (0..count)
+        .map(|i| {
+            let start = i * block_size;
+            let end = (start + block_size).min(total);
+            let Some(segment) = segments.iter().find(|s| start >= s.start && start <= s.end) else {
+                return ChunkState::Pending;
+            };
+
+            let done_to = segment.start + segment.downloaded;
+            if segment.state == SegmentState::Completed || end.min(segment.end + 1) <= done_to {
+                ChunkState::Complete
+            } else if matches!(segment.state, SegmentState::Failed { .. }) {
+                ChunkState::Failed
+            } else if segment.state == SegmentState::Downloading && start <= done_to {
+                ChunkState::Downloading
+            } else {
+                ChunkState::Pending
+            }
+        })
+        .collect()
+}
+
+/// One cell of the downsampled map
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkCell {
+    /// Unknown if all chunks in the cell are, Failed or Downloading if any
+    /// is, otherwise Complete when all are and Pending when not
+    pub state: ChunkState,
+    /// Share of the cell's chunks that are complete
+    pub complete: f64,
+}
+
+/// Summarise `states` into at most `cells` cells, each covering an even share
+pub fn downsample(states: &[ChunkState], cells: usize) -> Vec<ChunkCell> {
+    let cells = cells.min(states.len());
+    (0..cells)
+        .map(|cell| {
+            let group = &states[cell * states.len() / cells..(cell + 1) * states.len() / cells];
+            let complete = group.iter().filter(|s| **s == ChunkState::Complete).count();
+            let state = if group.iter().all(|s| *s == ChunkState::Unknown) {
+                ChunkState::Unknown
+            } else if group.contains(&ChunkState::Failed) {
+                ChunkState::Failed
+            } else if group.contains(&ChunkState::Downloading) {
+                ChunkState::Downloading
+            } else if complete == group.len() {
+                ChunkState::Complete
+            } else {
+                ChunkState::Pending
+            };
+            ChunkCell {
+                state,
+                complete: complete as f64 / group.len() as f64,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::status;
+    use gosh_dl::TorrentStatusInfo;
+
+    #[test]
+    fn segments_fill_from_their_start() {
+        let mut first = Segment::new(0, 0, 4095);
+        first.downloaded = 2048;
+        first.state = SegmentState::Downloading;
+        let mut second = Segment::new(1, 4096, 8191);
+        second.state = SegmentState::Failed {
+            error: "reset".to_string(),
+            retries: 1,
+        };
+
+        let blocks = segment_blocks(&[first, second], 8192);
+        assert_eq!(blocks.len(), 1024);
+        assert_eq!(blocks[0], ChunkState::Complete);
+        assert_eq!(blocks[255], ChunkState::Complete);
+        assert_eq!(blocks[256], ChunkState::Downloading);
+        assert_eq!(blocks[257], ChunkState::Pending);
+        assert_eq!(blocks[512], ChunkState::Failed);
+    }
+
+    #[test]
+    fn downsample_spreads_chunks_evenly() {
+        let mut states = vec![ChunkState::Complete; 6];
+        states.extend([ChunkState::Pending; 3]);
+        states.push(ChunkState::Failed);
+
+        let cells = downsample(&states, 4);
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells[0].state, ChunkState::Complete);
+        assert_eq!(cells[2].state, ChunkState::Pending);
+        assert_eq!(cells[2].complete, 0.5);
+        assert_eq!(cells[3].state, ChunkState::Failed);
+
+        assert_eq!(downsample(&states[..2], 10).len(), 2);
+        assert!(downsample(&[], 10).is_empty());
+    }
+
+    /// Wait for the piece check to finish and pick up its result
+    async fn checked(loader: &mut ChunkLoader, dl: &DownloadStatus) {
+        while !loader.verify.as_ref().unwrap().is_finished() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        loader.refresh(Some(dl)).await;
+    }
+
+    #[tokio::test]
+    async fn pieces_are_checked_once_per_selection() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let database = tempdir.path().join("gosh.db");
+        // One piece: the SHA-1 of "hello gosh\n"
+        let mut torrent =
+            b"d4:infod6:lengthi11e4:name5:a.txt12:piece lengthi16384e6:pieces20:".to_vec();
+        torrent.extend_from_slice(
+            b"\x30\x6a\x59\xa5\xdf\xa7\x4e\xcb\xe7\x55\xe1\x8b\x56\x52\x55\xd6\xc9\x6b\xbb\x1c",
+        );
+        torrent.extend_from_slice(b"ee");
+
+        let mut dl = status(DownloadKind::Torrent, DownloadState::Downloading, 11, 0);
+        dl.metadata.save_dir = tempdir.path().to_path_buf();
+        dl.torrent_info = Some(TorrentStatusInfo {
+            files: Vec::new(),
+            piece_length: 16384,
+            pieces_count: 1,
+            private: false,
+        });
+        let storage = SqliteStorage::new(&database).await.unwrap();
+        storage.save_download(&dl).await.unwrap();
+        storage.save_torrent_data(dl.id, &torrent).await.unwrap();
+
+        let mut loader = ChunkLoader::new(database);
+        loader.refresh(Some(&dl)).await;
+        assert!(loader.map().unwrap().is_unknown());
+        checked(&mut loader, &dl).await;
+        let map = loader.map().unwrap();
+        assert_eq!(map.states, vec![ChunkState::Pending]);
+        assert!(map.checked_at.is_some());
+
+        // Data arriving later does not trigger another check
+        std::fs::write(tempdir.path().join("a.txt"), "hello gosh\n").unwrap();
+        dl.progress.completed_size = 11;
+        loader.refresh(Some(&dl)).await;
+        assert!(loader.verify.is_none());
+        assert_eq!(loader.map().unwrap().states, vec![ChunkState::Pending]);
+
+        // Selecting it again does
+        loader.refresh(None).await;
+        loader.refresh(Some(&dl)).await;
+        checked(&mut loader, &dl).await;
+        assert_eq!(loader.map().unwrap().states, vec![ChunkState::Complete]);
+    }
+}
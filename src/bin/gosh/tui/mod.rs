@@ -1,5 +1,7 @@
 mod app;
+mod chunks;
 mod event;
+mod schedule_editor;
 mod theme;
 mod ui;
 mod widgets;
@@ -0,0 +1,274 @@
+//! The Schedule tab of the settings dialog
+//!
+//! The tab lists the rules of the draft config. Opening one shows its fields,
+//! with a day picker for the days it runs on; every field is checked as it is
+//! entered, so the draft always holds rules that `CliConfig::validate` accepts.
+
+use anyhow::Result;
+use chrono::Weekday;
+use crossterm::event::KeyCode;
+
+use crate::config::ScheduleRuleConfig;
+use crate::schedule::{format_days, parse_days, ClockTime, Rule, RuleAction};
+use crate::util::parse_speed;
+
+pub const FIELDS: [&str; 6] = [
+    "Start",
+    "End",
+    "Days",
+    "Download Limit",
+    "Upload Limit",
+    "Actions",
+];
+
+/// Row of the day picker in the rule view
+pub const DAYS_ROW: usize = 2;
+
+pub const WEEK: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// The rule open for editing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleEditor {
+    pub index: usize,
+    pub row: usize,
+    /// Day under the cursor in the day picker
+    pub day: usize,
+}
+
+/// What a key press did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOutcome {
+    /// Not for the Schedule tab; handle it as on any other tab
+    Ignored,
+    Handled,
+    Changed,
+}
+
+pub fn new_rule() -> ScheduleRuleConfig {
+    ScheduleRuleConfig {
+        start: "22:00".to_string(),
+        end: "06:00".to_string(),
+        days: "all".to_string(),
+        ..Default::default()
+    }
+}
+
+/// One-line description for the rule list
+pub fn summary(rule: &ScheduleRuleConfig) -> String {
+    match Rule::from_config(rule) {
+        Ok(rule) => rule.to_string(),
+        Err(_) => format!("{}-{} {} (invalid)", rule.start, rule.end, rule.days),
+    }
+}
+
+pub fn field_value(rule: &ScheduleRuleConfig, row: usize) -> String {
+    match row {
+        0 => rule.start.clone(),
+        1 => rule.end.clone(),
+        DAYS_ROW => rule.days.clone(),
+        3 => rule.download_limit.clone().unwrap_or_default(),
+        4 => rule.upload_limit.clone().unwrap_or_default(),
+        5 => rule.actions.join(", "),
+        _ => String::new(),
+    }
+}
+
+/// Set a text field from the edit buffer, rejecting invalid values
+pub fn apply_field(rule: &mut ScheduleRuleConfig, row: usize, val: &str) -> Result<()> {
+    let val = val.trim();
+    match row {
+        0 => rule.start = val.parse::<ClockTime>()?.to_string(),
+        1 => rule.end = val.parse::<ClockTime>()?.to_string(),
+        3 | 4 => {
+            let limit = if val.is_empty() {
+                None
+            } else {
+                parse_speed(val)?;
+                Some(val.to_string())
+            };
+            if row == 3 {
+                rule.download_limit = limit;
+            } else {
+                rule.upload_limit = limit;
+            }
+        }
+        5 => {
+            rule.actions = val
+                .split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .map(|a| a.parse::<RuleAction>().map(|a| a.to_string()))
+                .collect::<Result<_>>()?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Whether the rule runs on `WEEK[day]`
+pub fn day_enabled(rule: &ScheduleRuleConfig, day: usize) -> bool {
+    parse_days(&rule.days).is_ok_and(|days| days.is_empty() || days.contains(&WEEK[day]))
+}
+
+/// Flip one day, keeping at least one selected
+pub fn toggle_day(rule: &mut ScheduleRuleConfig, day: usize) {
+    let mut days = parse_days(&rule.days).unwrap_or_default();
+    if days.is_empty() {
+        days = WEEK.to_vec();
+    }
+    if let Some(pos) = days.iter().position(|d| *d == WEEK[day]) {
+        if days.len() > 1 {
+            days.remove(pos);
+        }
+    } else {
+        days.push(WEEK[day]);
+    }
+    rule.days = format_days(&days);
+}
+
+/// Handle a key on the Schedule tab while no field is being typed into.
+///
+/// In the rule list `selected_row` picks a rule, with an extra "add" row at
+/// the end; `editing` is set when a text field of an open rule is entered.
+pub fn handle_key(
+    code: KeyCode,
+    rules: &mut Vec<ScheduleRuleConfig>,
+    selected_row: &mut usize,
+    editor: &mut Option<RuleEditor>,
+    editing: &mut Option<String>,
+) -> KeyOutcome {
+    if let Some(open) = editor {
+        let Some(rule) = rules.get_mut(open.index) else {
+            *editor = None;
+            return KeyOutcome::Handled;
+        };
+        match code {
+            KeyCode::Esc => {
+                *selected_row = open.index;
+                *editor = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => open.row = open.row.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => open.row = (open.row + 1).min(FIELDS.len() - 1),
+            KeyCode::Left | KeyCode::Char('h') if open.row == DAYS_ROW => {
+                open.day = open.day.saturating_sub(1)
+            }
+            KeyCode::Right | KeyCode::Char('l') if open.row == DAYS_ROW => {
+                open.day = (open.day + 1).min(WEEK.len() - 1)
+            }
+            KeyCode::Enter | KeyCode::Char(' ') if open.row == DAYS_ROW => {
+                toggle_day(rule, open.day);
+                return KeyOutcome::Changed;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => *editing = Some(field_value(rule, open.row)),
+            // Stay on the tab while a rule is open
+            KeyCode::Left | KeyCode::Right | KeyCode::Char('1'..='5') => {}
+            _ => return KeyOutcome::Ignored,
+        }
+        return KeyOutcome::Handled;
+    }
+
+    let open = |index| RuleEditor {
+        index,
+        row: 0,
+        day: 0,
+    };
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => *selected_row = selected_row.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => *selected_row = (*selected_row + 1).min(rules.len()),
+        KeyCode::Enter | KeyCode::Char(' ') if *selected_row < rules.len() => {
+            *editor = Some(open(*selected_row))
+        }
+        KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('a') => {
+            rules.push(new_rule());
+            *selected_row = rules.len() - 1;
+            *editor = Some(open(*selected_row));
+            return KeyOutcome::Changed;
+        }
+        KeyCode::Char('d') | KeyCode::Delete if *selected_row < rules.len() => {
+            rules.remove(*selected_row);
+            return KeyOutcome::Changed;
+        }
+        KeyCode::Char('K') if *selected_row > 0 && *selected_row < rules.len() => {
+            rules.swap(*selected_row, *selected_row - 1);
+            *selected_row -= 1;
+            return KeyOutcome::Changed;
+        }
+        KeyCode::Char('J') if *selected_row + 1 < rules.len() => {
+            rules.swap(*selected_row, *selected_row + 1);
+            *selected_row += 1;
+            return KeyOutcome::Changed;
+        }
+        _ => return KeyOutcome::Ignored,
+    }
+    KeyOutcome::Handled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_are_validated() {
+        let mut rule = new_rule();
+
+        assert!(apply_field(&mut rule, 0, "7:30").is_ok());
+        assert_eq!(rule.start, "07:30");
+        assert!(apply_field(&mut rule, 1, "25:00").is_err());
+        assert_eq!(rule.end, "06:00");
+
+        assert!(apply_field(&mut rule, 3, "2M").is_ok());
+        assert!(apply_field(&mut rule, 4, "fast").is_err());
+        assert_eq!(rule.download_limit.as_deref(), Some("2M"));
+        assert!(apply_field(&mut rule, 3, "").is_ok());
+        assert_eq!(rule.download_limit, None);
+
+        assert!(apply_field(&mut rule, 5, "pause_all, max_concurrent_downloads = 2").is_ok());
+        assert_eq!(rule.actions, ["pause_all", "max_concurrent_downloads=2"]);
+        assert!(apply_field(&mut rule, 5, "pause_all, nap").is_err());
+    }
+
+    #[test]
+    fn day_picker_keeps_one_day() {
+        let mut rule = new_rule();
+        toggle_day(&mut rule, 6);
+        assert_eq!(rule.days, "mon,tue,wed,thu,fri,sat");
+        toggle_day(&mut rule, 5);
+        assert_eq!(rule.days, "weekdays");
+
+        rule.days = "wed".to_string();
+        toggle_day(&mut rule, 2);
+        assert_eq!(rule.days, "wed");
+        toggle_day(&mut rule, 2 + 2);
+        assert!(day_enabled(&rule, 4) && !day_enabled(&rule, 0));
+    }
+
+    #[test]
+    fn list_adds_reorders_and_deletes() {
+        let mut rules = vec![new_rule()];
+        let (mut row, mut editor, mut editing) = (0, None, None);
+        let mut press =
+            |code, rules: &mut Vec<_>| handle_key(code, rules, &mut row, &mut editor, &mut editing);
+
+        assert_eq!(press(KeyCode::Char('a'), &mut rules), KeyOutcome::Changed);
+        assert_eq!(rules.len(), 2);
+        // The new rule opens; Left stays on the tab, Esc goes back to the list
+        assert_eq!(press(KeyCode::Left, &mut rules), KeyOutcome::Handled);
+        assert_eq!(press(KeyCode::Esc, &mut rules), KeyOutcome::Handled);
+        assert_eq!(press(KeyCode::Esc, &mut rules), KeyOutcome::Ignored);
+
+        rules[1].start = "01:00".to_string();
+        assert_eq!(press(KeyCode::Char('K'), &mut rules), KeyOutcome::Changed);
+        assert_eq!(rules[0].start, "01:00");
+        assert_eq!(press(KeyCode::Char('d'), &mut rules), KeyOutcome::Changed);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].start, "22:00");
+    }
+}
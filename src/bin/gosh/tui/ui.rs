@@ -6,7 +6,7 @@ use ratatui::{
 use super::app::{LayoutMode, SearchState, TuiApp};
 use super::widgets::{
     activity_log, batch_import, chunk_map, details_panel, dialogs, download_list, header, logo,
-    net_graph, settings, status_bar, tab_bar, toasts,
+    net_graph, peers_panel, settings, status_bar, tab_bar, toasts,
 };
 
 /// Main render function
@@ -92,13 +92,14 @@ fn render_two_column(frame: &mut Frame, app: &mut TuiApp) {
     download_list::render_download_list(frame, left[2], app);
 
     // Right column: net graph, details/activity log, optional chunk map
-    let has_chunks = !app.chunk_states.is_empty();
-    let chunk_height = if has_chunks {
-        let rows_needed =
-            (app.chunk_count as u16).div_ceil(main_cols[1].width.saturating_sub(4).max(1)) + 2;
-        rows_needed.min(8)
-    } else {
-        0
+    let chunk_map = app.chunk_map();
+    let chunk_height = match chunk_map {
+        Some(map) if !map.states.is_empty() => {
+            let rows_needed =
+                (map.states.len() as u16).div_ceil(main_cols[1].width.saturating_sub(4).max(1)) + 2;
+            rows_needed.min(8)
+        }
+        _ => 0,
     };
 
     let right = Layout::vertical([
@@ -113,12 +114,14 @@ fn render_two_column(frame: &mut Frame, app: &mut TuiApp) {
     // Details or Activity log
     if app.show_activity_log {
         activity_log::render_activity_log(frame, right[1], app);
+    } else if app.showing_peers() {
+        peers_panel::render_peers(frame, right[1], app);
     } else {
         details_panel::render_details(frame, right[1], app);
     }
     // Chunk map (only when chunks exist)
-    if has_chunks && chunk_height > 0 {
-        chunk_map::render_chunk_map(frame, right[2], app);
+    if let Some(map) = chunk_map.filter(|_| chunk_height > 0) {
+        chunk_map::render_chunk_map(frame, right[2], app, map);
     }
 
     // Status bar
@@ -136,7 +139,11 @@ fn render_single_column(frame: &mut Frame, app: &mut TuiApp) {
 
     header::render_header(frame, chunks[0], app);
     download_list::render_download_list(frame, chunks[1], app);
-    details_panel::render_details(frame, chunks[2], app);
+    if app.showing_peers() {
+        peers_panel::render_peers(frame, chunks[2], app);
+    } else {
+        details_panel::render_details(frame, chunks[2], app);
+    }
     status_bar::render_status_bar(frame, chunks[3], app);
 }
 
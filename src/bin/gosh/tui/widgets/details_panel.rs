@@ -6,13 +6,17 @@ use ratatui::{
 use super::btop_border::btop_block;
 use super::download_item::connection_quality;
 use crate::format::{format_duration, format_size, format_speed, format_state};
-use crate::tui::app::TuiApp;
+use crate::tui::app::{RightPanelFocus, TuiApp};
 use crate::util::truncate_str;
 
 pub fn render_details(frame: &mut Frame, area: Rect, app: &TuiApp) {
     let theme = app.theme();
 
-    let block = btop_block("Details", theme, false);
+    let block = btop_block(
+        "Details",
+        theme,
+        app.right_panel_focus == RightPanelFocus::Details,
+    );
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -31,31 +35,55 @@ pub fn render_details(frame: &mut Frame, area: Rect, app: &TuiApp) {
             .map(format_size)
             .unwrap_or_else(|| "Unknown".to_string());
         let completed = format_size(dl.progress.completed_size);
-        let state = format_state(&dl.state);
-        let state_color = theme.state_color(&dl.state);
+        let (state, state_color) = match app.hold(dl) {
+            Some(hold) => (hold.label(), theme.info),
+            None => (format_state(&dl.state), theme.state_color(&dl.state)),
+        };
 
         // Connection quality indicator
         let quality = connection_quality(dl.progress.connections);
 
+        let mut name_line = vec![
+            Span::styled("  Name: ", Style::default().fg(theme.overlay1)),
+            Span::styled(&dl.metadata.name, Style::default().fg(theme.text)),
+        ];
+        let tags = app.tags(dl);
+        if !tags.is_empty() {
+            name_line.push(Span::styled(
+                "  \u{2502}  ",
+                Style::default().fg(theme.surface2),
+            ));
+            name_line.push(Span::styled("Tags: ", Style::default().fg(theme.overlay1)));
+            name_line.push(Span::styled(
+                tags.join(", "),
+                Style::default().fg(theme.info),
+            ));
+        }
+
+        let mut state_line = vec![
+            Span::styled(" State: ", Style::default().fg(theme.overlay1)),
+            Span::styled(state, Style::default().fg(state_color)),
+        ];
+        if let Some(retry) = app.next_retry(dl) {
+            state_line.push(Span::styled("  ", Style::default()));
+            state_line.push(Span::styled(retry, Style::default().fg(theme.info)));
+        }
+        state_line.extend([
+            Span::styled("  \u{2502}  ", Style::default().fg(theme.surface2)),
+            Span::styled(
+                format!("{:.1}%", dl.progress.percentage()),
+                Style::default().fg(theme.text),
+            ),
+            Span::styled("  \u{2502}  ", Style::default().fg(theme.surface2)),
+            Span::styled(
+                format!("{} / {}", completed, total),
+                Style::default().fg(theme.subtext0),
+            ),
+        ]);
+
         let meta_lines = vec![
-            Line::from(vec![
-                Span::styled("  Name: ", Style::default().fg(theme.overlay1)),
-                Span::styled(&dl.metadata.name, Style::default().fg(theme.text)),
-            ]),
-            Line::from(vec![
-                Span::styled(" State: ", Style::default().fg(theme.overlay1)),
-                Span::styled(state, Style::default().fg(state_color)),
-                Span::styled("  \u{2502}  ", Style::default().fg(theme.surface2)),
-                Span::styled(
-                    format!("{:.1}%", dl.progress.percentage()),
-                    Style::default().fg(theme.text),
-                ),
-                Span::styled("  \u{2502}  ", Style::default().fg(theme.surface2)),
-                Span::styled(
-                    format!("{} / {}", completed, total),
-                    Style::default().fg(theme.subtext0),
-                ),
-            ]),
+            Line::from(name_line),
+            Line::from(state_line),
             Line::from(vec![
                 Span::styled(" Speed: ", Style::default().fg(theme.overlay1)),
                 Span::styled(
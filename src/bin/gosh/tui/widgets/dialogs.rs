@@ -31,6 +31,8 @@ pub fn render_help_dialog(frame: &mut Frame, app: &TuiApp) {
       r        Resume selected\n\
       c        Cancel selected\n\
       d        Cancel and delete files\n\
+      R        Retry selected (or all) failed\n\
+      C        Clear completed downloads\n\
       /        Search/filter downloads\n\
       S        Open settings\n\
     \n\
@@ -38,6 +40,8 @@ pub fn render_help_dialog(frame: &mut Frame, app: &TuiApp) {
       1        All downloads\n\
       2        Active only\n\
       3        Completed only\n\
+      Tab      Cycle graph/details/chunks/peers\n\
+      o/O      Sort peers by next column/reverse\n\
     \n\
     Other:\n\
       ?        Toggle this help\n\
@@ -165,6 +169,68 @@ pub fn render_dialog(frame: &mut Frame, dialog: &DialogState, app: &TuiApp) {
             let paragraph = Paragraph::new(content).block(block);
             frame.render_widget(paragraph, area);
         }
+        DialogState::ConfirmRetry { ids, permanent } => {
+            let area = centered_rect(50, 20, frame.area());
+            frame.render_widget(Clear, area);
+
+            let content = vec![
+                Line::from(""),
+                Line::from(vec![Span::styled(
+                    format!(
+                        "  {} of {} download(s) failed with an error",
+                        permanent,
+                        ids.len()
+                    ),
+                    Style::default().fg(theme.warning),
+                )]),
+                Line::from(vec![Span::styled(
+                    "  that retrying may not fix. Retry anyway?",
+                    Style::default().fg(theme.text),
+                )]),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(" y ", Style::default().fg(theme.bg_deep).bg(theme.success)),
+                    Span::styled(" Yes  ", Style::default().fg(theme.subtext0)),
+                    Span::styled(" n ", Style::default().fg(theme.bg_deep).bg(theme.error)),
+                    Span::styled(" No ", Style::default().fg(theme.subtext0)),
+                ]),
+            ];
+
+            let block = btop_block("Confirm", theme, true).style(Style::default().bg(theme.bg));
+
+            let paragraph = Paragraph::new(content).block(block);
+            frame.render_widget(paragraph, area);
+        }
+        DialogState::ConfirmClear { ids } => {
+            let area = centered_rect(50, 20, frame.area());
+            frame.render_widget(Clear, area);
+
+            let content = vec![
+                Line::from(""),
+                Line::from(vec![Span::styled(
+                    format!("  Clear {} completed download(s)?", ids.len()),
+                    Style::default().fg(theme.text),
+                )]),
+                Line::from(vec![Span::styled(
+                    "  Downloaded files are kept.",
+                    Style::default().fg(theme.subtext0),
+                )]),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(" y ", Style::default().fg(theme.bg_deep).bg(theme.success)),
+                    Span::styled(" Yes  ", Style::default().fg(theme.subtext0)),
+                    Span::styled(" n ", Style::default().fg(theme.bg_deep).bg(theme.error)),
+                    Span::styled(" No ", Style::default().fg(theme.subtext0)),
+                ]),
+            ];
+
+            let block = btop_block("Confirm", theme, true).style(Style::default().bg(theme.bg));
+
+            let paragraph = Paragraph::new(content).block(block);
+            frame.render_widget(paragraph, area);
+        }
         DialogState::Error { message } => {
             let area = centered_rect(50, 20, frame.area());
             frame.render_widget(Clear, area);
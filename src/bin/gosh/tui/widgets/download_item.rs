@@ -8,6 +8,7 @@ use ratatui::{
 
 use super::gradient_bar::render_gradient_bar;
 use crate::format::{format_duration, format_speed, format_state};
+use crate::hold::Hold;
 use crate::tui::theme::Theme;
 use crate::util::truncate_str;
 
@@ -23,6 +24,7 @@ pub fn render_download_item(
     frame: &mut Frame,
     area: Rect,
     dl: &DownloadStatus,
+    hold: Option<Hold>,
     is_selected: bool,
     theme: &Theme,
     spinner: &str,
@@ -38,9 +40,15 @@ pub fn render_download_item(
         DownloadState::Error { .. } => "\u{2717}",
     };
 
-    let state_color = theme.state_color(&dl.state);
+    let (state_icon, state_color, state_label) = match hold {
+        Some(hold) => ("\u{23f2}", theme.info, hold.label()),
+        None => (
+            state_icon,
+            theme.state_color(&dl.state),
+            format_state(&dl.state),
+        ),
+    };
     let name = truncate_str(&dl.metadata.name, area.width.saturating_sub(20) as usize);
-    let state_label = format_state(&dl.state);
 
     let selector = if is_selected { "\u{25b6}" } else { " " };
     let bg = if is_selected {
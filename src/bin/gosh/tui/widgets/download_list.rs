@@ -61,7 +61,8 @@ pub fn render_download_list(frame: &mut Frame, area: Rect, app: &mut TuiApp) {
         }
 
         let item_area = Rect::new(inner.x, y, inner.width, lines_per_item as u16);
-        render_download_item(frame, item_area, dl, is_selected, &theme, spinner);
+        let hold = app.hold(dl);
+        render_download_item(frame, item_area, dl, hold, is_selected, &theme, spinner);
     }
 
     // Scrollbar
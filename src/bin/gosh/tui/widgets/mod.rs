@@ -10,6 +10,7 @@ pub mod gradient_bar;
 pub mod header;
 pub mod logo;
 pub mod net_graph;
+pub mod peers_panel;
 pub mod settings;
 pub mod status_bar;
 pub mod tab_bar;
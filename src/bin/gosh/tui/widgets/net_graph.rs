@@ -2,7 +2,7 @@ use ratatui::prelude::*;
 
 use super::btop_border::btop_block;
 use crate::format::format_speed;
-use crate::tui::app::TuiApp;
+use crate::tui::app::{RightPanelFocus, TuiApp};
 
 const BLOCKS: [char; 8] = [
     '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}',
@@ -10,7 +10,11 @@ const BLOCKS: [char; 8] = [
 
 pub fn render_net_graph(frame: &mut Frame, area: Rect, app: &TuiApp) {
     let theme = app.theme();
-    let block = btop_block("Network", theme, false);
+    let block = btop_block(
+        "Network",
+        theme,
+        app.right_panel_focus == RightPanelFocus::Graph,
+    );
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
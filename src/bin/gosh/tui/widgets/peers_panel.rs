@@ -0,0 +1,171 @@
+use gosh_dl::{DownloadKind, PeerInfo};
+use ratatui::{
+    prelude::*,
+    widgets::{Cell, Paragraph, Row, Table},
+};
+use std::cmp::Ordering;
+
+use super::btop_border::btop_block;
+use crate::format::format_speed;
+use crate::tui::app::{PeerColumn, PeerSort, TuiApp};
+use crate::util::truncate_str;
+
+pub fn render_peers(frame: &mut Frame, area: Rect, app: &TuiApp) {
+    let theme = app.theme();
+
+    let peers: Vec<&PeerInfo> = app
+        .selected_download()
+        .and_then(|dl| dl.peers.as_ref())
+        .map(|peers| peers.iter().collect())
+        .unwrap_or_default();
+
+    let block = btop_block(&format!("Peers ({})", peers.len()), theme, true);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let is_torrent = app
+        .selected_download()
+        .is_some_and(|dl| dl.kind != DownloadKind::Http);
+    if peers.is_empty() {
+        let msg = if is_torrent {
+            "No peers connected"
+        } else {
+            "Peers are only shown for torrents"
+        };
+        frame.render_widget(
+            Paragraph::new(msg)
+                .style(theme.muted_style())
+                .alignment(Alignment::Center),
+            inner,
+        );
+        return;
+    }
+
+    let mut peers = peers;
+    sort_peers(&mut peers, app.peer_sort);
+
+    let header = Row::new(PeerColumn::ALL.iter().map(|column| {
+        let mut label = column.label().to_string();
+        if *column == app.peer_sort.column {
+            label.push(if app.peer_sort.descending {
+                '\u{25bc}'
+            } else {
+                '\u{25b2}'
+            });
+        }
+        Cell::from(label)
+    }))
+    .style(
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let rows = peers.iter().map(|peer| {
+        Row::new(vec![
+            Cell::from(address(peer)),
+            Cell::from(truncate_str(peer.client.as_deref().unwrap_or("?"), 16)),
+            Cell::from(flags(peer)),
+            Cell::from(format!("{:5.1}%", peer.progress * 100.0)),
+            Cell::from(format_speed(peer.download_speed)).style(Style::default().fg(theme.teal)),
+            Cell::from(format_speed(peer.upload_speed)).style(Style::default().fg(theme.mauve)),
+        ])
+        .style(Style::default().fg(theme.text))
+    });
+
+    let widths = [
+        Constraint::Fill(2),
+        Constraint::Fill(1),
+        Constraint::Length(6),
+        Constraint::Length(9),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ];
+    frame.render_widget(Table::new(rows, widths).header(header), inner);
+}
+
+fn address(peer: &PeerInfo) -> String {
+    if peer.ip.contains(':') {
+        format!("[{}]:{}", peer.ip, peer.port)
+    } else {
+        format!("{}:{}", peer.ip, peer.port)
+    }
+}
+
+/// D/U: data flowing down/up, c: peer is choking us, x: we are choking the peer
+fn flags(peer: &PeerInfo) -> String {
+    [
+        (peer.download_speed > 0, 'D'),
+        (peer.upload_speed > 0, 'U'),
+        (peer.peer_choking, 'c'),
+        (peer.am_choking, 'x'),
+    ]
+    .iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, flag)| *flag)
+    .collect()
+}
+
+fn sort_peers(peers: &mut [&PeerInfo], sort: PeerSort) {
+    peers.sort_by(|a, b| {
+        let ordering = match sort.column {
+            PeerColumn::Address => (&a.ip, a.port).cmp(&(&b.ip, b.port)),
+            PeerColumn::Client => a.client.cmp(&b.client),
+            PeerColumn::Flags => flags(a).cmp(&flags(b)),
+            PeerColumn::Progress => a
+                .progress
+                .partial_cmp(&b.progress)
+                .unwrap_or(Ordering::Equal),
+            PeerColumn::Down => a.download_speed.cmp(&b.download_speed),
+            PeerColumn::Up => a.upload_speed.cmp(&b.upload_speed),
+        };
+        if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(ip: &str, down: u64, progress: f64) -> PeerInfo {
+        PeerInfo {
+            id: None,
+            ip: ip.to_string(),
+            port: 6881,
+            client: None,
+            download_speed: down,
+            upload_speed: 0,
+            progress,
+            am_choking: true,
+            peer_choking: false,
+        }
+    }
+
+    #[test]
+    fn sorts_by_selected_column() {
+        let a = peer("10.0.0.2", 100, 0.5);
+        let b = peer("10.0.0.1", 300, 1.0);
+        let c = peer("::1", 200, 0.0);
+        let mut peers = vec![&a, &b, &c];
+
+        sort_peers(&mut peers, PeerSort::default());
+        assert_eq!(peers[0].ip, "10.0.0.1");
+        assert_eq!(peers[2].ip, "10.0.0.2");
+
+        sort_peers(
+            &mut peers,
+            PeerSort {
+                column: PeerColumn::Progress,
+                descending: false,
+            },
+        );
+        assert_eq!(peers[0].ip, "::1");
+
+        assert_eq!(address(&c), "[::1]:6881");
+        assert_eq!(flags(&a), "Dx");
+    }
+}
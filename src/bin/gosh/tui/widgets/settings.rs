@@ -5,7 +5,10 @@ use ratatui::{
 
 use super::btop_border::btop_block;
 use super::dialogs::centered_rect;
-use crate::tui::app::{DialogState, TuiApp};
+use crate::config::CliConfig;
+use crate::tui::app::{DialogState, TuiApp, SCHEDULE_TAB};
+use crate::tui::schedule_editor::{self, RuleEditor, DAYS_ROW, FIELDS, WEEK};
+use crate::tui::theme::Theme;
 
 pub fn render_settings(frame: &mut Frame, dialog: &DialogState, app: &TuiApp) {
     let DialogState::Settings {
@@ -14,6 +17,7 @@ pub fn render_settings(frame: &mut Frame, dialog: &DialogState, app: &TuiApp) {
         editing,
         draft,
         dirty: _,
+        rule_editor,
     } = dialog
     else {
         return;
@@ -59,9 +63,42 @@ pub fn render_settings(frame: &mut Frame, dialog: &DialogState, app: &TuiApp) {
         .style(Style::default().fg(theme.surface1));
     frame.render_widget(sep, Rect::new(inner.x + 1, sep_y, inner.width - 2, 1));
 
+    let content_y = sep_y + 1;
+    let content = Rect::new(
+        inner.x,
+        content_y,
+        inner.width,
+        inner.height.saturating_sub(3),
+    );
+    let footer_y = inner.y + inner.height - 1;
+    let footer_hint = |hint: &str| {
+        Paragraph::new(Line::from(Span::styled(
+            format!("  {}", hint),
+            Style::default().fg(theme.overlay0),
+        )))
+    };
+
+    if *active_tab == SCHEDULE_TAB {
+        let hint = match (rule_editor, editing) {
+            (_, Some(_)) => "Type to edit | Enter: confirm | Esc: cancel",
+            (Some(_), None) => "j/k: field | Enter: edit | h/l + Space: days | Esc: back to rules",
+            (None, None) => {
+                "j/k: select | Enter: open | a: add | d: delete | J/K: move | Esc: save & close"
+            }
+        };
+        match rule_editor {
+            Some(open) => render_rule(frame, content, draft, *open, editing.as_deref(), theme),
+            None => render_rules(frame, content, draft, *selected_row, theme),
+        }
+        frame.render_widget(
+            footer_hint(hint),
+            Rect::new(inner.x, footer_y, inner.width, 1),
+        );
+        return;
+    }
+
     // Settings rows
     let row_count = TuiApp::settings_row_count(*active_tab);
-    let content_y = sep_y + 1;
     let content_height = (inner.height as usize).saturating_sub(3);
 
     for row in 0..row_count {
@@ -130,15 +167,127 @@ pub fn render_settings(frame: &mut Frame, dialog: &DialogState, app: &TuiApp) {
     }
 
     // Footer hint
-    let footer_y = inner.y + inner.height - 1;
     let hint = if editing.is_some() {
         "Type to edit | Enter: confirm | Esc: cancel"
     } else {
         "j/k: navigate | Enter/Space: edit | Left/Right: tabs | Esc: save & close"
     };
-    let footer = Paragraph::new(Line::from(Span::styled(
-        format!("  {}", hint),
+    frame.render_widget(
+        footer_hint(hint),
+        Rect::new(inner.x, footer_y, inner.width, 1),
+    );
+}
+
+fn row_style(theme: &Theme, selected: bool) -> Style {
+    if selected {
+        Style::default().fg(theme.text).bg(theme.surface0)
+    } else {
+        Style::default().fg(theme.subtext0)
+    }
+}
+
+/// The rule list, first match first, with an "add" row at the end
+fn render_rules(frame: &mut Frame, area: Rect, draft: &CliConfig, selected: usize, theme: &Theme) {
+    let rules = &draft.schedule.rules;
+    let mut lines: Vec<Line> = rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            Line::from(Span::styled(
+                format!("  {:>2}. {}", i + 1, schedule_editor::summary(rule)),
+                row_style(theme, i == selected),
+            ))
+        })
+        .collect();
+    lines.push(Line::from(Span::styled(
+        "   +  Add rule",
+        row_style(theme, selected == rules.len()).fg(theme.accent),
+    )));
+    if rules.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "  No rules: bandwidth is only limited by the Network tab",
+            Style::default().fg(theme.overlay0),
+        )));
+    }
+
+    // Keep the selection in view
+    let skip = (selected + 1).saturating_sub(area.height as usize);
+    let visible: Vec<Line> = lines.into_iter().skip(skip).collect();
+    frame.render_widget(Paragraph::new(visible), area);
+}
+
+/// The fields of one rule, with a day picker
+fn render_rule(
+    frame: &mut Frame,
+    area: Rect,
+    draft: &CliConfig,
+    open: RuleEditor,
+    editing: Option<&str>,
+    theme: &Theme,
+) {
+    let Some(rule) = draft.schedule.rules.get(open.index) else {
+        return;
+    };
+    let label_width = (area.width / 2).min(30) as usize;
+
+    let mut lines = vec![Line::from(Span::styled(
+        format!("  Rule {}", open.index + 1),
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    for (row, label) in FIELDS.iter().enumerate() {
+        let selected = row == open.row;
+        let mut spans = vec![Span::styled(
+            format!("  {:<width$}", label, width = label_width),
+            row_style(theme, selected),
+        )];
+
+        if row == DAYS_ROW {
+            for (i, day) in WEEK.iter().enumerate() {
+                let on = schedule_editor::day_enabled(rule, i);
+                let mut style = if on {
+                    Style::default().fg(theme.success)
+                } else {
+                    Style::default().fg(theme.overlay0)
+                };
+                if selected && i == open.day {
+                    style = style.bg(theme.surface1).add_modifier(Modifier::BOLD);
+                }
+                let name = day.to_string();
+                spans.push(Span::styled(format!(" {} ", &name[..2]), style));
+            }
+        } else {
+            let (value, style) = match editing {
+                Some(buf) if selected => (
+                    format!(" {} ", buf),
+                    Style::default().fg(theme.accent).bg(theme.surface0),
+                ),
+                _ => {
+                    let value = schedule_editor::field_value(rule, row);
+                    if value.is_empty() {
+                        (" none ".to_string(), Style::default().fg(theme.overlay0))
+                    } else {
+                        (format!(" {} ", value), Style::default().fg(theme.text))
+                    }
+                }
+            };
+            spans.push(Span::styled(value, style));
+            if selected && editing.is_some() {
+                spans.push(Span::styled(
+                    " [editing]",
+                    Style::default().fg(theme.warning),
+                ));
+            }
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  Actions: pause_all, resume_all, max_concurrent_downloads=N, seeding=off",
         Style::default().fg(theme.overlay0),
     )));
-    frame.render_widget(footer, Rect::new(inner.x, footer_y, inner.width, 1));
+    frame.render_widget(Paragraph::new(lines), area);
 }
@@ -1,6 +1,9 @@
 use ratatui::{prelude::*, text::Line, widgets::Paragraph};
 
+use crate::format::format_duration;
+use crate::supervisor::SeedingInfo;
 use crate::tui::app::TuiApp;
+use crate::util::truncate_str;
 
 pub fn render_status_bar(frame: &mut Frame, area: Rect, app: &TuiApp) {
     let theme = app.theme();
@@ -34,5 +37,44 @@ pub fn render_status_bar(frame: &mut Frame, area: Rect, app: &TuiApp) {
     }
 
     let status = Paragraph::new(Line::from(spans)).style(Style::default().bg(theme.bg_deep));
-    frame.render_widget(status, area);
+
+    if app.seeding.is_empty() {
+        frame.render_widget(status, area);
+        return;
+    }
+
+    // Seeding torrents take the right-hand side, up to half the bar
+    let seeding = app
+        .seeding
+        .iter()
+        .map(seeding_label)
+        .collect::<Vec<_>>()
+        .join("  ");
+    let width = (seeding.chars().count() as u16 + 2).min(area.width / 2);
+    let [keys_area, seeding_area] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(width)]).areas(area);
+
+    frame.render_widget(status, keys_area);
+    frame.render_widget(
+        Paragraph::new(format!(" {} ", seeding))
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(theme.teal).bg(theme.bg_deep)),
+        seeding_area,
+    );
+}
+
+/// "↑ name 0.42/1.00 3:12:00": ratio so far against the target, and time left
+fn seeding_label(info: &SeedingInfo) -> String {
+    let mut label = format!(
+        "\u{2191} {} {:.2}",
+        truncate_str(&info.name, 20),
+        info.ratio
+    );
+    if let Some(target) = info.target_ratio {
+        label.push_str(&format!("/{:.2}", target));
+    }
+    if let Some(left) = info.time_left {
+        label.push_str(&format!(" {}", format_duration(left.as_secs())));
+    }
+    label
 }
@@ -1,5 +1,9 @@
 use anyhow::{bail, Result};
-use gosh_dl::{DownloadEngine, DownloadId};
+use chrono::Utc;
+use gosh_dl::{DownloadEngine, DownloadId, EngineError};
+
+use crate::filter::Filter;
+use crate::store::Store;
 
 /// Parse a download ID string, supporting both full UUIDs and short GIDs.
 ///
@@ -22,7 +26,7 @@ pub fn resolve_download_id(s: &str, engine: &DownloadEngine) -> Result<DownloadI
         .collect();
 
     match matches.len() {
-        0 => bail!("Download not found: {}", s),
+        0 => Err(EngineError::NotFound(s.to_string()).into()),
         1 => Ok(matches[0].id),
         _ => {
             // Multiple matches - show them
@@ -54,6 +58,37 @@ pub fn resolve_download_ids(
     ids.iter().map(|s| resolve_download_id(s, engine)).collect()
 }
 
+/// Resolve download IDs as `resolve_download_ids` does, keeping only those
+/// with `tag` and matching `where_`. A tag or expression without IDs stands
+/// for "all".
+pub fn resolve_selected_ids(
+    ids: &[String],
+    tag: Option<&str>,
+    where_: Option<&Filter>,
+    engine: &DownloadEngine,
+    store: &Store,
+    filter: impl Fn(&gosh_dl::DownloadStatus) -> bool,
+) -> Result<Vec<DownloadId>> {
+    if tag.is_none() && where_.is_none() {
+        return resolve_download_ids(ids, engine, filter);
+    }
+    let all = ["all".to_string()];
+    let ids = if ids.is_empty() { &all[..] } else { ids };
+    let mut ids = resolve_download_ids(ids, engine, filter)?;
+    if let Some(tag) = tag {
+        ids.retain(|id| store.has_tag(*id, tag));
+    }
+    if let Some(where_) = where_ {
+        let now = Utc::now();
+        ids.retain(|id| {
+            engine
+                .status(*id)
+                .is_some_and(|dl| where_.matches(&dl, store, now))
+        });
+    }
+    Ok(ids)
+}
+
 /// Truncate a string to `max_len` bytes, appending "..." if truncated.
 /// Safe for UTF-8: always cuts on a char boundary.
 pub fn truncate_str(s: &str, max_len: usize) -> String {
@@ -119,6 +154,45 @@ pub fn parse_speed(s: &str) -> Result<u64> {
     }
 }
 
+/// Parse a duration such as "90", "30m", "24h", "7d" or "1h30m" into seconds.
+///
+/// A bare number is taken as seconds. Units are `s`, `m`, `h`, `d` and `w`.
+pub fn parse_duration(s: &str) -> Result<u64> {
+    let s = s.trim().to_lowercase();
+    if s.is_empty() {
+        bail!("Duration cannot be empty");
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(secs);
+    }
+
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => bail!("Invalid duration '{}'. Use e.g. 30s, 10m, 24h or 7d", s),
+        };
+        if digits.is_empty() {
+            bail!("Invalid duration '{}'. Use e.g. 30s, 10m, 24h or 7d", s);
+        }
+        total = total.saturating_add(digits.parse::<u64>()?.saturating_mul(unit));
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        bail!("Invalid duration '{}': missing unit after {}", s, digits);
+    }
+    Ok(total)
+}
+
 /// Parse a comma-separated file index list into a non-empty vector.
 pub fn parse_selected_files(s: &str) -> Result<Vec<usize>> {
     let mut indices = Vec::new();
@@ -158,6 +232,55 @@ pub fn validate_seed_ratio(seed_ratio: Option<f64>) -> Result<Option<f64>> {
     }
 }
 
+/// Fixtures shared by unit tests across modules
+#[cfg(test)]
+pub mod testing {
+    use gosh_dl::{
+        DownloadId, DownloadKind, DownloadMetadata, DownloadPriority, DownloadProgress,
+        DownloadState, DownloadStatus,
+    };
+
+    /// A download status with the given kind, state and sizes
+    pub fn status(
+        kind: DownloadKind,
+        state: DownloadState,
+        total: u64,
+        completed: u64,
+    ) -> DownloadStatus {
+        DownloadStatus {
+            id: DownloadId::new(),
+            kind,
+            state,
+            priority: DownloadPriority::Normal,
+            progress: DownloadProgress {
+                total_size: Some(total),
+                completed_size: completed,
+                ..Default::default()
+            },
+            metadata: DownloadMetadata {
+                name: "file.bin".to_string(),
+                url: None,
+                magnet_uri: None,
+                info_hash: None,
+                save_dir: std::path::PathBuf::from("/tmp"),
+                filename: None,
+                user_agent: None,
+                referer: None,
+                headers: Vec::new(),
+                cookies: Vec::new(),
+                checksum: None,
+                mirrors: Vec::new(),
+                etag: None,
+                last_modified: None,
+            },
+            torrent_info: None,
+            peers: None,
+            created_at: chrono::Utc::now(),
+            completed_at: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,6 +368,24 @@ mod tests {
         assert!(parse_speed("abc").is_err());
     }
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), 90);
+        assert_eq!(parse_duration("30m").unwrap(), 1800);
+        assert_eq!(parse_duration("24h").unwrap(), 86_400);
+        assert_eq!(parse_duration("7d").unwrap(), 604_800);
+        assert_eq!(parse_duration("1h30m").unwrap(), 5400);
+        assert_eq!(parse_duration(" 2H ").unwrap(), 7200);
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+
     #[test]
     fn test_parse_selected_files() {
         assert_eq!(parse_selected_files("1, 2,3").unwrap(), vec![1, 2, 3]);
@@ -1,11 +1,34 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
+use tempfile::TempDir;
 
 fn gosh() -> Command {
     #[allow(deprecated)]
     Command::cargo_bin("gosh").unwrap()
 }
 
+/// `gosh` with a config that keeps its database in `dir`
+fn gosh_with_db(dir: &TempDir) -> Command {
+    gosh_with_config(dir, "")
+}
+
+/// `gosh_with_db` with extra `[general]` settings
+fn gosh_with_config(dir: &TempDir, general: &str) -> Command {
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[general]\ndatabase_path = {:?}\n{}",
+            dir.path().join("gosh.db").display().to_string(),
+            general
+        ),
+    )
+    .unwrap();
+    let mut cmd = gosh();
+    cmd.arg("--config").arg(config);
+    cmd
+}
+
 #[test]
 fn test_version() {
     gosh()
@@ -59,6 +82,420 @@ fn test_info_missing_file() {
         .failure();
 }
 
+#[test]
+fn test_seed_requires_data_dir() {
+    gosh()
+        .args(["seed", "nonexistent.torrent"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--data"));
+}
+
+#[test]
+fn test_seed_missing_file() {
+    gosh()
+        .args(["seed", "nonexistent.torrent", "--data", "."])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_fetch_metadata_rejects_non_magnet() {
+    gosh()
+        .args(["fetch-metadata", "https://example.com/file.zip"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("magnet"));
+}
+
+#[test]
+fn test_move_requires_position() {
+    gosh().args(["move", "abc123"]).assert().failure();
+
+    let dir = TempDir::new().unwrap();
+    gosh_with_db(&dir)
+        .args(["move", "0123456789abcdef", "first"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid position 'first'"));
+}
+
+#[test]
+fn test_after_failure_requires_after() {
+    gosh()
+        .args(["add", "--after-failure", "start", "http://example.com/a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--after <GID>"));
+}
+
+#[test]
+fn test_schedule_rejects_unknown_day() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        "[[schedule.rules]]\nstart = \"22:00\"\nend = \"06:00\"\ndays = \"mon,funday\"\n",
+    )
+    .unwrap();
+
+    gosh()
+        .arg("--config")
+        .arg(&config)
+        .args(["schedule", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown day 'funday'"));
+}
+
+#[test]
+fn test_category_config_is_validated() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        "[[categories]]\nname = \"iso\"\nmax_speed = \"fast\"\n",
+    )
+    .unwrap();
+
+    gosh()
+        .arg("--config")
+        .arg(&config)
+        .args(["pause", "--tag", "iso"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("categories[0]"));
+}
+
+#[test]
+fn test_quota_rejects_bad_reset_day() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        "[quota]\nmonthly_download = \"500G\"\nreset_day = 31\n",
+    )
+    .unwrap();
+
+    gosh()
+        .arg("--config")
+        .arg(&config)
+        .arg("quota")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("quota.reset_day"));
+}
+
+#[test]
+fn test_retry_with_nothing_failed() {
+    let dir = TempDir::new().unwrap();
+
+    gosh_with_db(&dir)
+        .args(["retry", "all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No failed downloads to retry"));
+    gosh_with_db(&dir)
+        .args(["retry", "all", "--new-url", "https://example.com/a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("single download"));
+}
+
+#[test]
+fn test_purge_with_nothing_finished() {
+    let dir = TempDir::new().unwrap();
+    let retention = "retention = { completed = \"30d\" }\n";
+
+    gosh_with_config(&dir, retention)
+        .args(["purge", "--completed", "--older-than", "7d"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No downloads to purge"));
+    gosh_with_config(&dir, retention)
+        .args(["purge", "--older-than", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--older-than"));
+}
+
+#[test]
+fn test_export_import_empty_queue() {
+    let dir = TempDir::new().unwrap();
+
+    let output = gosh_with_db(&dir)
+        .args(["export", "--state", "paused,waiting"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let export: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(export["version"], 1);
+    assert_eq!(export["downloads"], serde_json::json!([]));
+
+    let file = dir.path().join("queue.json");
+    std::fs::write(&file, &output.stdout).unwrap();
+    gosh_with_db(&dir)
+        .arg("import")
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No downloads to import"));
+
+    std::fs::write(&file, "{}").unwrap();
+    gosh_with_db(&dir)
+        .args(["import", "--dry-run"])
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid export file"));
+}
+
+#[test]
+fn test_import_aria2_session_dry_run() {
+    let dir = TempDir::new().unwrap();
+    let session = dir.path().join("session");
+    std::fs::write(
+        &session,
+        "https://example.com/a.iso\n gid=2089b05ecca3d829\n pause=true\n\
+         https://example.com/files.meta4\n gid=3089b05ecca3d829\n",
+    )
+    .unwrap();
+
+    gosh_with_db(&dir)
+        .args(["import", "--dry-run", "--aria2-session"])
+        .arg(&session)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Would import: https://example.com/a.iso (http)",
+        ))
+        .stderr(predicate::str::contains(
+            "Skipped metalink downloads, which are not supported: line 4 (https://example.com/files.meta4)",
+        ));
+    gosh_with_db(&dir)
+        .args(["import", "queue.json", "--aria2-session"])
+        .arg(&session)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_pause_requires_ids_or_tag() {
+    gosh()
+        .arg("pause")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<IDS>"));
+}
+
+#[test]
+fn test_where_dry_run() {
+    let dir = TempDir::new().unwrap();
+
+    gosh_with_db(&dir)
+        .args([
+            "pause",
+            "--where",
+            "kind=torrent and state=seeding",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would pause 0 download(s)"));
+    gosh_with_db(&dir)
+        .args(["priority", "--where", "kind=http", "high"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No downloads match"));
+    gosh_with_db(&dir)
+        .args(["list", "--where", "colour=red"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown field 'colour'"));
+}
+
+#[test]
+fn test_list_ratio_of_seeded_torrent() {
+    let dir = TempDir::new().unwrap();
+    let data = dir.path().join("data");
+    std::fs::create_dir(&data).unwrap();
+    std::fs::write(data.join("a.txt"), "hello gosh\n").unwrap();
+    // One piece: the SHA-1 of "hello gosh\n"
+    let mut torrent =
+        b"d4:infod6:lengthi11e4:name5:a.txt12:piece lengthi16384e6:pieces20:".to_vec();
+    torrent.extend_from_slice(
+        b"\x30\x6a\x59\xa5\xdf\xa7\x4e\xcb\xe7\x55\xe1\x8b\x56\x52\x55\xd6\xc9\x6b\xbb\x1c",
+    );
+    torrent.extend_from_slice(b"ee");
+    let file = dir.path().join("a.torrent");
+    std::fs::write(&file, torrent).unwrap();
+
+    gosh_with_db(&dir)
+        .arg("seed")
+        .arg(&file)
+        .arg("--data")
+        .arg(&data)
+        .assert()
+        .success();
+    gosh_with_db(&dir)
+        .args(["list", "--columns", "gid,ratio"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0.00"));
+}
+
+#[test]
+fn test_csv_output_and_format_templates() {
+    let dir = TempDir::new().unwrap();
+
+    gosh_with_db(&dir)
+        .args(["--output", "csv", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("gid,id,name,kind,state,"));
+    gosh_with_db(&dir)
+        .args(["--format", "{active}/{total}", "stats"])
+        .assert()
+        .success()
+        .stdout("0/0\n");
+    gosh_with_db(&dir)
+        .args(["--format", r"{gid}\t{colour}", "list"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown field 'colour'"));
+    gosh_with_db(&dir)
+        .args(["list", "--columns", "gid,colour"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("colour"));
+    gosh_with_db(&dir)
+        .args(["--output", "tsv", "quota"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("not supported by this command"));
+}
+
+#[test]
+fn test_watch_flags() {
+    gosh()
+        .args(["list", "--watch", "--ids-only"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    let dir = TempDir::new().unwrap();
+    gosh_with_db(&dir)
+        .args(["top", "--interval", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--interval must be at least 1s"));
+}
+
+#[test]
+fn test_progress_fd_needs_json() {
+    gosh()
+        .args(["--progress-fd", "3", "http://127.0.0.1:9/a.iso"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--progress-fd needs --progress json",
+        ));
+}
+
+#[test]
+fn test_progress_modes() {
+    gosh()
+        .args(["--progress-interval", "0%", "http://127.0.0.1:9/a.iso"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--progress-interval"));
+    gosh()
+        .args([
+            "add",
+            "--wait",
+            "--progress",
+            "json",
+            "http://127.0.0.1:9/a.iso",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--progress json is only supported for direct downloads",
+        ));
+}
+
+#[test]
+fn test_exit_codes() {
+    gosh()
+        .args(["help", "exit-codes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("checksum"))
+        .stdout(predicate::str::contains("130"));
+    gosh()
+        .arg("--exit-code-map")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("permission_denied"));
+    gosh()
+        .args(["help", "add"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--wait"));
+    gosh().args(["help", "nope"]).assert().code(2);
+    gosh()
+        .arg("not a url")
+        .assert()
+        .code(9)
+        .stderr(predicate::str::contains("Cannot determine input type"));
+
+    let dir = TempDir::new().unwrap();
+    gosh_with_db(&dir)
+        .args(["status", "0123456789abcdef"])
+        .assert()
+        .code(10)
+        .stderr(predicate::str::contains("Download not found"));
+}
+
+#[test]
+fn test_log_file() {
+    let dir = TempDir::new().unwrap();
+    let log = dir.path().join("logs").join("gosh.log");
+    let logging = |rotate: &str| {
+        format!(
+            "log_file = {:?}\nlog_level = \"trace\"\nlog_format = \"json\"\nlog_rotate = {:?}\n",
+            log.display().to_string(),
+            rotate
+        )
+    };
+
+    gosh_with_config(&dir, &logging("weekly"))
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("general.log_rotate"));
+
+    gosh_with_config(&dir, &logging("1M"))
+        .arg("list")
+        .assert()
+        .success();
+    let first = std::fs::read_to_string(&log).unwrap();
+    let first = first.lines().next().expect("log file is empty");
+    let record: serde_json::Value = serde_json::from_str(first).unwrap();
+    assert!(record["level"].is_string(), "{}", first);
+    assert!(record["fields"].is_object(), "{}", first);
+}
+
+#[test]
+fn test_add_report_requires_wait() {
+    gosh()
+        .args(["add", "--report", "report.json", "http://127.0.0.1:9/a.iso"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--wait"));
+}
+
 #[test]
 fn test_no_color_env() {
     gosh().arg("--help").env("NO_COLOR", "1").assert().success();